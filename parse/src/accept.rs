@@ -0,0 +1,282 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag_no_case, take_while, take_while1, take_while_m_n},
+    character::complete::char,
+    combinator::{map, opt, recognize},
+    sequence::{pair, preceded, tuple},
+};
+
+use crate::{
+    header::{is_ows, is_tchar, HeaderError},
+    parse::ParseResult,
+    reject,
+};
+
+// Registered aliases of the charsets most often named in Accept-Charset, each row starts with the
+// preferred MIME name.
+// https://www.iana.org/assignments/character-sets/character-sets.xhtml
+const CHARSET_ALIASES: &[&[&str]] = &[
+    &["utf-8", "utf8", "csutf8"],
+    &[
+        "us-ascii",
+        "ascii",
+        "us",
+        "iso646-us",
+        "ansi_x3.4-1968",
+        "cp367",
+        "ibm367",
+        "csascii",
+    ],
+    &[
+        "iso-8859-1",
+        "latin1",
+        "l1",
+        "iso_8859-1",
+        "iso8859-1",
+        "iso-ir-100",
+        "cp819",
+        "ibm819",
+        "csisolatin1",
+    ],
+    &["windows-1252", "cp1252", "cswindows1252"],
+    &["shift_jis", "ms_kanji", "csshiftjis"],
+    &["euc-jp", "cseucpkdfmtjapanese"],
+    &["gb2312", "csgb2312"],
+    &["big5", "csbig5"],
+    &["koi8-r", "cskoi8r"],
+];
+
+/// The charsets accepted by a client, from an `Accept-Charset` header field.
+///
+/// Charset names are compared case-insensitively, and registered aliases of common charsets
+/// such as `latin1` for `ISO-8859-1` are treated as the same charset.
+///
+/// See [RFC9110](https://www.rfc-editor.org/rfc/rfc9110#section-12.5.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptCharset<'a> {
+    preferences: Vec<(&'a str, u16)>,
+}
+
+impl<'a> AcceptCharset<'a> {
+    /// Parse an `Accept-Charset` field value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an element is not a charset or `*` followed by an optional weight.
+    pub fn parse(input: &'a str) -> Result<Self, HeaderError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, HeaderError> {
+        // Accept-Charset = #( ( token / "*" ) [ weight ] )
+        let mut preferences = Vec::new();
+        let mut i = input;
+
+        loop {
+            i = i.trim_start_matches([',', ' ', '\t']);
+            if i.is_empty() {
+                break;
+            }
+
+            let Ok((rest, preference)) = parse_charset_preference(i) else {
+                return Err(HeaderError::InvalidValue {
+                    offset: input.len() - i.len(),
+                });
+            };
+            preferences.push(preference);
+
+            i = rest.trim_start_matches(is_ows);
+            if !i.is_empty() && !i.starts_with(',') {
+                return Err(HeaderError::InvalidValue {
+                    offset: input.len() - i.len(),
+                });
+            }
+        }
+
+        Ok(Self { preferences })
+    }
+
+    /// Every charset and its weight in thousandths, in the order they appeared.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, u16)> + '_ {
+        self.preferences.iter().copied()
+    }
+
+    /// The weight in thousandths the client gives a charset, 0 if it is not acceptable.
+    ///
+    /// A charset named in the field uses its own weight, otherwise the weight of `*` is used.
+    /// Without `*`, charsets that are not named are not acceptable.
+    #[must_use]
+    pub fn quality(&self, charset: &str) -> u16 {
+        let charset = canonical_charset(charset);
+
+        self.preferences
+            .iter()
+            .find(|(name, _)| canonical_charset(name).eq_ignore_ascii_case(charset))
+            .or_else(|| self.preferences.iter().find(|(name, _)| *name == "*"))
+            .map_or(0, |&(_, quality)| quality)
+    }
+
+    /// Choose the charset to respond with from those the server supports.
+    ///
+    /// Returns the acceptable charset with the highest weight, preferring the earliest in
+    /// `supported` when weights are equal, or `None` if none are acceptable.
+    #[must_use]
+    pub fn negotiate<'s>(&self, supported: &[&'s str]) -> Option<&'s str> {
+        supported
+            .iter()
+            .map(|&charset| (charset, self.quality(charset)))
+            .filter(|&(_, quality)| quality > 0)
+            // max_by_key returns the last maximum, so reverse to prefer the first
+            .rev()
+            .max_by_key(|&(_, quality)| quality)
+            .map(|(charset, _)| charset)
+    }
+}
+
+// Map a registered alias to the preferred name of its charset
+fn canonical_charset(charset: &'_ str) -> &'_ str {
+    CHARSET_ALIASES
+        .iter()
+        .find(|aliases| {
+            aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(charset))
+        })
+        .map_or(charset, |aliases| aliases[0])
+}
+
+fn parse_charset_preference(i: &'_ str) -> ParseResult<'_, (&'_ str, u16)> {
+    let (i, charset) = take_while1(is_tchar)(i)?;
+    let (i, quality) = opt(parse_weight)(i)?;
+
+    Ok((i, (charset, quality.unwrap_or(1000))))
+}
+
+// weight = OWS ";" OWS "q=" qvalue
+fn parse_weight(i: &'_ str) -> ParseResult<'_, u16> {
+    preceded(
+        tuple((
+            take_while(is_ows),
+            char(';'),
+            take_while(is_ows),
+            tag_no_case("q="),
+        )),
+        parse_qvalue,
+    )(i)
+}
+
+// qvalue = ( "0" [ "." 0*3DIGIT ] )
+//        / ( "1" [ "." 0*3("0") ] )
+fn parse_qvalue(i: &'_ str) -> ParseResult<'_, u16> {
+    alt((
+        map(
+            preceded(
+                char('0'),
+                opt(preceded(
+                    char('.'),
+                    take_while_m_n(0, 3, |c: char| c.is_ascii_digit()),
+                )),
+            ),
+            |digits: Option<&str>| {
+                digits
+                    .unwrap_or_default()
+                    .bytes()
+                    .chain(std::iter::repeat(b'0'))
+                    .take(3)
+                    .fold(0, |quality, c| quality * 10 + u16::from(c - b'0'))
+            },
+        ),
+        map(
+            recognize(pair(
+                char('1'),
+                opt(pair(char('.'), take_while_m_n(0, 3, |c| c == '0'))),
+            )),
+            |_| 1000,
+        ),
+    ))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_charset() {
+        let accept = AcceptCharset::parse("iso-8859-5, unicode-1-1;q=0.8").unwrap();
+        assert_eq!(
+            vec![("iso-8859-5", 1000), ("unicode-1-1", 800)],
+            accept.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(1000, accept.quality("ISO-8859-5"));
+        assert_eq!(800, accept.quality("unicode-1-1"));
+        assert_eq!(0, accept.quality("utf-8"));
+
+        let accept =
+            AcceptCharset::parse("utf-8;q=0.9 , latin1 ;Q=1, *;q=0.1,,koi8-r;q=0").unwrap();
+        assert_eq!(900, accept.quality("UTF8"));
+        assert_eq!(1000, accept.quality("ISO-8859-1"));
+        assert_eq!(100, accept.quality("shift_jis"));
+        assert_eq!(0, accept.quality("KOI8-R"));
+
+        let test_data = [
+            ("0", 0),
+            ("0.", 0),
+            ("0.5", 500),
+            ("0.05", 50),
+            ("0.123", 123),
+            ("1", 1000),
+            ("1.000", 1000),
+        ];
+        for (qvalue, expected) in test_data {
+            let input = format!("utf-8;q={qvalue}");
+            assert_eq!(
+                expected,
+                AcceptCharset::parse(&input).unwrap().quality("utf-8")
+            );
+        }
+
+        assert!(AcceptCharset::parse("").unwrap().iter().next().is_none());
+    }
+
+    #[test]
+    fn test_accept_charset_invalid() {
+        let test_data = [
+            ("utf-8;q=2", 5),
+            ("utf-8;q=0.1234", 13),
+            ("utf-8;q=1.1", 10),
+            ("utf-8;level=1", 5),
+            ("utf 8", 4),
+            ("\"utf-8\"", 0),
+        ];
+
+        for (input, offset) in test_data {
+            let err = AcceptCharset::parse(input).unwrap_err();
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_negotiate_charset() {
+        let test_data = [
+            ("utf-8, iso-8859-1;q=0.5", Some("utf-8")),
+            ("iso-8859-1, utf-8;q=0.5", Some("iso-8859-1")),
+            ("latin1", Some("iso-8859-1")),
+            ("utf-8, iso-8859-1", Some("utf-8")),
+            ("*", Some("utf-8")),
+            ("*;q=0.5, utf-8;q=0.1", Some("iso-8859-1")),
+            ("utf-8;q=0, *", Some("iso-8859-1")),
+            ("shift_jis", None),
+            ("*;q=0", None),
+        ];
+
+        for (input, expected) in test_data {
+            let accept = AcceptCharset::parse(input).unwrap();
+            assert_eq!(
+                expected,
+                accept.negotiate(&["utf-8", "iso-8859-1"]),
+                "{input}"
+            );
+        }
+    }
+}
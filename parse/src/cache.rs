@@ -0,0 +1,846 @@
+use std::{borrow::Cow, fmt};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped, take_while, take_while1, take_while_m_n},
+    character::complete::{char, one_of, satisfy},
+    combinator::{map, map_res, opt, recognize, value},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+};
+
+use crate::{
+    header::{is_ows, is_tchar, HeaderError},
+    parse::ParseResult,
+    reject,
+};
+
+/// One warning from a `Warning` header field.
+///
+/// The field is obsolete, but is still sent by older caches and may need to be forwarded or
+/// removed by newer ones.
+///
+/// Formatting a warning writes the fields as they are, so use
+/// [`to_field_value`](Self::to_field_value) to write one into a header field.
+///
+/// See [RFC7234](https://www.rfc-editor.org/rfc/rfc7234#section-5.5)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning<'a> {
+    /// The three digit warning code, such as `110` for a stale response.
+    pub code: u16,
+    /// The host and port, or pseudonym, of the server or cache adding the warning.
+    pub agent: &'a str,
+    /// The human-readable warning text, unescaped.
+    pub text: Cow<'a, str>,
+    /// The `HTTP-date` the warning was generated, unparsed.
+    pub date: Option<&'a str>,
+}
+
+impl<'a> Warning<'a> {
+    /// Parse the warnings in a `Warning` field value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value contains no warnings or a malformed warning.
+    pub fn parse_list(input: &'a str) -> Result<Vec<Self>, HeaderError> {
+        Self::parse_list_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_list_unreported(input: &'a str) -> Result<Vec<Self>, HeaderError> {
+        // Warning = 1#warning-value
+        //
+        // A recipient MUST parse and ignore a reasonable number of empty list elements
+        let mut warnings = Vec::new();
+        let mut i = input;
+
+        loop {
+            i = i.trim_start_matches([',', ' ', '\t']);
+            if i.is_empty() {
+                break;
+            }
+
+            let Ok((rest, warning)) = parse_warning_value(i) else {
+                return Err(invalid_value(input, i));
+            };
+            warnings.push(warning);
+
+            i = rest.trim_start_matches(is_ows);
+            if !i.is_empty() && !i.starts_with(',') {
+                return Err(invalid_value(input, i));
+            }
+        }
+
+        if warnings.is_empty() {
+            return Err(invalid_value(input, i));
+        }
+
+        Ok(warnings)
+    }
+
+    /// Format the warning as a `Warning` field value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field can not be written in a field value, such as a code above 999
+    /// or text containing a control character other than HTAB. Text containing non-ASCII
+    /// characters is also rejected, as obs-text is only accepted when parsing. The offset is
+    /// that of the first such field or character in the formatted value.
+    pub fn to_field_value(&self) -> Result<String, HeaderError> {
+        self.write().finish()
+    }
+
+    fn write(&self) -> FieldValue {
+        let mut out = FieldValue::default();
+
+        if self.code > 999 {
+            out.reject();
+        }
+        out.push_str(&format!("{:03} ", self.code));
+        if self.agent.is_empty() {
+            out.reject();
+        }
+        out.push_checked(self.agent, is_warn_agent_char);
+        out.push_str(" ");
+        // A sender must not generate obs-text
+        out.push_quoted(&self.text, |c| c == '\t' || matches!(c, ' '..='~'));
+        if let Some(date) = self.date {
+            out.push_str(" \"");
+            if date.is_empty() {
+                out.reject();
+            }
+            out.push_checked(date, is_warn_date_char);
+            out.push_str("\"");
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Warning<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.write().value)
+    }
+}
+
+/// One cache's entry from a `Cache-Status` header field.
+///
+/// Parameters with values of the wrong type, and unknown parameters, are ignored.
+///
+/// Formatting an entry writes the fields as they are, so use
+/// [`to_field_value`](Self::to_field_value) to write one into a header field.
+///
+/// See [RFC9211](https://www.rfc-editor.org/rfc/rfc9211#section-2)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CacheStatus<'a> {
+    /// The identifier of the cache, usually its host name.
+    pub cache: Cow<'a, str>,
+    /// `hit`, the request was satisfied by the cache without contacting the next hop.
+    pub hit: bool,
+    /// `fwd`, why the request was forwarded towards the origin, such as `uri-miss` or `stale`.
+    pub fwd: Option<&'a str>,
+    /// `fwd-status`, the status code the next hop returned for the forwarded request.
+    pub fwd_status: Option<u16>,
+    /// `ttl`, the response's remaining freshness lifetime in seconds, negative if stale.
+    pub ttl: Option<i64>,
+    /// `stored`, the cache stored the forwarded response.
+    pub stored: bool,
+    /// `collapsed`, the forwarded request was collapsed with others.
+    pub collapsed: bool,
+    /// `key`, a representation of the cache key of the response.
+    pub key: Option<Cow<'a, str>>,
+    /// `detail`, implementation-specific information.
+    pub detail: Option<Cow<'a, str>>,
+}
+
+impl<'a> CacheStatus<'a> {
+    /// Parse the entries in a `Cache-Status` field value, a structured field list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not a list of tokens and strings with parameters.
+    pub fn parse_list(input: &'a str) -> Result<Vec<Self>, HeaderError> {
+        Self::parse_list_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_list_unreported(input: &'a str) -> Result<Vec<Self>, HeaderError> {
+        // sf-list = list-member *( OWS "," OWS list-member )
+        let mut entries = Vec::new();
+        let mut i = input.trim_matches(' ');
+
+        while !i.is_empty() {
+            let Ok((rest, entry)) = parse_cache_status_entry(i) else {
+                return Err(invalid_value(input, i));
+            };
+            entries.push(entry);
+
+            i = rest.trim_start_matches(is_ows);
+            if i.is_empty() {
+                break;
+            }
+
+            // A trailing comma is not allowed
+            let Some(rest) = i.strip_prefix(',') else {
+                return Err(invalid_value(input, i));
+            };
+            i = rest.trim_start_matches(is_ows);
+            if i.is_empty() {
+                return Err(invalid_value(input, rest));
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Format the entry as a member of a `Cache-Status` field value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field can not be written as its structured field type, such as a
+    /// `fwd` that is not a token or a string containing a character outside printable ASCII. The
+    /// offset is that of the first such field or character in the formatted value.
+    pub fn to_field_value(&self) -> Result<String, HeaderError> {
+        self.write().finish()
+    }
+
+    fn write(&self) -> FieldValue {
+        let mut out = FieldValue::default();
+
+        out.push_sf_token_or_string(&self.cache);
+        if self.hit {
+            out.push_str(";hit");
+        }
+        if let Some(fwd) = self.fwd {
+            out.push_str(";fwd=");
+            if !is_sf_token(fwd) {
+                out.reject();
+            }
+            out.push_str(fwd);
+        }
+        if let Some(fwd_status) = self.fwd_status {
+            out.push_str(&format!(";fwd-status={fwd_status}"));
+        }
+        if let Some(ttl) = self.ttl {
+            out.push_str(";ttl=");
+            // sf-integer = ["-"] 1*15DIGIT
+            if ttl.unsigned_abs() > 999_999_999_999_999 {
+                out.reject();
+            }
+            out.push_str(&ttl.to_string());
+        }
+        if self.stored {
+            out.push_str(";stored");
+        }
+        if self.collapsed {
+            out.push_str(";collapsed");
+        }
+        if let Some(key) = &self.key {
+            out.push_str(";key=");
+            out.push_quoted(key, is_sf_string_char);
+        }
+        if let Some(detail) = &self.detail {
+            out.push_str(";detail=");
+            out.push_sf_token_or_string(detail);
+        }
+
+        out
+    }
+}
+
+// A field value being formatted, and the offset of the first thing written that can not be part
+// of one
+#[derive(Debug, Default)]
+struct FieldValue {
+    value: String,
+    invalid: Option<usize>,
+}
+
+impl FieldValue {
+    // Mark the next thing written as invalid
+    fn reject(&mut self) {
+        self.invalid.get_or_insert(self.value.len());
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.value.push_str(s);
+    }
+
+    fn push_checked(&mut self, s: &str, is_valid: impl Fn(char) -> bool) {
+        if let Some(n) = s.find(|c| !is_valid(c)) {
+            self.invalid.get_or_insert(self.value.len() + n);
+        }
+        self.value.push_str(s);
+    }
+
+    // Both HTTP quoted-strings and structured field strings escape only DQUOTE and "\"
+    fn push_quoted(&mut self, s: &str, is_valid: impl Fn(char) -> bool) {
+        self.value.push('"');
+        for c in s.chars() {
+            if !is_valid(c) {
+                self.reject();
+            }
+            if c == '"' || c == '\\' {
+                self.value.push('\\');
+            }
+            self.value.push(c);
+        }
+        self.value.push('"');
+    }
+
+    fn push_sf_token_or_string(&mut self, s: &str) {
+        if is_sf_token(s) {
+            self.value.push_str(s);
+        } else {
+            self.push_quoted(s, is_sf_string_char);
+        }
+    }
+
+    fn finish(self) -> Result<String, HeaderError> {
+        match self.invalid {
+            None => Ok(self.value),
+            Some(offset) => Err(HeaderError::InvalidValue { offset }),
+        }
+    }
+}
+
+impl fmt::Display for CacheStatus<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.write().value)
+    }
+}
+
+fn invalid_value(input: &'_ str, i: &'_ str) -> HeaderError {
+    HeaderError::InvalidValue {
+        offset: input.len() - i.len(),
+    }
+}
+
+// warn-agent = ( uri-host [ ":" port ] ) / pseudonym
+fn is_warn_agent_char(c: char) -> bool {
+    c.is_ascii_graphic() && c != '"' && c != ','
+}
+
+// An IMF-fixdate, or one of the obsolete formats
+fn is_warn_date_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == ' ' || c == ':' || c == ','
+}
+
+fn is_sf_token(s: &str) -> bool {
+    matches!(parse_sf_token(s), Ok(("", _)))
+}
+
+// Structured field strings can only contain printable ASCII
+fn is_sf_string_char(c: char) -> bool {
+    matches!(c, ' '..='~')
+}
+
+// Unescape the contents of a quoted string, only allocating if it contains escapes
+fn unescape(s: &'_ str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        out.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+
+    Cow::Owned(out)
+}
+
+// warning-value = warn-code SP warn-agent SP warn-text [ SP warn-date ]
+// warn-code  = 3DIGIT
+// warn-agent = ( uri-host [ ":" port ] ) / pseudonym
+// warn-text  = quoted-string
+// warn-date  = DQUOTE HTTP-date DQUOTE
+fn parse_warning_value(i: &'_ str) -> ParseResult<'_, Warning<'_>> {
+    let (i, code) = map_res(
+        take_while_m_n(3, 3, |c: char| c.is_ascii_digit()),
+        str::parse,
+    )(i)?;
+    let (i, _) = char(' ')(i)?;
+    let (i, agent) = take_while1(is_warn_agent_char)(i)?;
+    let (i, _) = char(' ')(i)?;
+    let (i, text) = parse_quoted_string(i)?;
+    let (i, date) = opt(preceded(
+        char(' '),
+        delimited(char('"'), take_while1(is_warn_date_char), char('"')),
+    ))(i)?;
+
+    Ok((
+        i,
+        Warning {
+            code,
+            agent,
+            text,
+            date,
+        },
+    ))
+}
+
+// quoted-string  = DQUOTE *( qdtext / quoted-pair ) DQUOTE
+// qdtext         = HTAB / SP / %x21 / %x23-5B / %x5D-7E / obs-text
+// quoted-pair    = "\" ( HTAB / SP / VCHAR / obs-text )
+fn parse_quoted_string(i: &'_ str) -> ParseResult<'_, Cow<'_, str>> {
+    let is_qdtext = |c: char| c == '\t' || (c >= ' ' && c != '"' && c != '\\' && c != '\x7F');
+    let is_quoted_pair = |c: char| c == '\t' || (c >= ' ' && c != '\x7F');
+
+    map(
+        delimited(
+            char('"'),
+            opt(escaped(
+                take_while1(is_qdtext),
+                '\\',
+                satisfy(is_quoted_pair),
+            )),
+            char('"'),
+        ),
+        |s| unescape(s.unwrap_or_default()),
+    )(i)
+}
+
+// The parsed value of a structured field item or parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BareItem<'a> {
+    Integer(i64),
+    Decimal,
+    String(Cow<'a, str>),
+    Token(&'a str),
+    ByteSequence,
+    Boolean(bool),
+}
+
+// sf-item   = bare-item parameters
+// Cache-Status list members are a token or string identifying the cache
+fn parse_cache_status_entry(i: &'_ str) -> ParseResult<'_, CacheStatus<'_>> {
+    let (mut i, cache) = alt((parse_sf_string, map(parse_sf_token, Cow::Borrowed)))(i)?;
+    let mut entry = CacheStatus {
+        cache,
+        ..CacheStatus::default()
+    };
+
+    // parameters = *( ";" *SP parameter )
+    // parameter  = param-key [ "=" param-value ]
+    // param-value = bare-item
+    while let Ok((rest, (key, value))) = preceded(
+        pair(char(';'), take_while(|c| c == ' ')),
+        pair(
+            parse_sf_key,
+            map(opt(preceded(char('='), parse_bare_item)), |value| {
+                value.unwrap_or(BareItem::Boolean(true))
+            }),
+        ),
+    )(i)
+    {
+        i = rest;
+
+        match (key, value) {
+            ("hit", BareItem::Boolean(hit)) => entry.hit = hit,
+            ("fwd", BareItem::Token(fwd)) => entry.fwd = Some(fwd),
+            ("fwd-status", BareItem::Integer(status)) => {
+                entry.fwd_status = u16::try_from(status).ok();
+            }
+            ("ttl", BareItem::Integer(ttl)) => entry.ttl = Some(ttl),
+            ("stored", BareItem::Boolean(stored)) => entry.stored = stored,
+            ("collapsed", BareItem::Boolean(collapsed)) => entry.collapsed = collapsed,
+            ("key", BareItem::String(key)) => entry.key = Some(key),
+            ("detail", BareItem::Token(detail)) => entry.detail = Some(Cow::Borrowed(detail)),
+            ("detail", BareItem::String(detail)) => entry.detail = Some(detail),
+            _ => {}
+        }
+    }
+
+    Ok((i, entry))
+}
+
+// bare-item = sf-integer / sf-decimal / sf-string / sf-token / sf-binary / sf-boolean
+fn parse_bare_item(i: &'_ str) -> ParseResult<'_, BareItem<'_>> {
+    alt((
+        // sf-decimal = ["-"] 1*12DIGIT "." 1*3DIGIT
+        value(
+            BareItem::Decimal,
+            tuple((
+                opt(char('-')),
+                take_while_m_n(1, 12, |c: char| c.is_ascii_digit()),
+                char('.'),
+                take_while_m_n(1, 3, |c: char| c.is_ascii_digit()),
+            )),
+        ),
+        // sf-integer = ["-"] 1*15DIGIT
+        map_res(
+            recognize(pair(
+                opt(char('-')),
+                take_while_m_n(1, 15, |c: char| c.is_ascii_digit()),
+            )),
+            |integer: &str| integer.parse().map(BareItem::Integer),
+        ),
+        map(parse_sf_string, BareItem::String),
+        map(parse_sf_token, BareItem::Token),
+        // sf-binary = ":" base64 ":"
+        value(
+            BareItem::ByteSequence,
+            delimited(
+                char(':'),
+                take_while(|c: char| c.is_ascii_alphanumeric() || "+/=".contains(c)),
+                char(':'),
+            ),
+        ),
+        // sf-boolean = "?" ( "0" / "1" )
+        map(preceded(char('?'), one_of("01")), |c| {
+            BareItem::Boolean(c == '1')
+        }),
+    ))(i)
+}
+
+// sf-string = DQUOTE *( unescaped / "%" / bs-escaped ) DQUOTE
+// unescaped = %x20-21 / %x23-24 / %x26-5B / %x5D-7E
+// bs-escaped = "\" ( DQUOTE / "\" )
+fn parse_sf_string(i: &'_ str) -> ParseResult<'_, Cow<'_, str>> {
+    let is_unescaped = |c: char| matches!(c, ' '..='!' | '#'..='[' | ']'..='~');
+
+    map(
+        delimited(
+            char('"'),
+            opt(escaped(take_while1(is_unescaped), '\\', one_of("\"\\"))),
+            char('"'),
+        ),
+        |s| unescape(s.unwrap_or_default()),
+    )(i)
+}
+
+// sf-token = ( ALPHA / "*" ) *( tchar / ":" / "/" )
+fn parse_sf_token(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic() || c == '*'),
+        take_while(|c| is_tchar(c) || c == ':' || c == '/'),
+    ))(i)
+}
+
+// key = ( lcalpha / "*" ) *( lcalpha / DIGIT / "_" / "-" / "." / "*" )
+fn parse_sf_key(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    recognize(terminated(
+        satisfy(|c| c.is_ascii_lowercase() || c == '*'),
+        take_while(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.*".contains(c)),
+    ))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_warning() {
+        let warnings = Warning::parse_list(
+            "110 anderson/1.3.37 \"Response is stale\", ,112 cache.example:8080 \"a \\\"b\\\"\" \"Sun, 06 Nov 1994 08:49:37 GMT\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                Warning {
+                    code: 110,
+                    agent: "anderson/1.3.37",
+                    text: Cow::Borrowed("Response is stale"),
+                    date: None,
+                },
+                Warning {
+                    code: 112,
+                    agent: "cache.example:8080",
+                    text: Cow::Borrowed("a \"b\""),
+                    date: Some("Sun, 06 Nov 1994 08:49:37 GMT"),
+                },
+            ],
+            warnings
+        );
+
+        for warning in &warnings {
+            let serialized = warning.to_field_value().unwrap();
+            assert_eq!(warning.to_string(), serialized);
+            assert_eq!(
+                vec![warning.clone()],
+                Warning::parse_list(&serialized).unwrap()
+            );
+        }
+
+        assert_eq!(
+            "299 - \"\"",
+            Warning::parse_list("299 - \"\"").unwrap()[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_warning_invalid() {
+        let warning = Warning {
+            code: 299,
+            agent: "-",
+            text: Cow::Borrowed("a\t\"b\" \\ c"),
+            date: None,
+        };
+        assert_eq!(
+            vec![warning.clone()],
+            Warning::parse_list(&warning.to_string()).unwrap()
+        );
+
+        let test_data = [
+            (
+                Warning {
+                    code: 1000,
+                    ..warning.clone()
+                },
+                0,
+            ),
+            (
+                Warning {
+                    agent: "",
+                    ..warning.clone()
+                },
+                4,
+            ),
+            (
+                Warning {
+                    agent: "a\r\nSet-Cookie: x=1",
+                    ..warning.clone()
+                },
+                5,
+            ),
+            (
+                Warning {
+                    agent: "a,b",
+                    ..warning.clone()
+                },
+                5,
+            ),
+            (
+                Warning {
+                    text: Cow::Borrowed("a\r\nSet-Cookie: x=1"),
+                    ..warning.clone()
+                },
+                8,
+            ),
+            (
+                Warning {
+                    text: Cow::Borrowed("\0"),
+                    ..warning.clone()
+                },
+                7,
+            ),
+            (
+                Warning {
+                    text: Cow::Borrowed("caf\u{e9}"),
+                    ..warning.clone()
+                },
+                10,
+            ),
+            (
+                Warning {
+                    date: Some("x\"\r\nSet-Cookie: x=1"),
+                    ..warning.clone()
+                },
+                23,
+            ),
+            (
+                Warning {
+                    date: Some(""),
+                    ..warning.clone()
+                },
+                22,
+            ),
+        ];
+
+        for (warning, offset) in test_data {
+            let err = warning.to_field_value().unwrap_err();
+            assert_eq!(offset, err.offset(), "{warning:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_warning_invalid() {
+        let test_data = [
+            ("", 0),
+            (" , ", 3),
+            ("11 agent \"text\"", 0),
+            ("110 agent text", 0),
+            ("110 agent \"text", 0),
+            ("110 agent \"text\" extra", 17),
+            ("110 agent \"text\", 111", 18),
+        ];
+
+        for (input, offset) in test_data {
+            let err = Warning::parse_list(input).unwrap_err();
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_cache_status() {
+        let entries = CacheStatus::parse_list(
+            "ReverseProxyCache; hit, ForwardProxyCache; fwd=uri-miss; collapsed; stored, \"cache 3\";fwd=stale;fwd-status=304;ttl=-20;key=\"a\\\\b\";detail=\"x y\";ext=1.5;hit=?0",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                CacheStatus {
+                    cache: Cow::Borrowed("ReverseProxyCache"),
+                    hit: true,
+                    ..CacheStatus::default()
+                },
+                CacheStatus {
+                    cache: Cow::Borrowed("ForwardProxyCache"),
+                    fwd: Some("uri-miss"),
+                    collapsed: true,
+                    stored: true,
+                    ..CacheStatus::default()
+                },
+                CacheStatus {
+                    cache: Cow::Borrowed("cache 3"),
+                    fwd: Some("stale"),
+                    fwd_status: Some(304),
+                    ttl: Some(-20),
+                    key: Some(Cow::Borrowed("a\\b")),
+                    detail: Some(Cow::Borrowed("x y")),
+                    ..CacheStatus::default()
+                },
+            ],
+            entries
+        );
+
+        assert_eq!(
+            vec![
+                "ReverseProxyCache;hit",
+                "ForwardProxyCache;fwd=uri-miss;stored;collapsed",
+                "\"cache 3\";fwd=stale;fwd-status=304;ttl=-20;key=\"a\\\\b\";detail=\"x y\"",
+            ],
+            entries.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+
+        for entry in &entries {
+            let serialized = entry.to_field_value().unwrap();
+            assert_eq!(entry.to_string(), serialized);
+            assert_eq!(
+                vec![entry.clone()],
+                CacheStatus::parse_list(&serialized).unwrap()
+            );
+        }
+
+        // Parameters of the wrong type are ignored
+        let entries = CacheStatus::parse_list("c; hit=1; fwd=\"miss\"; ttl=?1").unwrap();
+        assert_eq!(
+            vec![CacheStatus {
+                cache: Cow::Borrowed("c"),
+                ..CacheStatus::default()
+            }],
+            entries
+        );
+
+        assert!(CacheStatus::parse_list("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_format_cache_status_invalid() {
+        let entry = CacheStatus {
+            cache: Cow::Borrowed("a \"b\" \\ c"),
+            key: Some(Cow::Borrowed("~ !")),
+            detail: Some(Cow::Borrowed("*tok/en:1")),
+            ..CacheStatus::default()
+        };
+        assert_eq!(
+            vec![entry.clone()],
+            CacheStatus::parse_list(&entry.to_string()).unwrap()
+        );
+
+        let test_data = [
+            (
+                CacheStatus {
+                    cache: Cow::Borrowed("a\r\nSet-Cookie: x=1"),
+                    ..entry.clone()
+                },
+                2,
+            ),
+            (
+                CacheStatus {
+                    cache: Cow::Borrowed("a\tb"),
+                    ..entry.clone()
+                },
+                2,
+            ),
+            (
+                CacheStatus {
+                    cache: Cow::Borrowed("caf\u{e9}"),
+                    ..entry.clone()
+                },
+                4,
+            ),
+            (
+                CacheStatus {
+                    fwd: Some("uri-miss\r\nSet-Cookie: x=1"),
+                    ..entry.clone()
+                },
+                19,
+            ),
+            (
+                CacheStatus {
+                    fwd: Some("1"),
+                    ..entry.clone()
+                },
+                19,
+            ),
+            (
+                CacheStatus {
+                    fwd: Some(""),
+                    ..entry.clone()
+                },
+                19,
+            ),
+            (
+                CacheStatus {
+                    ttl: Some(i64::MIN),
+                    ..entry.clone()
+                },
+                19,
+            ),
+            (
+                CacheStatus {
+                    key: Some(Cow::Borrowed("\x7F")),
+                    ..entry.clone()
+                },
+                20,
+            ),
+            (
+                CacheStatus {
+                    detail: Some(Cow::Borrowed("x\ny")),
+                    ..entry.clone()
+                },
+                34,
+            ),
+        ];
+
+        for (entry, offset) in test_data {
+            let err = entry.to_field_value().unwrap_err();
+            assert_eq!(offset, err.offset(), "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_cache_status_invalid() {
+        let test_data = [
+            ("a,", 2),
+            ("a, ,b", 3),
+            ("1cache", 0),
+            ("(a b)", 0),
+            ("a;HIT", 1),
+            ("a \"b\"", 2),
+            ("\"unterminated", 0),
+        ];
+
+        for (input, offset) in test_data {
+            let err = CacheStatus::parse_list(input).unwrap_err();
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+}
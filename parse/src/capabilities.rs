@@ -0,0 +1,47 @@
+/// The parsing behavior this build of the crate was compiled with, see [`capabilities`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// The version of this crate.
+    pub version: &'static str,
+    /// Whether the `idna` feature is enabled, so hosts may contain Unicode.
+    pub idna: bool,
+    /// The Unicode version of the IDNA mapping table as `(major, minor, micro)`, if the `idna`
+    /// feature is enabled.
+    pub idna_unicode_version: Option<(u16, u16, u16)>,
+}
+
+/// Describe the parsing behavior of this build, for logging which behavior an application
+/// shipped with.
+#[must_use]
+pub fn capabilities() -> Capabilities {
+    #[cfg(feature = "idna")]
+    let idna_unicode_version = {
+        let version = unic_idna_mapping::UNICODE_VERSION;
+        Some((version.major, version.minor, version.micro))
+    };
+    #[cfg(not(feature = "idna"))]
+    let idna_unicode_version = None;
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        idna: cfg!(feature = "idna"),
+        idna_unicode_version,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let capabilities = capabilities();
+        assert_eq!(env!("CARGO_PKG_VERSION"), capabilities.version);
+        assert_eq!(cfg!(feature = "idna"), capabilities.idna);
+        assert_eq!(
+            capabilities.idna,
+            capabilities.idna_unicode_version.is_some()
+        );
+    }
+}
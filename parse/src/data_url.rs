@@ -0,0 +1,414 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    encoding::{base64_decode, Alphabet},
+    error::ErrorCode,
+    header::is_token,
+    percent_encode::percent_decode_lenient,
+    reject,
+};
+
+/// A parsed `data:` URL.
+///
+/// See [RFC2397](https://www.rfc-editor.org/rfc/rfc2397) and
+/// [WHATWG Fetch](https://fetch.spec.whatwg.org/#data-urls)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl<'a> {
+    mime_type: Cow<'a, str>,
+    parameters: Vec<(&'a str, Cow<'a, str>)>,
+    base64: bool,
+    data: Cow<'a, [u8]>,
+}
+
+/// An error encountered while parsing a `data:` URL.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum DataUrlError {
+    /// The input does not start with `data:`.
+    InvalidScheme,
+    /// There is no `,` separating the media type from the data.
+    MissingComma { offset: usize },
+    /// The data is marked `;base64` but is not valid base64.
+    InvalidBase64 { offset: usize },
+}
+
+impl DataUrlError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::MissingComma { .. } | Self::InvalidBase64 { .. } => ErrorCode::InvalidSyntax,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::MissingComma { offset } | Self::InvalidBase64 { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for DataUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a data: URL"),
+            Self::MissingComma { offset } => {
+                write!(f, "missing `,` before the data at offset {offset}")
+            }
+            Self::InvalidBase64 { offset, .. } => {
+                write!(f, "invalid base64 data at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DataUrlError {}
+
+impl<'a> DataUrl<'a> {
+    /// Parse a `data:` URL, decoding its payload.
+    ///
+    /// A missing or malformed media type is replaced by `text/plain;charset=US-ASCII`, and any
+    /// fragment is ignored. Payloads that are not base64 and contain no percent-encoded bytes are
+    /// borrowed from the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `data:` URL, has no `,` before the payload, or is
+    /// marked `;base64` but the payload is not valid base64.
+    pub fn parse(input: &'a str) -> Result<Self, DataUrlError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    // https://fetch.spec.whatwg.org/#data-url-processor
+    fn parse_unreported(input: &'a str) -> Result<Self, DataUrlError> {
+        if !input
+            .get(..5)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+        {
+            return Err(DataUrlError::InvalidScheme);
+        }
+        let url = input[5..].split('#').next().unwrap_or_default();
+
+        let Some((mime_type, body)) = url.split_once(',') else {
+            return Err(DataUrlError::MissingComma {
+                offset: 5 + url.len(),
+            });
+        };
+        let body_offset = 5 + mime_type.len() + 1;
+        let mut mime_type = mime_type.trim_matches(|c: char| c.is_ascii_whitespace());
+
+        let data = percent_decode_lenient(body.as_bytes());
+
+        // If mimeType ends with U+003B (;), followed by zero or more U+0020 SPACE, followed by an
+        // ASCII case-insensitive match for "base64", then remove those characters from mimeType
+        let base64 = mime_type.len() >= 6
+            && mime_type.is_char_boundary(mime_type.len() - 6)
+            && mime_type[mime_type.len() - 6..].eq_ignore_ascii_case("base64")
+            && mime_type[..mime_type.len() - 6]
+                .trim_end_matches(' ')
+                .ends_with(';');
+        let data = if base64 {
+            mime_type = mime_type[..mime_type.len() - 6].trim_end_matches(' ');
+            mime_type = &mime_type[..mime_type.len() - 1];
+
+            let invalid_base64 = || DataUrlError::InvalidBase64 {
+                offset: body_offset,
+            };
+            // Forgiving-base64 decode removes all ASCII whitespace
+            let encoded = std::str::from_utf8(&data).map_err(|_| invalid_base64())?;
+            let encoded = if encoded.contains(|c: char| c.is_ascii_whitespace()) {
+                Cow::Owned(encoded.replace(|c: char| c.is_ascii_whitespace(), ""))
+            } else {
+                Cow::Borrowed(encoded)
+            };
+            Cow::Owned(base64_decode(&encoded, Alphabet::Standard).map_err(|_| invalid_base64())?)
+        } else {
+            data
+        };
+
+        let (essence, parameters) = mime_type.split_once(';').unwrap_or((mime_type, ""));
+        // A mimeType starting with U+003B (;) gets "text/plain" prepended, while an empty one is
+        // left to fail parsing
+        let essence = if essence.is_empty() && mime_type.starts_with(';') {
+            "text/plain"
+        } else {
+            essence
+        };
+        let (mime_type, parameters) = parse_mime_type(essence, parameters).unwrap_or_else(|| {
+            (
+                Cow::Borrowed("text/plain"),
+                vec![("charset", Cow::Borrowed("US-ASCII"))],
+            )
+        });
+
+        Ok(Self {
+            mime_type,
+            parameters,
+            base64,
+            data,
+        })
+    }
+
+    /// The type and subtype of the payload, in lowercase, such as `image/png`.
+    #[must_use]
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    /// The media type parameters, in the order they appeared, with quoted values unquoted.
+    #[must_use]
+    pub fn parameters(&self) -> &[(&'a str, Cow<'a, str>)] {
+        &self.parameters
+    }
+
+    /// The value of the first parameter with this name, compared ASCII case-insensitively.
+    #[must_use]
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter, _)| parameter.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+
+    /// Whether the payload was base64 encoded.
+    #[must_use]
+    pub fn is_base64(&self) -> bool {
+        self.base64
+    }
+
+    /// The decoded payload.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The decoded payload, without copying it if it was borrowed from the input.
+    #[must_use]
+    pub fn into_data(self) -> Cow<'a, [u8]> {
+        self.data
+    }
+}
+
+impl<'a> TryFrom<&'a str> for DataUrl<'a> {
+    type Error = DataUrlError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+// type "/" subtype *( OWS ";" OWS parameter ), where malformed parameters are skipped
+// https://mimesniff.spec.whatwg.org/#parse-a-mime-type
+type MimeType<'a> = (Cow<'a, str>, Vec<(&'a str, Cow<'a, str>)>);
+
+fn parse_mime_type<'a>(essence: &'a str, parameters: &'a str) -> Option<MimeType<'a>> {
+    let (type_, subtype) = essence.split_once('/')?;
+    let subtype = subtype.trim_end_matches(is_http_whitespace);
+    if !is_token(type_) || !is_token(subtype) {
+        return None;
+    }
+
+    let mime_type = if essence.bytes().any(|c| c.is_ascii_uppercase()) {
+        Cow::Owned(format!("{type_}/{subtype}").to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(&essence[..type_.len() + 1 + subtype.len()])
+    };
+
+    let mut result = Vec::new();
+    let mut i = parameters;
+    while !i.is_empty() {
+        i = i.trim_start_matches(is_http_whitespace);
+
+        let name_end = i.find([';', '=']).unwrap_or(i.len());
+        let name = &i[..name_end];
+        i = &i[name_end..];
+
+        let value = match i.strip_prefix('=') {
+            Some(rest) if rest.starts_with('"') => {
+                let (value, rest) = parse_quoted_string(rest);
+                i = rest;
+                Some(value)
+            }
+            Some(rest) => {
+                let value_end = rest.find(';').unwrap_or(rest.len());
+                i = &rest[value_end..];
+                Some(Cow::Borrowed(
+                    rest[..value_end].trim_end_matches(is_http_whitespace),
+                ))
+            }
+            None => None,
+        };
+
+        // Skip anything after a quoted value up to the next parameter
+        i = i.find(';').map_or("", |n| &i[n + 1..]);
+
+        if let Some(value) = value {
+            if is_token(name) && !value.is_empty() {
+                result.push((name, value));
+            }
+        }
+    }
+
+    Some((mime_type, result))
+}
+
+// quoted-string = DQUOTE *( qdtext / quoted-pair ) DQUOTE, where a missing closing quote ends the
+// string at the end of the input
+fn parse_quoted_string(i: &'_ str) -> (Cow<'_, str>, &'_ str) {
+    let i = &i[1..];
+
+    let mut value: Option<String> = None;
+    let mut chars = i.char_indices();
+    while let Some((n, c)) = chars.next() {
+        match c {
+            '"' => {
+                let value = value.map_or(Cow::Borrowed(&i[..n]), Cow::Owned);
+                return (value, &i[n + 1..]);
+            }
+            '\\' => {
+                let value = value.get_or_insert_with(|| i[..n].to_owned());
+                if let Some((_, escaped)) = chars.next() {
+                    value.push(escaped);
+                }
+            }
+            c => {
+                if let Some(value) = &mut value {
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    (value.map_or(Cow::Borrowed(i), Cow::Owned), "")
+}
+
+// HTTP whitespace is U+000A LF, U+000D CR, or an HTTP tab or space.
+fn is_http_whitespace(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\t' | ' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_data_url() {
+        type Parameters<'a> = Vec<(&'a str, &'a str)>;
+
+        let test_data: Vec<(&'_ str, Parameters, bool, &'_ [u8], &'_ str)> = vec![
+            (
+                "text/plain",
+                vec![],
+                false,
+                b"hello",
+                "data:text/plain,hello",
+            ),
+            (
+                "text/plain",
+                vec![("charset", "US-ASCII")],
+                false,
+                b"A brief note",
+                "data:,A%20brief%20note",
+            ),
+            (
+                "text/plain",
+                vec![("charset", "iso-8859-7")],
+                false,
+                b"\xbe%fg\xa1",
+                "data:text/plain;charset=iso-8859-7,%be%fg%a1",
+            ),
+            (
+                "image/gif",
+                vec![],
+                true,
+                b"GIF87a",
+                "DATA:Image/GIF;BASE64,R0lG ODdh",
+            ),
+            (
+                "text/plain",
+                vec![],
+                true,
+                b"hi",
+                "data:text/plain ; base64,aGk=#frag",
+            ),
+            (
+                "text/plain",
+                vec![("charset", "US-ASCII")],
+                true,
+                b"hi",
+                "data:;base64,aGk",
+            ),
+            (
+                "text/html",
+                vec![("charset", "utf-8"), ("a", "b;\"c")],
+                false,
+                b"<p>",
+                "data:text/html; charset=utf-8 ;x; a=\"b;\\\"c\"junk;=y,<p>",
+            ),
+            (
+                "text/plain",
+                vec![("charset", "US-ASCII")],
+                false,
+                b"x",
+                "data:text,x",
+            ),
+            (
+                "text/plain",
+                vec![("charset", "US-ASCII")],
+                false,
+                b"x",
+                "data:base64,x",
+            ),
+            ("a/b", vec![], false, b"", "data:a/b,"),
+        ];
+
+        for (mime_type, parameters, base64, data, input) in test_data {
+            let url = DataUrl::parse(input).unwrap();
+            assert_eq!(mime_type, url.mime_type(), "{input}");
+            assert_eq!(
+                parameters,
+                url.parameters()
+                    .iter()
+                    .map(|(name, value)| (*name, &**value))
+                    .collect::<Vec<_>>(),
+                "{input}"
+            );
+            assert_eq!(base64, url.is_base64(), "{input}");
+            assert_eq!(data, url.data(), "{input}");
+        }
+
+        let url = DataUrl::parse("data:text/plain;Charset=UTF-8,x").unwrap();
+        assert_eq!(Some("UTF-8"), url.parameter("charset"));
+        assert_eq!(None, url.parameter("boundary"));
+
+        assert_no_alloc(|| {
+            let url = DataUrl::parse("data:text/plain,hello").unwrap();
+            assert!(matches!(url.into_data(), Cow::Borrowed(b"hello")));
+        });
+    }
+
+    #[test]
+    fn test_data_url_invalid() {
+        let test_data = [
+            ("http://example.com", ErrorCode::InvalidScheme, 0),
+            ("data", ErrorCode::InvalidScheme, 0),
+            ("data:text/plain", ErrorCode::InvalidSyntax, 15),
+            ("data:text/plain#a,b", ErrorCode::InvalidSyntax, 15),
+            ("data:;base64,a", ErrorCode::InvalidSyntax, 13),
+            ("data:;base64,aGk*", ErrorCode::InvalidSyntax, 13),
+            ("data:;base64,%C3%A9", ErrorCode::InvalidSyntax, 13),
+        ];
+
+        for (input, code, offset) in test_data {
+            let err = DataUrl::parse(input).unwrap_err();
+            assert_eq!(code, err.code(), "{input}");
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+}
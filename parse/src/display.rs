@@ -0,0 +1,235 @@
+use std::borrow::Cow;
+
+use unicode_script::{Script, UnicodeScript};
+
+use crate::idna::idna_ascii_to_unicode;
+
+/// An assessment of how misleading a host may be when shown to a user, see
+/// [`assess_display_risk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayRisk<'a> {
+    mixed_script: bool,
+    invisible_characters: bool,
+    confusable_with: Option<&'a str>,
+}
+
+impl<'a> DisplayRisk<'a> {
+    /// Whether a label mixes scripts in a way not used by any language, such as Latin and
+    /// Cyrillic in `pаypal` (with a Cyrillic `а`).
+    ///
+    /// Latin mixed with Han and Japanese kana, Han and Bopomofo, or Han and Hangul is not mixed.
+    #[must_use]
+    pub fn is_mixed_script(&self) -> bool {
+        self.mixed_script
+    }
+
+    /// Whether the host contains characters that are not rendered, such as zero width joiners
+    /// or bidirectional controls.
+    #[must_use]
+    pub fn has_invisible_characters(&self) -> bool {
+        self.invisible_characters
+    }
+
+    /// The known domain the host looks like but is not, if any.
+    #[must_use]
+    pub fn confusable_with(&self) -> Option<&'a str> {
+        self.confusable_with
+    }
+
+    /// Whether any risk was found.
+    #[must_use]
+    pub fn is_risky(&self) -> bool {
+        self.mixed_script || self.invisible_characters || self.confusable_with.is_some()
+    }
+}
+
+/// Assess the risk of showing a host to a user, for example as the text of a link.
+///
+/// The host may be in its Unicode or punycode (`xn--`) form, it is checked as it would be shown
+/// after IDNA processing. `known_domains` are domains the caller wants to protect, such as
+/// popular or internal sites, the host is reported as confusable with the first one it looks
+/// like.
+///
+/// Confusables are detected using a small built-in table of Cyrillic, Greek, and Latin letters
+/// and digits that look like other Latin letters, not the full Unicode confusables data.
+#[must_use]
+pub fn assess_display_risk<'a>(host: &str, known_domains: &[&'a str]) -> DisplayRisk<'a> {
+    let unicode =
+        idna_ascii_to_unicode(host, false, true, true, false, false).unwrap_or(Cow::Borrowed(host));
+
+    let invisible_characters = host.chars().chain(unicode.chars()).any(is_invisible);
+    let mixed_script = unicode.split('.').any(is_mixed_script);
+
+    let skeleton = skeleton(&unicode);
+    let confusable_with = known_domains.iter().copied().find(|known| {
+        !trim_root(known).eq_ignore_ascii_case(trim_root(&unicode))
+            && skeleton == self::skeleton(&known.to_lowercase())
+    });
+
+    DisplayRisk {
+        mixed_script,
+        invisible_characters,
+        confusable_with,
+    }
+}
+
+fn trim_root(domain: &'_ str) -> &'_ str {
+    domain.strip_suffix('.').unwrap_or(domain)
+}
+
+// Default ignorable code points and bidirectional controls
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{AD}'
+            | '\u{34F}'
+            | '\u{61C}'
+            | '\u{180E}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FEFF}'
+    )
+}
+
+// A label is mixed script if its characters, ignoring those in the Common and Inherited
+// scripts, are not all covered by one of the combinations used to write a single language.
+// https://www.unicode.org/reports/tr39/#Restriction_Level_Detection
+fn is_mixed_script(label: &'_ str) -> bool {
+    const COMBINATIONS: [&[Script]; 3] = [
+        &[
+            Script::Latin,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+        &[Script::Latin, Script::Han, Script::Bopomofo],
+        &[Script::Latin, Script::Han, Script::Hangul],
+    ];
+
+    let mut scripts = label
+        .chars()
+        .map(|c| c.script())
+        .filter(|script| !matches!(script, Script::Common | Script::Inherited));
+
+    let Some(first) = scripts.next() else {
+        return false;
+    };
+
+    let mut allowed = COMBINATIONS.map(|combination| combination.contains(&first));
+    for script in scripts {
+        if script == first {
+            continue;
+        }
+
+        for (allowed, combination) in allowed.iter_mut().zip(COMBINATIONS) {
+            *allowed &= combination.contains(&script);
+        }
+        if !allowed.contains(&true) {
+            return true;
+        }
+    }
+
+    false
+}
+
+// Characters commonly mistaken for Latin letters, and the letters they are mistaken for
+const CONFUSABLES: [(char, &str); 33] = [
+    // Cyrillic
+    ('а', "a"),
+    ('с', "c"),
+    ('ԁ', "d"),
+    ('е', "e"),
+    ('һ', "h"),
+    ('і', "l"),
+    ('ӏ', "l"),
+    ('ј', "j"),
+    ('о', "o"),
+    ('р', "p"),
+    ('ԛ', "q"),
+    ('ѕ', "s"),
+    ('ԝ', "w"),
+    ('х', "x"),
+    ('у', "y"),
+    ('ү', "y"),
+    // Greek
+    ('α', "a"),
+    ('ϲ', "c"),
+    ('ι', "l"),
+    ('ϳ', "j"),
+    ('κ', "k"),
+    ('ν', "v"),
+    ('ο', "o"),
+    ('ρ', "p"),
+    ('υ', "u"),
+    ('χ', "x"),
+    // Latin and digits
+    ('ı', "l"),
+    ('ɩ', "l"),
+    ('i', "l"),
+    ('1', "l"),
+    ('ɡ', "g"),
+    ('0', "o"),
+    ('m', "rn"),
+];
+
+// Replace each character with the Latin characters it is commonly mistaken for, two domains
+// with the same skeleton look alike
+fn skeleton(domain: &'_ str) -> String {
+    let mut out = String::with_capacity(domain.len());
+
+    for c in trim_root(domain).chars() {
+        match CONFUSABLES.iter().find(|(confusable, _)| *confusable == c) {
+            Some((_, latin)) => out.push_str(latin),
+            None => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess_display_risk() {
+        let known = ["paypal.com", "example.com", "apple.com"];
+
+        // (host, mixed script, invisible characters, confusable with)
+        let test_data = [
+            ("paypal.com", false, false, None),
+            ("PayPal.com.", false, false, None),
+            ("example.org", false, false, None),
+            ("münchen.de", false, false, None),
+            ("яндекс.рф", false, false, None),
+            ("日本語とカタカナ.jp", false, false, None),
+            ("pаypal.com", true, false, Some("paypal.com")),
+            ("xn--pypal-4ve.com", true, false, Some("paypal.com")),
+            ("аррӏе.com", false, false, Some("apple.com")),
+            ("examp1e.com", false, false, Some("example.com")),
+            ("exarnple.com", false, false, Some("example.com")),
+            ("exam\u{200D}ple.com", false, true, None),
+            ("exam\u{AD}ple.com", false, true, None),
+            ("\u{202E}moc.elpmaxe", false, true, None),
+            ("ηbc.com", true, false, None),
+        ];
+
+        for (host, mixed_script, invisible_characters, confusable_with) in test_data {
+            let risk = assess_display_risk(host, &known);
+            assert_eq!(mixed_script, risk.is_mixed_script(), "{host}");
+            assert_eq!(
+                invisible_characters,
+                risk.has_invisible_characters(),
+                "{host}"
+            );
+            assert_eq!(confusable_with, risk.confusable_with(), "{host}");
+            assert_eq!(
+                mixed_script || invisible_characters || confusable_with.is_some(),
+                risk.is_risky()
+            );
+        }
+    }
+}
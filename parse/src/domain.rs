@@ -0,0 +1,141 @@
+use std::borrow::Cow;
+
+#[cfg(feature = "idna")]
+use crate::idna::idna_unicode_to_ascii;
+
+#[derive(Debug)]
+pub(crate) enum DomainError {
+    Empty,
+    // Only the last label may be empty, marking a fully qualified domain
+    #[cfg(not(feature = "idna"))]
+    EmptyLabel,
+    #[cfg(feature = "idna")]
+    Idna,
+    // Processing the domain requires IDNA, which was disabled at compile time
+    #[cfg(not(feature = "idna"))]
+    IdnaDisabled,
+}
+
+impl DomainError {
+    // Whether the domain was only rejected because IDNA was disabled at compile time
+    pub(crate) fn is_idna_disabled(&self) -> bool {
+        match self {
+            #[cfg(not(feature = "idna"))]
+            Self::IdnaDisabled => true,
+            _ => false,
+        }
+    }
+}
+
+// The domain to ASCII algorithm, given a string domain and a boolean beStrict, runs these steps:
+//     Let result be the result of running Unicode ToASCII with domain_name set to domain,
+//     UseSTD3ASCIIRules set to beStrict, CheckHyphens set to false, CheckBidi set to true,
+//     CheckJoiners set to true, Transitional_Processing set to false, and VerifyDnsLength set to
+//     beStrict.
+//     If result is a failure value, domain-to-ASCII validation error, return failure.
+//     If result is the empty string, domain-to-ASCII validation error, return failure.
+//     Return result.
+// https://url.spec.whatwg.org/#concept-domain-to-ascii
+#[cfg(feature = "idna")]
+pub(crate) fn domain_to_ascii(
+    domain: &'_ str,
+    be_strict: bool,
+) -> Result<Cow<'_, str>, DomainError> {
+    let result = idna_unicode_to_ascii(domain, false, true, true, be_strict, false, be_strict)
+        .map_err(|_| DomainError::Idna)?;
+
+    if result.is_empty() {
+        return Err(DomainError::Empty);
+    }
+
+    Ok(result)
+}
+
+// Without IDNA only domains that are already ASCII and contain no punycode labels can be
+// processed, the UTS46 mapping of the remaining characters is ASCII lowercasing
+#[cfg(not(feature = "idna"))]
+pub(crate) fn domain_to_ascii(
+    domain: &'_ str,
+    _be_strict: bool,
+) -> Result<Cow<'_, str>, DomainError> {
+    if domain.is_empty() {
+        return Err(DomainError::Empty);
+    }
+
+    if !domain.is_ascii()
+        || domain.split('.').any(|label| {
+            label
+                .get(..4)
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("xn--"))
+        })
+    {
+        return Err(DomainError::IdnaDisabled);
+    }
+
+    // Matches the label handling of the IDNA processing steps
+    let labels = domain.strip_suffix('.').unwrap_or(domain);
+    if labels.split('.').any(str::is_empty) {
+        return Err(DomainError::EmptyLabel);
+    }
+
+    if domain.bytes().any(|c| c.is_ascii_uppercase()) {
+        return Ok(Cow::Owned(domain.to_ascii_lowercase()));
+    }
+
+    Ok(Cow::Borrowed(domain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_domain_to_ascii() {
+        assert_eq!(
+            "example.com",
+            domain_to_ascii("EXAMPLE.com", false).unwrap()
+        );
+        assert!(domain_to_ascii("", false).is_err());
+        assert!(domain_to_ascii(".", false).is_err());
+        assert!(domain_to_ascii("example..com", false).is_err());
+        assert!(domain_to_ascii("example.com..", false).is_err());
+        assert_eq!(
+            "example.com.",
+            domain_to_ascii("example.com.", false).unwrap()
+        );
+
+        assert_no_alloc(|| {
+            assert!(matches!(
+                domain_to_ascii("example.com", false),
+                Ok(Cow::Borrowed("example.com"))
+            ));
+        });
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn test_domain_to_ascii_idna() {
+        assert_eq!(
+            "xn--bcher-kva.example",
+            domain_to_ascii("bücher.example", false).unwrap()
+        );
+        assert_eq!(
+            "xn--bcher-kva.example",
+            domain_to_ascii("xn--bcher-kva.example", false).unwrap()
+        );
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn test_domain_to_ascii_idna_disabled() {
+        assert!(matches!(
+            domain_to_ascii("bücher.example", false),
+            Err(DomainError::IdnaDisabled)
+        ));
+        assert!(matches!(
+            domain_to_ascii("XN--bcher-kva.example", false),
+            Err(DomainError::IdnaDisabled)
+        ));
+    }
+}
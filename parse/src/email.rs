@@ -0,0 +1,323 @@
+//! Validation of email addresses.
+
+use std::fmt;
+
+use nom::combinator::all_consuming;
+
+use crate::{
+    domain::{domain_to_ascii, DomainError},
+    error::ErrorCode,
+    ipv4::parse_ipv4_dotted_decimal,
+    ipv6,
+    parse::floor_char_boundary,
+    reject,
+};
+
+/// An error encountered while validating an email address.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum EmailError {
+    /// The address has no `@`, or is longer than a mailbox can be.
+    InvalidSyntax { offset: usize },
+    /// The local part is too long, or is not a valid dot-string or quoted string.
+    InvalidLocalPart { offset: usize },
+    /// The domain is not a valid domain name.
+    InvalidDomain { offset: usize },
+    /// The domain needs IDNA processing, and the `idna` feature is disabled.
+    IdnaDisabled { offset: usize },
+}
+
+impl EmailError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidSyntax { .. } => ErrorCode::InvalidSyntax,
+            Self::InvalidLocalPart { .. } => ErrorCode::InvalidUserinfo,
+            Self::InvalidDomain { .. } | Self::IdnaDisabled { .. } => ErrorCode::InvalidHost,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidSyntax { offset, .. }
+            | Self::InvalidLocalPart { offset, .. }
+            | Self::InvalidDomain { offset, .. }
+            | Self::IdnaDisabled { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for EmailError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSyntax { offset, .. } => {
+                write!(f, "invalid email address at offset {offset}")
+            }
+            Self::InvalidLocalPart { offset, .. } => {
+                write!(f, "invalid local-part at offset {offset}")
+            }
+            Self::InvalidDomain { offset, .. } => write!(f, "invalid domain at offset {offset}"),
+            Self::IdnaDisabled { offset, .. } => {
+                write!(f, "domain at offset {offset} needs IDNA, which is disabled")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EmailError {}
+
+// The maximum total length of a user name or other local-part is 64 octets.
+const MAX_LOCAL_PART_LEN: usize = 64;
+
+// The maximum total length of a reverse-path or forward-path is 256 octets (including the
+// punctuation and element separators), which leaves 254 for the mailbox between "<" and ">".
+const MAX_MAILBOX_LEN: usize = 254;
+
+/// Validate an internationalized email address.
+///
+/// The local-part may contain UTF-8 as allowed by SMTPUTF8, and the domain is checked with the
+/// same IDNA processing as URL hosts, so a domain that can not be converted to ASCII is
+/// rejected. The domain may also be an IPv4 or IPv6 address literal in brackets.
+///
+/// See [RFC6531](https://www.rfc-editor.org/rfc/rfc6531#section-3.3)
+///
+/// # Errors
+///
+/// Returns an error if the address does not contain `@`, if the local-part is not a dot-string
+/// or quoted-string, if the domain is not a valid domain or address literal, or if the address
+/// is too long. A domain that needs IDNA processing is rejected when the `idna` feature is
+/// disabled.
+pub fn validate(address: &str) -> Result<(), EmailError> {
+    validate_unreported(address)
+        .inspect_err(|err| reject::report(address, err.code(), err.offset()))
+}
+
+// Also used by the mailto parser, which reports its own rejections
+pub(crate) fn validate_unreported(address: &str) -> Result<(), EmailError> {
+    // Mailbox = Local-part "@" ( Domain / address-literal )
+    //
+    // A quoted local-part may contain "@", the domain can not
+    let Some(at) = address.rfind('@') else {
+        return Err(EmailError::InvalidSyntax {
+            offset: address.len(),
+        });
+    };
+    let (local_part, domain) = (&address[..at], &address[at + 1..]);
+
+    if address.len() > MAX_MAILBOX_LEN {
+        return Err(EmailError::InvalidSyntax {
+            offset: floor_char_boundary(address, MAX_MAILBOX_LEN),
+        });
+    }
+
+    if let Some(offset) = invalid_local_part_offset(local_part) {
+        return Err(EmailError::InvalidLocalPart { offset });
+    }
+
+    match is_valid_domain(domain) {
+        Ok(true) => {}
+        Err(err) if err.is_idna_disabled() => {
+            return Err(EmailError::IdnaDisabled { offset: at + 1 });
+        }
+        Ok(false) | Err(_) => {
+            return Err(EmailError::InvalidDomain { offset: at + 1 });
+        }
+    }
+
+    Ok(())
+}
+
+// Local-part     = Dot-string / Quoted-string
+// Dot-string     = Atom *("."  Atom)
+// Atom           = 1*atext
+// Quoted-string  = DQUOTE *QcontentSMTP DQUOTE
+// QcontentSMTP   = qtextSMTP / quoted-pairSMTP
+// quoted-pairSMTP  = %d92 %d32-126
+// qtextSMTP      = %d32-33 / %d35-91 / %d93-126 / UTF8-non-ascii
+// atext          =/ UTF8-non-ascii
+fn invalid_local_part_offset(local_part: &'_ str) -> Option<usize> {
+    if local_part.len() > MAX_LOCAL_PART_LEN {
+        return Some(floor_char_boundary(local_part, MAX_LOCAL_PART_LEN));
+    }
+
+    match local_part.strip_prefix('"') {
+        Some(quoted) => invalid_quoted_string_offset(quoted).map(|offset| offset + 1),
+        None => invalid_dot_string_offset(local_part),
+    }
+}
+
+fn invalid_dot_string_offset(dot_string: &'_ str) -> Option<usize> {
+    let mut atom_start = 0;
+
+    for (n, c) in dot_string.char_indices() {
+        if c == '.' {
+            if n == atom_start {
+                return Some(n);
+            }
+            atom_start = n + 1;
+        } else if !is_atext(c) {
+            return Some(n);
+        }
+    }
+
+    // Also covers an empty local-part
+    (atom_start == dot_string.len()).then_some(dot_string.len())
+}
+
+// The input follows the opening DQUOTE
+fn invalid_quoted_string_offset(quoted: &'_ str) -> Option<usize> {
+    let mut chars = quoted.char_indices();
+
+    while let Some((n, c)) = chars.next() {
+        match c {
+            '"' => return (n + 1 != quoted.len()).then_some(n + 1),
+            '\\' => match chars.next() {
+                Some((_, ' '..='~')) => {}
+                Some((n, _)) => return Some(n),
+                None => return Some(quoted.len()),
+            },
+            c if !is_qtext_smtp(c) => return Some(n),
+            _ => {}
+        }
+    }
+
+    // No closing DQUOTE
+    Some(quoted.len())
+}
+
+// atext = ALPHA / DIGIT / "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" / "-" / "/" / "=" / "?" /
+//         "^" / "_" / "`" / "{" / "|" / "}" / "~"
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+fn is_qtext_smtp(c: char) -> bool {
+    matches!(c, ' '..='!' | '#'..='[' | ']'..='~') || !c.is_ascii()
+}
+
+// Domain = sub-domain *("." sub-domain)
+// sub-domain = Let-dig [Ldh-str]
+// address-literal = "[" ( IPv4-address-literal / IPv6-address-literal /
+//                   General-address-literal ) "]"
+// IPv6-address-literal = "IPv6:" IPv6-addr
+//
+// Fails if the domain could not be converted to ASCII
+fn is_valid_domain(domain: &'_ str) -> Result<bool, DomainError> {
+    if let Some(literal) = domain
+        .strip_prefix('[')
+        .and_then(|domain| domain.strip_suffix(']'))
+    {
+        return Ok(match literal.strip_prefix("IPv6:") {
+            Some(addr) => all_consuming(ipv6::parse)(addr).is_ok(),
+            None => all_consuming(parse_ipv4_dotted_decimal)(literal).is_ok(),
+        });
+    }
+
+    let ascii_domain = domain_to_ascii(domain, true)?;
+
+    // Unlike a URL host, a mailbox domain can not be fully qualified with a trailing dot
+    Ok(ascii_domain.split('.').all(|label| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-')
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let test_data = [
+            "user@example.com",
+            "first.last+tag@example.com",
+            "!#$%&'*+-/=?^_`{|}~@example.com",
+            "\"john doe\"@example.com",
+            "\"a@b\\\"c\"@example.com",
+            "\"\"@example.com",
+            "user@[192.0.2.1]",
+            "user@[IPv6:2001:db8::1]",
+            "user@EXAMPLE.COM",
+            "用户@example.com",
+        ];
+
+        for input in test_data {
+            assert!(validate(input).is_ok(), "{input}");
+        }
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn test_validate_idna() {
+        assert!(validate("用户@例子.广告").is_ok());
+        assert!(validate("user@bücher.example").is_ok());
+        assert!(validate("θσερ@εχαμπλε.ψομ").is_ok());
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn test_validate_idna_disabled() {
+        let err = validate("user@bücher.example").unwrap_err();
+        assert!(matches!(err, EmailError::IdnaDisabled { offset: 5, .. }));
+        assert_eq!(ErrorCode::InvalidHost, err.code());
+
+        assert!(validate("用户@example.com").is_ok());
+        assert!(matches!(
+            validate("user@exa_mple.com"),
+            Err(EmailError::InvalidDomain { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_invalid() {
+        let local_part_64 = "a".repeat(64);
+        let local_part_65 = "a".repeat(65);
+        let long_domain = format!("{}.com", vec!["a".repeat(60); 5].join("."));
+        let valid_64 = format!("{local_part_64}@example.com");
+        let invalid_65 = format!("{local_part_65}@example.com");
+        let invalid_long = format!("user@{long_domain}");
+        // 2 byte characters, so the limits fall inside a character
+        let invalid_65_utf8 = format!("a{}@example.com", "\u{e9}".repeat(32));
+        let invalid_long_utf8 = format!("\u{e9}@{}", "\u{e9}".repeat(127));
+
+        assert!(validate(&valid_64).is_ok());
+
+        let test_data = [
+            ("user.example.com", ErrorCode::InvalidSyntax, 16),
+            (&invalid_long, ErrorCode::InvalidSyntax, 254),
+            (&invalid_long_utf8, ErrorCode::InvalidSyntax, 253),
+            ("@example.com", ErrorCode::InvalidUserinfo, 0),
+            (".user@example.com", ErrorCode::InvalidUserinfo, 0),
+            ("user.@example.com", ErrorCode::InvalidUserinfo, 5),
+            ("us..er@example.com", ErrorCode::InvalidUserinfo, 3),
+            ("us er@example.com", ErrorCode::InvalidUserinfo, 2),
+            ("us(er)@example.com", ErrorCode::InvalidUserinfo, 2),
+            ("\"unterminated@example.com", ErrorCode::InvalidUserinfo, 13),
+            ("\"a\"b@example.com", ErrorCode::InvalidUserinfo, 3),
+            ("\"a\tb\"@example.com", ErrorCode::InvalidUserinfo, 2),
+            (&invalid_65, ErrorCode::InvalidUserinfo, 64),
+            (&invalid_65_utf8, ErrorCode::InvalidUserinfo, 63),
+            ("user@", ErrorCode::InvalidHost, 5),
+            ("user@example..com", ErrorCode::InvalidHost, 5),
+            ("user@example.com.", ErrorCode::InvalidHost, 5),
+            ("user@-example.com", ErrorCode::InvalidHost, 5),
+            ("user@exa_mple.com", ErrorCode::InvalidHost, 5),
+            ("user@[300.0.0.1]", ErrorCode::InvalidHost, 5),
+            ("user@[2001:db8::1]", ErrorCode::InvalidHost, 5),
+            ("user@[IPv6:1.2.3.4]", ErrorCode::InvalidHost, 5),
+        ];
+
+        for (input, code, offset) in test_data {
+            let err = validate(input).expect_err(input);
+            assert_eq!(code, err.code(), "{input}");
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,364 @@
+//! Base64 encoding and decoding.
+//!
+//! Characters are converted with branchless arithmetic instead of lookup tables, so the time
+//! taken does not depend on the value of the data being encoded or decoded, only on its length
+//! and on where the first invalid byte is.
+
+use std::fmt;
+
+use crate::error::ErrorCode;
+
+/// The alphabet used to represent the 62nd and 63rd values.
+///
+/// See [RFC4648](https://www.rfc-editor.org/rfc/rfc4648#section-4)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// `+` and `/`, the base64 alphabet.
+    Standard,
+    /// `-` and `_`, the base64url alphabet, which is safe to use in URLs and file names.
+    UrlSafe,
+}
+
+/// An error encountered while decoding base64.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A byte is not in the alphabet, or is padding before the end of the input.
+    InvalidByte { offset: usize, byte: u8 },
+    /// The input, without padding, is one byte longer than a multiple of four.
+    InvalidLength,
+    /// The input ends in padding, but its length is not a multiple of four.
+    InvalidPadding { offset: usize },
+}
+
+impl DecodeError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidSyntax
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidByte { offset, .. } | Self::InvalidPadding { offset } => *offset,
+            Self::InvalidLength => 0,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidByte { offset, byte } => {
+                write!(f, "invalid base64 byte 0x{byte:02X} at offset {offset}")
+            }
+            Self::InvalidLength => f.write_str("invalid base64 length"),
+            Self::InvalidPadding { offset } => {
+                write!(f, "invalid base64 padding at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Encode bytes as base64, appending the result to `out`.
+pub fn base64_encode_to(input: &[u8], alphabet: Alphabet, padding: bool, out: &mut String) {
+    out.reserve(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let sextets = [
+            b[0] >> 2,
+            (b[0] & 0x03) << 4 | b[1] >> 4,
+            (b[1] & 0x0F) << 2 | b[2] >> 6,
+            b[2] & 0x3F,
+        ];
+
+        // n bytes of input become n + 1 characters of output
+        for &sextet in &sextets[..=chunk.len()] {
+            out.push(char::from(encode_sextet(sextet, alphabet)));
+        }
+        if padding {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+}
+
+/// Encode bytes as base64.
+#[must_use]
+pub fn base64_encode(input: &[u8], alphabet: Alphabet, padding: bool) -> String {
+    let mut out = String::new();
+    base64_encode_to(input, alphabet, padding, &mut out);
+    out
+}
+
+/// Decode base64, appending the result to `out`.
+///
+/// Padding is optional, but if present it must be correct. Any bits left over after the last
+/// full byte are ignored. Whitespace is not skipped.
+///
+/// # Errors
+///
+/// Returns an error if the input contains a byte outside the alphabet, has a length that no
+/// encoding produces, or is padded incorrectly. Nothing is appended to `out` on error.
+pub fn base64_decode_to(
+    input: &str,
+    alphabet: Alphabet,
+    out: &mut Vec<u8>,
+) -> Result<(), DecodeError> {
+    let input = input.as_bytes();
+
+    let data = match input {
+        [data @ .., b'=', b'='] | [data @ .., b'='] => {
+            if !input.len().is_multiple_of(4) {
+                return Err(DecodeError::InvalidPadding { offset: data.len() });
+            }
+            data
+        }
+        data => data,
+    };
+
+    if data.len() % 4 == 1 {
+        return Err(DecodeError::InvalidLength);
+    }
+
+    let start = out.len();
+    out.reserve(data.len() / 4 * 3 + 2);
+
+    for (n, chunk) in data.chunks(4).enumerate() {
+        let mut sextets = [0; 4];
+        for (i, (&c, sextet)) in chunk.iter().zip(&mut sextets).enumerate() {
+            let Some(value) = decode_sextet(c, alphabet) else {
+                out.truncate(start);
+                return Err(DecodeError::InvalidByte {
+                    offset: n * 4 + i,
+                    byte: c,
+                });
+            };
+            *sextet = value;
+        }
+
+        let bytes = [
+            sextets[0] << 2 | sextets[1] >> 4,
+            sextets[1] << 4 | sextets[2] >> 2,
+            sextets[2] << 6 | sextets[3],
+        ];
+        // n characters of input become n - 1 bytes of output
+        out.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+
+    Ok(())
+}
+
+/// Decode base64.
+///
+/// # Errors
+///
+/// See [`base64_decode_to`].
+pub fn base64_decode(input: &str, alphabet: Alphabet) -> Result<Vec<u8>, DecodeError> {
+    let mut out = Vec::new();
+    base64_decode_to(input, alphabet, &mut out)?;
+    Ok(out)
+}
+
+/// Whether the input is a `token68`, the form of the credentials in `Authorization` headers
+/// using schemes such as `Bearer` and `Basic`.
+///
+/// See [RFC9110](https://www.rfc-editor.org/rfc/rfc9110#section-11.2)
+#[must_use]
+pub fn is_token68(input: &str) -> bool {
+    // token68 = 1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="
+    let token = input.trim_end_matches('=');
+
+    !token.is_empty()
+        && token
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || b"-._~+/".contains(&c))
+}
+
+// -1 if lo < c < hi, otherwise 0
+fn in_range(c: i32, lo: i32, hi: i32) -> i32 {
+    ((lo - c) & (c - hi)) >> 8
+}
+
+fn encode_sextet(sextet: u8, alphabet: Alphabet) -> u8 {
+    let s = i32::from(sextet);
+
+    // Start from 'A' and shift into each following range of the alphabet that s is past
+    let mut c = s + i32::from(b'A');
+    c += ((25 - s) >> 8) & 0x06;
+    c -= ((51 - s) >> 8) & 0x4B;
+    match alphabet {
+        Alphabet::Standard => {
+            c -= ((61 - s) >> 8) & 0x0F;
+            c += ((62 - s) >> 8) & 0x03;
+        }
+        Alphabet::UrlSafe => {
+            c -= ((61 - s) >> 8) & 0x0D;
+            c += ((62 - s) >> 8) & 0x31;
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let c = c as u8;
+    c
+}
+
+fn decode_sextet(c: u8, alphabet: Alphabet) -> Option<u8> {
+    let c = i32::from(c);
+
+    // Each range adds its value plus one, so the result is -1 for bytes outside the alphabet
+    let mut value = -1;
+    value += in_range(c, 0x40, 0x5B) & (c - 64);
+    value += in_range(c, 0x60, 0x7B) & (c - 70);
+    value += in_range(c, 0x2F, 0x3A) & (c + 5);
+    match alphabet {
+        Alphabet::Standard => {
+            value += in_range(c, 0x2A, 0x2C) & 0x3F;
+            value += in_range(c, 0x2E, 0x30) & 0x40;
+        }
+        Alphabet::UrlSafe => {
+            value += in_range(c, 0x2C, 0x2E) & 0x3F;
+            value += in_range(c, 0x5E, 0x60) & 0x40;
+        }
+    }
+
+    u8::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_base64() {
+        // https://www.rfc-editor.org/rfc/rfc4648#section-10
+        let test_data: Vec<(&'_ [u8], &'_ str)> = vec![
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+
+        for (bytes, encoded) in test_data {
+            assert_eq!(encoded, base64_encode(bytes, Alphabet::Standard, true));
+            assert_eq!(
+                encoded.trim_end_matches('='),
+                base64_encode(bytes, Alphabet::Standard, false)
+            );
+            assert_eq!(bytes, base64_decode(encoded, Alphabet::Standard).unwrap());
+            assert_eq!(
+                bytes,
+                base64_decode(encoded.trim_end_matches('='), Alphabet::Standard).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_base64_alphabets() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        for alphabet in [Alphabet::Standard, Alphabet::UrlSafe] {
+            let encoded = base64_encode(&bytes, alphabet, true);
+            assert_eq!(bytes, base64_decode(&encoded, alphabet).unwrap());
+        }
+
+        assert_eq!("+/8=", base64_encode(b"\xFB\xFF", Alphabet::Standard, true));
+        assert_eq!("-_8", base64_encode(b"\xFB\xFF", Alphabet::UrlSafe, false));
+        assert!(base64_decode("-_8", Alphabet::Standard).is_err());
+        assert!(base64_decode("+/8", Alphabet::UrlSafe).is_err());
+
+        // Every byte outside the alphabet is rejected, apart from the padding
+        for c in 0..=255u8 {
+            let valid = c.is_ascii_alphanumeric() || b"+/=".contains(&c);
+            let input = [b'A', b'A', b'A', c];
+            let input = String::from_utf8_lossy(&input);
+            assert_eq!(
+                valid,
+                base64_decode(&input, Alphabet::Standard).is_ok(),
+                "{c}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_base64_decode_invalid() {
+        let test_data = [
+            ("A", DecodeError::InvalidLength),
+            ("AAAAA", DecodeError::InvalidLength),
+            ("Zg=", DecodeError::InvalidPadding { offset: 2 }),
+            ("Zm9v=", DecodeError::InvalidPadding { offset: 4 }),
+            ("Zg===", DecodeError::InvalidPadding { offset: 3 }),
+            (
+                "Z=g=",
+                DecodeError::InvalidByte {
+                    offset: 1,
+                    byte: b'=',
+                },
+            ),
+            (
+                "Zm9v Zm9",
+                DecodeError::InvalidByte {
+                    offset: 4,
+                    byte: b' ',
+                },
+            ),
+        ];
+
+        for (input, expected) in test_data {
+            let mut out = vec![1, 2, 3];
+            assert_eq!(
+                Err(expected),
+                base64_decode_to(input, Alphabet::Standard, &mut out),
+                "{input}"
+            );
+            assert_eq!(vec![1, 2, 3], out);
+        }
+    }
+
+    #[test]
+    fn test_base64_no_alloc() {
+        let mut out = Vec::with_capacity(16);
+        let mut encoded = String::with_capacity(16);
+
+        assert_no_alloc(|| {
+            base64_decode_to("Zm9vYmFy", Alphabet::Standard, &mut out).unwrap();
+            base64_encode_to(&out, Alphabet::UrlSafe, false, &mut encoded);
+        });
+
+        assert_eq!(b"foobar", &*out);
+        assert_eq!("Zm9vYmFy", encoded);
+    }
+
+    #[test]
+    fn test_is_token68() {
+        let test_data = [
+            ("Zm9vYmFy", true),
+            ("Zm9vYg==", true),
+            ("abc-._~+/", true),
+            ("mF_9.B5f-4.1JqM", true),
+            ("", false),
+            ("==", false),
+            ("a=b", false),
+            ("a b", false),
+            ("a,b", false),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(expected, is_token68(input), "{input}");
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+/// A stable classification of parse errors.
+///
+/// Every public error type maps its variants onto one of these codes through its `code()`
+/// method, so callers can branch on the class of an error without matching on the variants of
+/// each error type.
+///
+/// Each error type also has an `offset()` method, the byte offset in the input at which the error
+/// was detected, always on a character boundary and at most the length of the input. Errors that
+/// reject the input as a whole, such as a missing or unexpected scheme, report offset 0.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// The input does not have the overall shape expected by the parser.
+    InvalidSyntax,
+    /// The scheme is missing or is not one accepted by the parser.
+    InvalidScheme,
+    /// The userinfo contains invalid characters.
+    InvalidUserinfo,
+    /// The host is not a valid domain or IP address.
+    InvalidHost,
+    /// The port is not a number in the range 0 to 65535.
+    InvalidPort,
+    /// The path is empty or contains invalid characters.
+    InvalidPath,
+    /// The query contains invalid characters.
+    InvalidQuery,
+    /// The fragment contains invalid characters.
+    InvalidFragment,
+    /// A parameter is malformed or missing.
+    InvalidParameter,
+    /// A header field is malformed.
+    InvalidHeader,
+    /// A status code is outside the range 100 to 599.
+    InvalidStatus,
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidSyntax => "invalid syntax",
+            Self::InvalidScheme => "invalid scheme",
+            Self::InvalidUserinfo => "invalid userinfo",
+            Self::InvalidHost => "invalid host",
+            Self::InvalidPort => "invalid port",
+            Self::InvalidPath => "invalid path",
+            Self::InvalidQuery => "invalid query",
+            Self::InvalidFragment => "invalid fragment",
+            Self::InvalidParameter => "invalid parameter",
+            Self::InvalidHeader => "invalid header",
+            Self::InvalidStatus => "invalid status",
+        })
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+
+/// An event dispatched from a `text/event-stream` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    kind: String,
+    data: String,
+    last_event_id: String,
+}
+
+impl Event {
+    /// The type of the event, `message` if the event did not set one.
+    #[must_use]
+    pub fn event_type(&self) -> &str {
+        if self.kind.is_empty() {
+            "message"
+        } else {
+            &self.kind
+        }
+    }
+
+    /// The data of the event, with the values of multiple `data` fields joined by newlines.
+    #[must_use]
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// The most recent `id` set by this or an earlier event.
+    #[must_use]
+    pub fn last_event_id(&self) -> &str {
+        &self.last_event_id
+    }
+}
+
+/// An incremental parser for `text/event-stream` bodies.
+///
+/// Chunks of the body are passed to [`feed`](Self::feed) as they arrive, split at any point, and
+/// complete events are taken from [`next_event`](Self::next_event).
+///
+/// See [HTML](https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation)
+#[derive(Debug, Default)]
+pub struct EventStreamParser {
+    line: Vec<u8>,
+    started: bool,
+    after_cr: bool,
+    event_type: String,
+    data: String,
+    last_event_id: String,
+    retry: Option<u64>,
+    events: VecDeque<Event>,
+}
+
+impl EventStreamParser {
+    /// A parser at the start of a stream, with no last event ID or reconnection time.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the next chunk of the body.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for &c in chunk {
+            // Lines must be separated by either a U+000D CARRIAGE RETURN U+000A LINE FEED (CRLF)
+            // character pair, a single U+000A LINE FEED (LF) character, or a single U+000D
+            // CARRIAGE RETURN (CR) character.
+            let after_cr = std::mem::replace(&mut self.after_cr, c == b'\r');
+            match c {
+                b'\n' if after_cr => {}
+                b'\r' | b'\n' => self.end_line(),
+                c => self.line.push(c),
+            }
+        }
+    }
+
+    /// The next complete event, if any.
+    pub fn next_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    /// The reconnection time in milliseconds, if the stream set one with a `retry` field.
+    #[must_use]
+    pub fn retry(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// The most recent `id` seen by the parser, to be sent as `Last-Event-ID` when reconnecting.
+    #[must_use]
+    pub fn last_event_id(&self) -> &str {
+        &self.last_event_id
+    }
+
+    fn end_line(&mut self) {
+        let mut line = &self.line[..];
+
+        // One leading U+FEFF BYTE ORDER MARK character must be ignored if any are present.
+        if !std::mem::replace(&mut self.started, true) {
+            line = line.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(line);
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let line = &*line;
+
+        if line.is_empty() {
+            // If the line is empty (a blank line), dispatch the event.
+            self.dispatch();
+        } else if line.starts_with(':') {
+            // If the line starts with a U+003A COLON character (:), ignore the line.
+        } else {
+            // If the line contains a U+003A COLON character (:), collect the characters on the
+            // line before the first U+003A COLON character (:), and let field be that string.
+            // Collect the characters on the line after the first U+003A COLON character (:), and
+            // let value be that string. If value starts with a U+0020 SPACE character, remove it
+            // from value.
+            //
+            // Otherwise, the string is not empty but does not contain a U+003A COLON character
+            // (:). Process the field using the whole line as the field name, and the empty string
+            // as the field value.
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "event" => value.clone_into(&mut self.event_type),
+                "data" => {
+                    self.data.push_str(value);
+                    self.data.push('\n');
+                }
+                "id" if !value.contains('\0') => value.clone_into(&mut self.last_event_id),
+                "retry" if !value.is_empty() && value.bytes().all(|c| c.is_ascii_digit()) => {
+                    // A time too long to represent is ignored like any other invalid value
+                    if let Ok(retry) = value.parse() {
+                        self.retry = Some(retry);
+                    }
+                }
+                // Otherwise, the field is ignored.
+                _ => {}
+            }
+        }
+
+        self.line.clear();
+    }
+
+    fn dispatch(&mut self) {
+        // If the data buffer is an empty string, set the data buffer and the event type buffer
+        // to the empty string and return.
+        if self.data.is_empty() {
+            self.event_type.clear();
+            return;
+        }
+
+        // If the data buffer's last character is a U+000A LINE FEED (LF) character, then remove
+        // the last character from the data buffer.
+        self.data.pop();
+
+        self.events.push_back(Event {
+            kind: std::mem::take(&mut self.event_type),
+            data: std::mem::take(&mut self.data),
+            last_event_id: self.last_event_id.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_chunks(chunks: &[&[u8]]) -> (Vec<(String, String, String)>, EventStreamParser) {
+        let mut parser = EventStreamParser::new();
+        let mut events = Vec::new();
+
+        for chunk in chunks {
+            parser.feed(chunk);
+            while let Some(event) = parser.next_event() {
+                events.push((
+                    event.event_type().to_owned(),
+                    event.data().to_owned(),
+                    event.last_event_id().to_owned(),
+                ));
+            }
+        }
+
+        (events, parser)
+    }
+
+    // (event type, data, last event id)
+    type Events<'a> = Vec<(&'a str, &'a str, &'a str)>;
+
+    #[test]
+    fn test_event_stream() {
+        let test_data: Vec<(&'_ [u8], Events)> = vec![
+            (b"data: hello\n\n", vec![("message", "hello", "")]),
+            (b"data:hello\r\n\r\n", vec![("message", "hello", "")]),
+            (b"data: hello\r\r", vec![("message", "hello", "")]),
+            (
+                b"data: YHOO\ndata: +2\ndata: 10\n\n",
+                vec![("message", "YHOO\n+2\n10", "")],
+            ),
+            (
+                b": test stream\n\ndata: first event\nid: 1\n\ndata:second event\nid\n\ndata:  third event\n\n",
+                vec![
+                    ("message", "first event", "1"),
+                    ("message", "second event", ""),
+                    ("message", " third event", ""),
+                ],
+            ),
+            (
+                b"event: add\ndata: 73857293\n\nevent: remove\ndata: 2153\n\ndata: 113411\n\n",
+                vec![
+                    ("add", "73857293", ""),
+                    ("remove", "2153", ""),
+                    ("message", "113411", ""),
+                ],
+            ),
+            (b"data\n\ndata\ndata\n\ndata:", vec![("message", "", ""), ("message", "\n", "")]),
+            (b"event: ignored\n\ndata: x\n\n", vec![("message", "x", "")]),
+            (b"\xEF\xBB\xBFdata: bom\n\n", vec![("message", "bom", "")]),
+            (b"\xEF\xBB\xBF\xEF\xBB\xBFdata: bom\n\n", vec![]),
+            (b"id: a\0b\ndata: x\n\n", vec![("message", "x", "")]),
+            (b"data: \xFF\n\n", vec![("message", "\u{FFFD}", "")]),
+            (b"data: incomplete\n", vec![]),
+        ];
+
+        for (input, expected) in test_data {
+            let expected: Vec<_> = expected
+                .into_iter()
+                .map(|(t, d, i)| (t.to_owned(), d.to_owned(), i.to_owned()))
+                .collect();
+
+            // Every way of splitting the input in two gives the same events
+            for n in 0..=input.len() {
+                let (events, _) = parse_chunks(&[&input[..n], &input[n..]]);
+                assert_eq!(expected, events, "{input:?} split at {n}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_stream_retry() {
+        let test_data: Vec<(&'_ [u8], Option<u64>)> = vec![
+            (b"retry: 1000\n", Some(1000)),
+            (b"retry: 1000\nretry: 1s\n", Some(1000)),
+            (b"retry: 1000\nretry: 99999999999999999999\n", Some(1000)),
+            (b"retry: -1\n", None),
+            (b"retry:\n", None),
+            (b"retry\n", None),
+        ];
+
+        for (input, expected) in test_data {
+            let (_, parser) = parse_chunks(&[input]);
+            assert_eq!(expected, parser.retry());
+        }
+
+        let (_, parser) = parse_chunks(&[b"id: 7\n\n"]);
+        assert_eq!("7", parser.last_event_id());
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error::ErrorCode,
+    host::{parse_host, Host, HostErrorKind},
+    percent_encode::{percent_decode_str, percent_encode, AsciiSet, DecodePolicy},
+    reject,
+};
+
+// The path percent-encode set, plus "%" so that file names containing it survive a round trip
+const FILE_PATH: AsciiSet = AsciiSet::PATH.add(b'%');
+
+/// A parsed `file:` URL.
+///
+/// See [WHATWG URL](https://url.spec.whatwg.org/#file-state)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileUrl<'a> {
+    host: Option<Host<'a>>,
+    path: Cow<'a, str>,
+}
+
+/// An error encountered while parsing a `file:` URL or converting it to or from a file path.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum FileUrlError {
+    /// The input does not start with `file:`.
+    InvalidScheme,
+    /// The host is not a valid domain or IP address, or a URL with a host is converted to a
+    /// path on a platform without network shares.
+    InvalidHost { offset: usize },
+    /// The host needs IDNA processing, and the `idna` feature is disabled.
+    IdnaDisabled { offset: usize },
+    /// The path can not be converted to or from a file path, such as a relative path or one
+    /// containing an encoded separator or NUL.
+    InvalidPath { offset: usize },
+}
+
+impl FileUrlError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::InvalidHost { .. } | Self::IdnaDisabled { .. } => ErrorCode::InvalidHost,
+            Self::InvalidPath { .. } => ErrorCode::InvalidPath,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::InvalidHost { offset, .. }
+            | Self::IdnaDisabled { offset, .. }
+            | Self::InvalidPath { offset, .. } => *offset,
+        }
+    }
+
+    fn host(kind: HostErrorKind, offset: usize) -> Self {
+        match kind {
+            HostErrorKind::IdnaDisabled => Self::IdnaDisabled { offset },
+            _ => Self::InvalidHost { offset },
+        }
+    }
+}
+
+impl fmt::Display for FileUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a file: URL"),
+            Self::InvalidHost { offset, .. } => write!(f, "invalid host at offset {offset}"),
+            Self::IdnaDisabled { offset, .. } => {
+                write!(f, "host at offset {offset} needs IDNA, which is disabled")
+            }
+            Self::InvalidPath { offset, .. } => write!(f, "invalid path at offset {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for FileUrlError {}
+
+impl<'a> FileUrl<'a> {
+    /// Parse a `file:` URL.
+    ///
+    /// Backslashes are treated as `/`, a `localhost` host is treated as no host, and a Windows
+    /// drive letter is moved out of the host position and written with `:`, so `file://C|\dir`
+    /// has the path `/C:/dir`. The query and fragment are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `file:` URL or if the host is not a valid domain
+    /// or IP address.
+    pub fn parse(input: &'a str) -> Result<Self, FileUrlError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, FileUrlError> {
+        if !input
+            .get(..5)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file:"))
+        {
+            return Err(FileUrlError::InvalidScheme);
+        }
+        let url = &input[5..];
+        let url = &url[..url.find(['?', '#']).unwrap_or(url.len())];
+
+        let mut host = None;
+        let mut path = url;
+        if let Some(rest) = url
+            .strip_prefix(is_slash)
+            .and_then(|rest| rest.strip_prefix(is_slash))
+        {
+            let host_end = rest.find(is_slash).unwrap_or(rest.len());
+            let (authority, rest) = rest.split_at(host_end);
+
+            // A drive letter in the host position is the start of the path instead
+            if is_windows_drive_letter(authority) {
+                path = &url[2..];
+            } else if authority.is_empty() || authority.eq_ignore_ascii_case("localhost") {
+                path = rest;
+            } else {
+                let parsed = parse_host(authority)
+                    .map_err(|kind| FileUrlError::host(kind, 7 + kind.offset(authority)))?;
+                host = Some(parsed);
+                path = rest;
+            }
+        }
+
+        let mut path = if path.contains('\\') {
+            Cow::Owned(path.replace('\\', "/"))
+        } else {
+            Cow::Borrowed(path)
+        };
+        if !path.starts_with('/') {
+            path = Cow::Owned(format!("/{path}"));
+        }
+        if path.get(1..3).is_some_and(is_windows_drive_letter)
+            && matches!(path.as_bytes().get(3), None | Some(b'/'))
+            && path.as_bytes()[2] == b'|'
+        {
+            path.to_mut().replace_range(2..3, ":");
+        }
+
+        Ok(Self { host, path })
+    }
+
+    /// The host of a file on a network share, `None` for local files.
+    #[must_use]
+    pub fn host(&self) -> Option<&Host<'a>> {
+        self.host.as_ref()
+    }
+
+    /// The path as written, always starting with `/`.
+    ///
+    /// Backslashes are replaced with `/` and a drive letter is written with `:`, but the path is
+    /// otherwise neither percent-encoded nor decoded.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The Windows drive letter the path starts with, if any.
+    #[must_use]
+    pub fn drive_letter(&self) -> Option<char> {
+        self.path
+            .get(1..3)
+            .filter(|&drive| is_windows_drive_letter(drive))
+            .filter(|_| matches!(self.path.as_bytes().get(3), None | Some(b'/')))
+            .map(|drive| char::from(drive.as_bytes()[0]))
+    }
+
+    /// Convert to a file path on the current platform.
+    ///
+    /// On Windows, a URL with a host becomes a UNC path such as `\\server\share\file`, and a URL
+    /// without one must start with a drive letter. Elsewhere, URLs with a host are rejected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL can not be represented as a path on the current platform, or
+    /// if the path is not percent-encoded UTF-8 or contains an encoded separator or NUL.
+    pub fn to_file_path(&self) -> Result<PathBuf, FileUrlError> {
+        self.to_file_path_for(cfg!(windows)).map(PathBuf::from)
+    }
+
+    fn to_file_path_for(&self, windows: bool) -> Result<String, FileUrlError> {
+        let invalid_path = |offset| FileUrlError::InvalidPath { offset };
+
+        let decoded = percent_decode_str(&self.path, DecodePolicy::Strict)
+            .map_err(|err| invalid_path(err.offset()))?;
+        if let Some(n) = decoded.find(|c| c == '\0' || (windows && c == '\\')) {
+            return Err(invalid_path(encoded_offset(&self.path, n)));
+        }
+        // A separator that was percent-encoded is part of a file name, which no path can hold
+        if let Some(n) = decoded
+            .match_indices('/')
+            .map(|(n, _)| encoded_offset(&self.path, n))
+            .find(|&n| self.path.as_bytes()[n] == b'%')
+        {
+            return Err(invalid_path(n));
+        }
+
+        if !windows {
+            if self.host.is_some() {
+                return Err(FileUrlError::InvalidHost {
+                    offset: "file://".len(),
+                });
+            }
+            return Ok(decoded.into_owned());
+        }
+
+        match (&self.host, self.drive_letter()) {
+            (Some(host), _) => Ok(format!("\\\\{host}{}", decoded.replace('/', "\\"))),
+            (None, Some(_)) => Ok(decoded[1..].replace('/', "\\")),
+            (None, None) => Err(invalid_path(0)),
+        }
+    }
+
+    /// Convert an absolute file path on the current platform to a `file:` URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the path is not absolute or is not valid UTF-8.
+    pub fn from_file_path(path: &Path) -> Result<String, FileUrlError> {
+        let Some(path) = path.to_str() else {
+            return Err(FileUrlError::InvalidPath { offset: 0 });
+        };
+
+        from_file_path_for(path, cfg!(windows))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for FileUrl<'a> {
+    type Error = FileUrlError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+impl std::fmt::Display for FileUrl<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("file://")?;
+        if let Some(host) = &self.host {
+            write!(f, "{host}")?;
+        }
+        f.write_str(&self.path)
+    }
+}
+
+fn from_file_path_for(path: &str, windows: bool) -> Result<String, FileUrlError> {
+    let not_absolute = || FileUrlError::InvalidPath { offset: 0 };
+
+    let normalized = if windows {
+        Cow::Owned(path.replace('\\', "/"))
+    } else {
+        Cow::Borrowed(path)
+    };
+
+    if windows {
+        // \\server\share\file
+        if let Some(unc) = normalized.strip_prefix("//") {
+            let host_end = unc.find('/').unwrap_or(unc.len());
+            let (host, rest) = unc.split_at(host_end);
+            let host =
+                parse_host(host).map_err(|kind| FileUrlError::host(kind, 2 + kind.offset(host)))?;
+            return Ok(format!("file://{host}{}", percent_encode(rest, &FILE_PATH)));
+        }
+
+        // C:\dir\file
+        if normalized.get(..2).is_some_and(is_windows_drive_letter)
+            && normalized[2..].starts_with('/')
+        {
+            return Ok(format!(
+                "file:///{}",
+                percent_encode(&normalized, &FILE_PATH)
+            ));
+        }
+
+        return Err(not_absolute());
+    }
+
+    if !normalized.starts_with('/') {
+        return Err(not_absolute());
+    }
+
+    Ok(format!(
+        "file://{}",
+        percent_encode(&normalized, &FILE_PATH)
+    ))
+}
+
+// The offset in a valid percent-encoded path of the byte at offset in its decoding
+fn encoded_offset(path: &str, offset: usize) -> usize {
+    let bytes = path.as_bytes();
+    let mut encoded = 0;
+    for _ in 0..offset {
+        encoded += if bytes[encoded] == b'%' { 3 } else { 1 };
+    }
+    encoded
+}
+
+fn is_slash(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+// A Windows drive letter is two code points, of which the first is an ASCII alpha and the second
+// is either U+003A (:) or U+007C (|).
+fn is_windows_drive_letter(input: &str) -> bool {
+    matches!(input.as_bytes(), [letter, b':' | b'|'] if letter.is_ascii_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_url() {
+        let test_data = [
+            ("file:///etc/hosts", None, "/etc/hosts", None),
+            ("FILE:/etc/hosts", None, "/etc/hosts", None),
+            ("file://localhost/etc/hosts", None, "/etc/hosts", None),
+            ("file:///C:/Windows", None, "/C:/Windows", Some('C')),
+            ("file:///c|/Windows", None, "/c:/Windows", Some('c')),
+            ("file://C:/Windows", None, "/C:/Windows", Some('C')),
+            (
+                "file:C:\\Windows\\x.txt",
+                None,
+                "/C:/Windows/x.txt",
+                Some('C'),
+            ),
+            (
+                "file:\\\\server\\share\\x",
+                Some("server"),
+                "/share/x",
+                None,
+            ),
+            (
+                "file://Server.Example/share?q#f",
+                Some("server.example"),
+                "/share",
+                None,
+            ),
+            ("file:///C|x", None, "/C|x", None),
+            ("file://", None, "/", None),
+        ];
+
+        for (input, host, path, drive_letter) in test_data {
+            let url = FileUrl::parse(input).unwrap();
+            assert_eq!(
+                host,
+                url.host().map(ToString::to_string).as_deref(),
+                "{input}"
+            );
+            assert_eq!(path, url.path(), "{input}");
+            assert_eq!(drive_letter, url.drive_letter(), "{input}");
+        }
+
+        assert!(matches!(
+            FileUrl::parse("file:///etc/hosts").unwrap().path,
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            "file://server/share",
+            FileUrl::parse("file:\\\\SERVER\\share")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_file_url_invalid() {
+        let test_data = [
+            ("http://example.com/", ErrorCode::InvalidScheme, 0),
+            ("file://exa mple/x", ErrorCode::InvalidHost, 10),
+            ("file://[::1/x", ErrorCode::InvalidHost, 11),
+        ];
+
+        for (input, code, offset) in test_data {
+            let err = FileUrl::parse(input).unwrap_err();
+            assert_eq!(code, err.code(), "{input}");
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn test_file_url_idna_disabled() {
+        assert!(matches!(
+            FileUrl::parse("file://b\u{fc}cher/x"),
+            Err(FileUrlError::IdnaDisabled { offset: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_file_path() {
+        let test_data = [
+            ("file:///etc/my%20hosts", false, Some("/etc/my hosts")),
+            ("file:///tmp/caf%C3%A9", false, Some("/tmp/caf\u{e9}")),
+            ("file://server/share/x", false, None),
+            ("file:///a%2Fb", false, None),
+            ("file:///a%00", false, None),
+            ("file:///a%FF", false, None),
+            (
+                "file:///C:/Program%20Files/x",
+                true,
+                Some("C:\\Program Files\\x"),
+            ),
+            ("file://server/share/x", true, Some("\\\\server\\share\\x")),
+            ("file:///etc/hosts", true, None),
+            ("file:///C:/a%5Cb", true, None),
+        ];
+
+        for (input, windows, expected) in test_data {
+            let url = FileUrl::parse(input).unwrap();
+            assert_eq!(
+                expected,
+                url.to_file_path_for(windows).ok().as_deref(),
+                "{input}"
+            );
+        }
+
+        let test_data = [
+            ("file:///a%20b%2Fc", false, 6),
+            ("file:///%C3%A9%00", false, 7),
+            ("file:///a%FF", false, 2),
+            ("file:///C:/%20a%5Cb", true, 8),
+            ("file://server/share/x", false, 7),
+        ];
+
+        for (input, windows, offset) in test_data {
+            let err = FileUrl::parse(input)
+                .unwrap()
+                .to_file_path_for(windows)
+                .unwrap_err();
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+
+        assert_eq!(
+            PathBuf::from("/etc/hosts"),
+            FileUrl::parse("file:///etc/hosts")
+                .unwrap()
+                .to_file_path()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_file_path() {
+        let test_data = [
+            ("/etc/hosts", false, Some("file:///etc/hosts")),
+            (
+                "/tmp/a b/100%#?",
+                false,
+                Some("file:///tmp/a%20b/100%25%23%3F"),
+            ),
+            ("/tmp/caf\u{e9}", false, Some("file:///tmp/caf%C3%A9")),
+            ("relative/path", false, None),
+            ("C:\\Windows\\x.txt", true, Some("file:///C:/Windows/x.txt")),
+            ("\\\\Server\\share\\x", true, Some("file://server/share/x")),
+            ("\\Windows", true, None),
+            ("C:relative", true, None),
+            ("\\\\exa mple\\share", true, None),
+        ];
+
+        for (input, windows, expected) in test_data {
+            assert_eq!(
+                expected,
+                from_file_path_for(input, windows).ok().as_deref(),
+                "{input}"
+            );
+        }
+
+        for path in ["/etc/hosts", "/tmp/a b/100%#?"] {
+            let url = FileUrl::from_file_path(Path::new(path)).unwrap();
+            assert_eq!(
+                Path::new(path),
+                FileUrl::parse(&url).unwrap().to_file_path().unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! The `application/x-www-form-urlencoded` format, used by HTML form submissions and query
+//! strings.
+//!
+//! See [WHATWG URL](https://url.spec.whatwg.org/#application/x-www-form-urlencoded)
+
+use std::borrow::Cow;
+
+use crate::percent_encode::{
+    decode_percent_encoded_byte, is_form_urlencoded_percent_encode, percent_decode_lenient,
+    percent_encode_bytes_with, percent_encode_with,
+};
+
+/// Iterator over the name-value pairs of a form-urlencoded byte sequence, see [`parse`].
+#[derive(Debug, Clone)]
+pub struct Parse<'a> {
+    input: &'a [u8],
+}
+
+/// Parse a form-urlencoded byte sequence, such as a request body, into its name-value pairs.
+///
+/// `+` is decoded as a space, and invalid UTF-8 after percent-decoding is replaced by U+FFFD
+/// REPLACEMENT CHARACTER. Names and values that need no decoding are borrowed from the input.
+#[must_use]
+pub fn parse(input: &[u8]) -> Parse<'_> {
+    Parse { input }
+}
+
+impl<'a> Iterator for Parse<'a> {
+    type Item = (Cow<'a, str>, Cow<'a, str>);
+
+    // Let sequences be the result of splitting input on 0x26 (&).
+    // For each byte sequence bytes in sequences:
+    //     If bytes is the empty byte sequence, then continue.
+    //     If bytes contains a 0x3D (=), then let name be the bytes from the start of bytes up to but
+    //     excluding its first 0x3D (=), and let value be the bytes, if any, after the first 0x3D (=)
+    //     up to the end of bytes. If 0x3D (=) is the first byte, then name will be the empty byte
+    //     sequence. If it is the last, then value will be the empty byte sequence.
+    //     Otherwise, let name have the value of bytes and let value be the empty byte sequence.
+    //     Replace any 0x2B (+) in name and value with 0x20 (SP).
+    //     Let nameString and valueString be the result of running UTF-8 decode without BOM on the
+    //     percent-decoding of name and value, respectively.
+    // https://url.spec.whatwg.org/#urlencoded-parsing
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.input.is_empty() {
+                return None;
+            }
+
+            let (bytes, rest) = match self.input.iter().position(|&c| c == b'&') {
+                Some(n) => (&self.input[..n], &self.input[n + 1..]),
+                None => (self.input, &[][..]),
+            };
+            self.input = rest;
+
+            if bytes.is_empty() {
+                continue;
+            }
+
+            let (name, value) = match bytes.iter().position(|&c| c == b'=') {
+                Some(n) => (&bytes[..n], &bytes[n + 1..]),
+                None => (bytes, &[][..]),
+            };
+
+            return Some((decode_bytes(name), decode_bytes(value)));
+        }
+    }
+}
+
+/// Serialize name-value pairs into a form-urlencoded string.
+///
+/// Every pair is written as `name=value`, with spaces encoded as `+` and every byte other than
+/// ASCII alphanumerics and `*-._` percent-encoded.
+///
+/// See [WHATWG URL](https://url.spec.whatwg.org/#urlencoded-serializing)
+#[must_use]
+pub fn serialize<I, N, V>(pairs: I) -> String
+where
+    I: IntoIterator<Item = (N, V)>,
+    N: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = String::new();
+
+    for (name, value) in pairs {
+        if !out.is_empty() {
+            out.push('&');
+        }
+        out.push_str(&encode(name.as_ref()));
+        out.push('=');
+        out.push_str(&encode(value.as_ref()));
+    }
+
+    out
+}
+
+// Replace "+" with " " then percent decode the input
+pub(crate) fn decode(input: &'_ str) -> Cow<'_, str> {
+    decode_bytes(input.as_bytes())
+}
+
+pub(crate) fn decode_bytes(input: &'_ [u8]) -> Cow<'_, str> {
+    match decode_to_bytes(input) {
+        Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+        Cow::Owned(bytes) => Cow::Owned(utf8_lossy(bytes)),
+    }
+}
+
+// Like decode_bytes, keeping bytes that are not valid UTF-8
+pub(crate) fn decode_to_bytes(input: &'_ [u8]) -> Cow<'_, [u8]> {
+    if !input.contains(&b'+') {
+        return percent_decode_lenient(input);
+    }
+
+    // Replace "+" in the same pass as percent-decoding, so that "%2B" is still decoded to "+"
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if let Some(byte) = decode_percent_encoded_byte(&input[i..]) {
+            out.push(byte);
+            i += 3;
+        } else {
+            out.push(if input[i] == b'+' { b' ' } else { input[i] });
+            i += 1;
+        }
+    }
+
+    Cow::Owned(out)
+}
+
+fn utf8_lossy(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
+pub(crate) fn encode(input: &'_ str) -> Cow<'_, str> {
+    percent_encode_with(
+        Cow::Borrowed(input),
+        true,
+        is_form_urlencoded_percent_encode,
+    )
+}
+
+// Like encode, for bytes that may not be UTF-8
+pub(crate) fn encode_bytes(input: &'_ [u8]) -> String {
+    percent_encode_bytes_with(input, true, is_form_urlencoded_percent_encode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    type Pairs<'a> = Vec<(&'a str, &'a str)>;
+
+    #[test]
+    fn test_parse() {
+        let test_data: Vec<(Pairs, &'_ [u8])> = vec![
+            (vec![("a", "1"), ("b", "2")], b"a=1&b=2"),
+            (vec![("a", ""), ("b", "")], b"a&&b="),
+            (vec![("", "x"), ("a b", "c d")], b"=x&a+b=c%20d"),
+            (vec![("a", "b=c")], b"a=b=c"),
+            (vec![("a+b", "%")], b"a%2Bb=%"),
+            (vec![("a +", "+ %")], b"a+%2B=%2B+%"),
+            (vec![("≡", "‽")], b"%E2%89%A1=%E2%80%BD"),
+            (vec![("caf\u{e9}", "\u{FFFD}")], b"caf\xC3\xA9=\xE9"),
+            (vec![("bad", "\u{FFFD}")], b"bad=%FF"),
+            (vec![], b""),
+        ];
+
+        for (expected, input) in test_data {
+            let pairs: Vec<_> = parse(input).collect();
+            assert_eq!(
+                expected,
+                pairs
+                    .iter()
+                    .map(|(name, value)| (&**name, &**value))
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        assert_no_alloc(|| {
+            for (name, value) in parse(b"a=1&b=2&c") {
+                assert!(matches!(name, Cow::Borrowed(_)));
+                assert!(matches!(value, Cow::Borrowed(_)));
+            }
+        });
+    }
+
+    #[test]
+    fn test_serialize() {
+        let test_data: Vec<(&'_ str, Pairs)> = vec![
+            ("a=1&b=2", vec![("a", "1"), ("b", "2")]),
+            ("a+b=c%2Bd", vec![("a b", "c+d")]),
+            ("=&x=", vec![("", ""), ("x", "")]),
+            ("q=%E2%80%BD%26%3D", vec![("q", "‽&=")]),
+            ("*-._=%7E%21", vec![("*-._", "~!")]),
+            ("", vec![]),
+        ];
+
+        for (expected, pairs) in test_data {
+            let serialized = serialize(pairs.iter().copied());
+            assert_eq!(expected, serialized);
+
+            let parsed: Vec<_> = parse(serialized.as_bytes())
+                .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                .collect();
+            assert_eq!(
+                pairs,
+                parsed
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        assert_eq!("a=1", serialize(vec![("a".to_owned(), String::from("1"))]));
+    }
+}
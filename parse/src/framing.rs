@@ -0,0 +1,130 @@
+use crate::{header::is_ows, status::StatusCode};
+
+/// How the length of a message body is determined.
+///
+/// See [RFC9112](https://www.rfc-editor.org/rfc/rfc9112#section-6.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyLength {
+    /// The message has no body, it ends with the header section.
+    Empty,
+    /// The connection becomes a tunnel after the header section, any following data is not part
+    /// of the message.
+    Tunnel,
+    /// The body uses the chunked transfer coding.
+    Chunked,
+    /// The body is exactly this many bytes.
+    Length(u64),
+    /// The body continues until the connection is closed.
+    UntilClose,
+}
+
+/// Determine how the body of a response is framed.
+///
+/// `method` is the method of the request the response answers. `transfer_encoding` and
+/// `content_length` are the values of the response's `Transfer-Encoding` and `Content-Length`
+/// header fields, which the caller is responsible for validating and combining when repeated.
+#[must_use]
+pub fn response_body_length(
+    method: &str,
+    status: StatusCode,
+    transfer_encoding: Option<&str>,
+    content_length: Option<u64>,
+) -> BodyLength {
+    // Any response to a HEAD request and any response with a 1xx (Informational), 204 (No
+    // Content), or 304 (Not Modified) status code is always terminated by the first empty line
+    // after the header fields, regardless of the header fields present in the message, and thus
+    // cannot contain a message body or trailer section.
+    if method == "HEAD"
+        || status.is_informational()
+        || status.as_u16() == 204
+        || status.as_u16() == 304
+    {
+        return BodyLength::Empty;
+    }
+
+    // Any 2xx (Successful) response to a CONNECT request implies that the connection will become
+    // a tunnel immediately after the empty line that concludes the header fields.
+    if method == "CONNECT" && status.is_success() {
+        return BodyLength::Tunnel;
+    }
+
+    // If a Transfer-Encoding header field is present in a response and the chunked transfer
+    // coding is the final encoding, the message body length is determined by reading and
+    // decoding the chunked data until the transfer coding indicates the data is complete.
+    //
+    // If a Transfer-Encoding header field is present in a response and the chunked transfer
+    // coding is not the final encoding, the message body length is determined by reading the
+    // connection until it is closed by the server.
+    if let Some(transfer_encoding) = transfer_encoding {
+        return if is_chunked_final(transfer_encoding) {
+            BodyLength::Chunked
+        } else {
+            BodyLength::UntilClose
+        };
+    }
+
+    // If a valid Content-Length header field is present without Transfer-Encoding, its decimal
+    // value defines the expected message body length in octets.
+    if let Some(content_length) = content_length {
+        return BodyLength::Length(content_length);
+    }
+
+    // Otherwise, this is a response message without a declared message body length, so the
+    // message body length is determined by the number of octets received prior to the server
+    // closing the connection.
+    BodyLength::UntilClose
+}
+
+// transfer-coding names are case-insensitive, and the list may contain empty elements
+fn is_chunked_final(transfer_encoding: &'_ str) -> bool {
+    transfer_encoding
+        .rsplit(',')
+        .map(|coding| coding.trim_matches(is_ows))
+        .find(|coding| !coding.is_empty())
+        .is_some_and(|coding| coding.eq_ignore_ascii_case("chunked"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_body_length() {
+        let test_data = [
+            ("HEAD", 200, None, Some(10), BodyLength::Empty),
+            ("HEAD", 200, Some("chunked"), None, BodyLength::Empty),
+            ("GET", 101, None, None, BodyLength::Empty),
+            ("GET", 204, None, Some(10), BodyLength::Empty),
+            ("GET", 304, Some("chunked"), None, BodyLength::Empty),
+            ("CONNECT", 200, None, Some(10), BodyLength::Tunnel),
+            ("CONNECT", 407, None, Some(10), BodyLength::Length(10)),
+            ("GET", 200, Some("chunked"), Some(10), BodyLength::Chunked),
+            ("GET", 200, Some("gzip, CHUNKED"), None, BodyLength::Chunked),
+            ("GET", 200, Some("chunked , "), None, BodyLength::Chunked),
+            (
+                "GET",
+                200,
+                Some("chunked, gzip"),
+                None,
+                BodyLength::UntilClose,
+            ),
+            ("GET", 200, Some(""), None, BodyLength::UntilClose),
+            ("GET", 200, None, Some(0), BodyLength::Length(0)),
+            ("POST", 500, None, None, BodyLength::UntilClose),
+            ("head", 200, None, Some(10), BodyLength::Length(10)),
+        ];
+
+        for (method, status, transfer_encoding, content_length, expected) in test_data {
+            assert_eq!(
+                expected,
+                response_body_length(
+                    method,
+                    StatusCode::new(status).unwrap(),
+                    transfer_encoding,
+                    content_length
+                ),
+                "{method} {status}"
+            );
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{error::ErrorCode, reject};
+
+/// Options for [`sanitize_header`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SanitizeOptions {
+    /// Remove CR, LF, and NUL from values instead of rejecting them.
+    pub strip_control: bool,
+    /// Drop hop-by-hop header fields, see [`is_hop_by_hop`].
+    pub drop_hop_by_hop: bool,
+}
+
+/// An error encountered while parsing or sanitizing a header field.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum HeaderError {
+    /// The field name is not a token.
+    InvalidName { offset: usize },
+    /// The field value contains a character it can not, or does not match the grammar of the
+    /// field.
+    InvalidValue { offset: usize },
+}
+
+impl HeaderError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidHeader
+    }
+
+    /// The byte offset in the name or value at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidName { offset, .. } | Self::InvalidValue { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName { offset, .. } => write!(f, "invalid header name at offset {offset}"),
+            Self::InvalidValue { offset, .. } => {
+                write!(f, "invalid header value at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+/// A header field value.
+///
+/// Field values are octets rather than text. Values containing bytes outside ASCII are usually
+/// ISO-8859-1 or UTF-8 written by older implementations, so they are accepted when parsing and
+/// only interpreted as text when [`to_str`](Self::to_str) or [`to_str_lossy`](Self::to_str_lossy)
+/// is called.
+///
+/// See [RFC9110](https://www.rfc-editor.org/rfc/rfc9110#section-5.5)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderValue<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> HeaderValue<'a> {
+    /// Parse a field value, removing leading and trailing whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value contains a control character other than horizontal tab.
+    pub fn parse(input: &'a [u8]) -> Result<Self, HeaderError> {
+        // field-value    = *field-content
+        // field-content  = field-vchar
+        //                  [ 1*( SP / HTAB / field-vchar ) field-vchar ]
+        // field-vchar    = VCHAR / obs-text
+        // obs-text       = %x80-FF
+        if let Some(offset) = input
+            .iter()
+            .position(|&c| c != b'\t' && c.is_ascii_control())
+        {
+            reject::report_bytes(input, ErrorCode::InvalidHeader, offset);
+            return Err(HeaderError::InvalidValue { offset });
+        }
+
+        let start = input
+            .iter()
+            .position(|&c| !is_ows(char::from(c)))
+            .unwrap_or(input.len());
+        let end = input
+            .iter()
+            .rposition(|&c| !is_ows(char::from(c)))
+            .map_or(start, |n| n + 1);
+
+        Ok(Self {
+            bytes: &input[start..end],
+        })
+    }
+
+    /// The octets of the value.
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The value as a string, if it is valid UTF-8.
+    #[must_use]
+    pub fn to_str(&self) -> Option<&'a str> {
+        std::str::from_utf8(self.bytes).ok()
+    }
+
+    /// The value as a string, with any invalid UTF-8 replaced by U+FFFD REPLACEMENT CHARACTER.
+    #[must_use]
+    pub fn to_str_lossy(&self) -> Cow<'a, str> {
+        String::from_utf8_lossy(self.bytes)
+    }
+}
+
+/// Check a header field before it is written to an outbound message.
+///
+/// The name must be a token, and the value must not contain CR, LF, or NUL, which would allow
+/// splitting the message or injecting header fields when the value comes from user input.
+/// Returns `None` if the field should be dropped.
+///
+/// # Errors
+///
+/// Returns an error if the name is not a token, or if the value contains CR, LF, or NUL and
+/// `strip_control` is not set.
+pub fn sanitize_header<'a>(
+    name: &'a str,
+    value: &'a str,
+    options: SanitizeOptions,
+) -> Result<Option<(&'a str, Cow<'a, str>)>, HeaderError> {
+    if let Some(offset) = invalid_name_offset(name) {
+        reject::report(name, ErrorCode::InvalidHeader, offset);
+        return Err(HeaderError::InvalidName { offset });
+    }
+
+    if options.drop_hop_by_hop && is_hop_by_hop(name) {
+        return Ok(None);
+    }
+
+    let Some(offset) = value.find(is_forbidden_in_value) else {
+        return Ok(Some((name, Cow::Borrowed(value))));
+    };
+
+    if !options.strip_control {
+        reject::report(value, ErrorCode::InvalidHeader, offset);
+        return Err(HeaderError::InvalidValue { offset });
+    }
+
+    Ok(Some((
+        name,
+        Cow::Owned(value.replace(is_forbidden_in_value, "")),
+    )))
+}
+
+/// Whether a header field only applies to a single connection and must not be forwarded.
+///
+/// See [RFC9110](https://www.rfc-editor.org/rfc/rfc9110#section-7.6.1)
+#[must_use]
+pub fn is_hop_by_hop(name: &str) -> bool {
+    [
+        "connection",
+        "keep-alive",
+        "proxy-connection",
+        "proxy-authenticate",
+        "proxy-authorization",
+        "te",
+        "trailer",
+        "transfer-encoding",
+        "upgrade",
+    ]
+    .iter()
+    .any(|hop_by_hop| name.eq_ignore_ascii_case(hop_by_hop))
+}
+
+// field-name = token
+fn invalid_name_offset(name: &'_ str) -> Option<usize> {
+    if name.is_empty() {
+        return Some(0);
+    }
+
+    name.find(|c| !is_tchar(c))
+}
+
+// token = 1*tchar
+pub(crate) fn is_token(input: &str) -> bool {
+    !input.is_empty() && input.chars().all(is_tchar)
+}
+
+// tchar = "!" / "#" / "$" / "%" / "&" / "'" / "*" / "+" / "-" / "." / "^" / "_" / "`" / "|" / "~"
+//       / DIGIT / ALPHA
+pub(crate) fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+// OWS = *( SP / HTAB )
+pub(crate) fn is_ows(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+// CR and LF end the field line, NUL is rejected by most implementations
+fn is_forbidden_in_value(c: char) -> bool {
+    c == '\r' || c == '\n' || c == '\0'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_sanitize_header() {
+        let strip = SanitizeOptions {
+            strip_control: true,
+            ..SanitizeOptions::default()
+        };
+        let drop = SanitizeOptions {
+            drop_hop_by_hop: true,
+            ..SanitizeOptions::default()
+        };
+
+        let test_data = [
+            ("Content-Type", "text/html", strip, Some("text/html")),
+            (
+                "X-Name",
+                "a\r\nSet-Cookie: x=1",
+                strip,
+                Some("aSet-Cookie: x=1"),
+            ),
+            ("X-Name", "a\0b", strip, Some("ab")),
+            ("X-Name", "caf\u{e9}", strip, Some("caf\u{e9}")),
+            ("Connection", "close", strip, Some("close")),
+            ("Connection", "close", drop, None),
+            ("Transfer-Encoding", "chunked", drop, None),
+            ("Content-Length", "10", drop, Some("10")),
+        ];
+
+        for (name, value, options, expected) in test_data {
+            let actual = sanitize_header(name, value, options).unwrap();
+            assert_eq!(expected, actual.as_ref().map(|(_, value)| value.as_ref()));
+        }
+
+        assert_no_alloc(|| {
+            assert!(sanitize_header("Host", "example.com", SanitizeOptions::default()).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_header_value() {
+        let test_data: Vec<(&'_ [u8], Option<&'_ str>, &'_ str)> = vec![
+            (b"text/html", Some("text/html"), "text/html"),
+            (b" \t a  b \t", Some("a  b"), "a  b"),
+            (b"", Some(""), ""),
+            (b"   ", Some(""), ""),
+            (b"caf\xC3\xA9", Some("caf\u{e9}"), "caf\u{e9}"),
+            (b"caf\xE9", None, "caf\u{FFFD}"),
+        ];
+
+        for (input, string, lossy) in test_data {
+            let value = assert_no_alloc(|| HeaderValue::parse(input).unwrap());
+            assert_eq!(input.trim_ascii(), value.as_bytes());
+            assert_eq!(string, value.to_str());
+            assert_eq!(lossy, value.to_str_lossy());
+        }
+
+        let test_data: Vec<(&'_ [u8], usize)> =
+            vec![(b"a\r\nb", 1), (b"ab\0", 2), (b"\x7F", 0), (b"a\x1Bb", 1)];
+
+        for (input, offset) in test_data {
+            let err = HeaderValue::parse(input).unwrap_err();
+            assert!(matches!(err, HeaderError::InvalidValue { .. }));
+            assert_eq!(offset, err.offset());
+        }
+    }
+
+    #[test]
+    fn test_sanitize_header_invalid() {
+        let test_data = [
+            ("", "x", 0),
+            ("X Name", "x", 1),
+            ("X-Name:", "x", 6),
+            ("Caf\u{e9}", "x", 3),
+            ("X-Name", "a\r\nb", 1),
+            ("X-Name", "a\nb", 1),
+            ("X-Name", "ab\0", 2),
+        ];
+
+        for (name, value, offset) in test_data {
+            let err = sanitize_header(name, value, SanitizeOptions::default()).unwrap_err();
+            assert_eq!(ErrorCode::InvalidHeader, err.code());
+            assert_eq!(offset, err.offset(), "{name:?}: {value:?}");
+        }
+    }
+}
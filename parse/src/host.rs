@@ -0,0 +1,832 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use nom::{
+    bytes::complete::take_while1,
+    character::complete::{char, one_of},
+    combinator::{all_consuming, recognize},
+    sequence::tuple,
+};
+
+use crate::{
+    domain::domain_to_ascii,
+    error::ErrorCode,
+    ipv4::{self, Ipv4Host},
+    ipv6,
+    parse::ParseResult,
+    percent_encode::{decode_percent_encoded_byte, percent_decode_utf8_lossy},
+    reject,
+};
+
+/// The kind of a URL host, see [`classify_host`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKind {
+    /// An IPv4 address, possibly in a non-canonical notation, see [`Ipv4Host`].
+    Ipv4,
+    /// An IPv6 address enclosed in brackets.
+    Ipv6,
+    /// An address in a future IP version enclosed in brackets, such as `[v1.fe80::a+en1]`.
+    IpvFuture,
+    /// A domain name.
+    Domain,
+    /// Not a valid host.
+    Invalid,
+}
+
+/// A parsed URL host.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Host<'a> {
+    /// A domain in its lowercase ASCII form, borrowed from the input if it was already in that
+    /// form.
+    Domain(Cow<'a, str>),
+    /// An IPv4 address, possibly written in a non-canonical notation, see [`Ipv4Host`].
+    Ipv4(Ipv4Addr),
+    /// An IPv6 address, written in brackets.
+    ///
+    /// The zone identifier of a link-local address, such as `eth0` in `[fe80::1%25eth0]`, is kept
+    /// as it was written, percent-encoded, see [`zone_id`](Self::zone_id).
+    Ipv6 {
+        addr: Ipv6Addr,
+        zone_id: Option<&'a str>,
+    },
+    /// An address in a future IP version, as written between the brackets.
+    IpvFuture(&'a str),
+}
+
+impl<'a> Host<'a> {
+    /// Parse a host the way the [WHATWG](https://url.spec.whatwg.org/#concept-host-parser) host
+    /// parser does for special schemes such as `http`, see [`classify_host`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host is not a valid domain, IPv4 address, or bracketed IPv6
+    /// address, or if it is a domain that needs IDNA processing and the `idna` feature is
+    /// disabled.
+    pub fn parse(input: &'a str) -> Result<Self, HostError> {
+        parse_host(input)
+            .map_err(|kind| kind.into_error(input))
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    /// The percent-decoded zone identifier of an IPv6 address, if it has one.
+    #[must_use]
+    pub fn zone_id(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Self::Ipv6 {
+                zone_id: Some(zone_id),
+                ..
+            } => Some(percent_decode_utf8_lossy(zone_id)),
+            _ => None,
+        }
+    }
+
+    /// The kind of the host.
+    #[must_use]
+    pub fn kind(&self) -> HostKind {
+        match self {
+            Self::Domain(_) => HostKind::Domain,
+            Self::Ipv4(_) => HostKind::Ipv4,
+            Self::Ipv6 { .. } => HostKind::Ipv6,
+            Self::IpvFuture(_) => HostKind::IpvFuture,
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Host<'a> {
+    type Error = HostError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+/// Serialize the host, IPv4 addresses in dotted decimal, IPv6 addresses in brackets in their
+/// [RFC5952](https://www.rfc-editor.org/rfc/rfc5952) form followed by any zone identifier, and
+/// future addresses in brackets as they were written.
+impl fmt::Display for Host<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Domain(domain) => f.write_str(domain),
+            Self::Ipv4(addr) => write!(f, "{addr}"),
+            Self::Ipv6 {
+                addr,
+                zone_id: None,
+            } => write!(f, "[{addr}]"),
+            Self::Ipv6 {
+                addr,
+                zone_id: Some(zone_id),
+            } => write!(f, "[{addr}%25{zone_id}]"),
+            Self::IpvFuture(addr) => write!(f, "[{addr}]"),
+        }
+    }
+}
+
+/// An error encountered while parsing a [`Host`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum HostError {
+    /// A bracketed IP literal is not a valid IPv6 address, or is missing its closing `]`.
+    InvalidIpv6 { offset: usize },
+    /// A bracketed IP literal starting with `v` is not a valid future IP version literal.
+    InvalidIpvFuture { offset: usize },
+    /// The zone identifier following an IPv6 address is empty, contains invalid characters, or
+    /// is not separated from the address by `%25`.
+    InvalidZoneId { offset: usize },
+    /// The domain contains a forbidden code point, possibly percent-encoded.
+    ForbiddenCodePoint { offset: usize },
+    /// The domain ends in a number, but is not a valid IPv4 address.
+    InvalidIpv4 { offset: usize },
+    /// The domain has an empty label or fails IDNA processing.
+    InvalidDomain { offset: usize },
+    /// The host contains non-ASCII characters or punycode labels, which can only be processed
+    /// with the `idna` feature enabled.
+    IdnaDisabled { offset: usize },
+}
+
+impl HostError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidHost
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    ///
+    /// For domains this is the start of the label that failed IDNA processing, or 0 if the
+    /// domain was only rejected as a whole.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidIpv6 { offset, .. }
+            | Self::InvalidIpvFuture { offset, .. }
+            | Self::InvalidZoneId { offset, .. }
+            | Self::ForbiddenCodePoint { offset, .. }
+            | Self::InvalidIpv4 { offset, .. }
+            | Self::InvalidDomain { offset, .. }
+            | Self::IdnaDisabled { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidIpv6 { offset, .. } => {
+                write!(f, "invalid IPv6 address at offset {offset}")
+            }
+            Self::InvalidIpvFuture { offset, .. } => {
+                write!(f, "invalid IPvFuture address at offset {offset}")
+            }
+            Self::InvalidZoneId { offset, .. } => {
+                write!(f, "invalid IPv6 zone identifier at offset {offset}")
+            }
+            Self::ForbiddenCodePoint { offset, .. } => {
+                write!(f, "forbidden code point in host at offset {offset}")
+            }
+            Self::InvalidIpv4 { offset, .. } => {
+                write!(f, "invalid IPv4 address at offset {offset}")
+            }
+            Self::InvalidDomain { offset, .. } => write!(f, "invalid domain at offset {offset}"),
+            Self::IdnaDisabled { offset, .. } => {
+                write!(f, "host at offset {offset} needs IDNA, which is disabled")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
+
+// Why a host was rejected. The offsets of errors in domains take more work to find, so the
+// predicates built on the host parser leave them to be found when asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HostErrorKind {
+    Ipv6(usize),
+    IpvFuture(usize),
+    ZoneId(usize),
+    ForbiddenCodePoint(usize),
+    Ipv4,
+    Domain,
+    IdnaDisabled,
+}
+
+impl HostErrorKind {
+    // The byte offset in the host at which the error was detected
+    pub(crate) fn offset(self, host: &str) -> usize {
+        match self {
+            Self::Ipv6(offset)
+            | Self::IpvFuture(offset)
+            | Self::ZoneId(offset)
+            | Self::ForbiddenCodePoint(offset) => offset,
+            Self::Ipv4 => ipv4::invalid_part_offset(host),
+            Self::Domain | Self::IdnaDisabled => invalid_label_offset(host),
+        }
+    }
+
+    pub(crate) fn into_error(self, input: &str) -> HostError {
+        let offset = self.offset(input);
+        match self {
+            Self::Ipv6(_) => HostError::InvalidIpv6 { offset },
+            Self::IpvFuture(_) => HostError::InvalidIpvFuture { offset },
+            Self::ZoneId(_) => HostError::InvalidZoneId { offset },
+            Self::ForbiddenCodePoint(_) => HostError::ForbiddenCodePoint { offset },
+            Self::Ipv4 => HostError::InvalidIpv4 { offset },
+            Self::Domain => HostError::InvalidDomain { offset },
+            Self::IdnaDisabled => HostError::IdnaDisabled { offset },
+        }
+    }
+}
+
+/// Classify a host the way the [WHATWG](https://url.spec.whatwg.org/#concept-host-parser) host
+/// parser does for special schemes such as `http`.
+///
+/// The decision is made by the first matching row:
+///
+/// | Host                                                          | Kind                 |
+/// |---------------------------------------------------------------|----------------------|
+/// | Starts with `[`                                               | `Ipv6` if the rest is an IPv6 address followed by `]`, `IpvFuture` if it is a future IP version literal followed by `]`, otherwise `Invalid` |
+/// | Fails percent-decoding and IDNA processing, or contains a forbidden code point | `Invalid` |
+/// | Last label, ignoring one trailing dot, is decimal or `0x` hex | `Ipv4` if the host is an IPv4 address, otherwise `Invalid` |
+/// | Anything else                                                 | `Domain`             |
+///
+/// In particular a host that looks numeric never falls back to being a domain, so `1.2.3.4.`
+/// is `Ipv4` while `1.2.3.4.5` and `1.2.3.09` are `Invalid`.
+///
+/// Literals for future IP versions are defined by
+/// [RFC3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.2.2), and IPv6 zone identifiers
+/// such as `[fe80::1%25eth0]` by [RFC6874](https://www.rfc-editor.org/rfc/rfc6874). Both are
+/// rejected by the WHATWG parser, callers that need to match browsers exactly should treat them
+/// as `Invalid`.
+#[must_use]
+pub fn classify_host(host: &str) -> HostKind {
+    parse_host(host).map_or(HostKind::Invalid, |host| host.kind())
+}
+
+/// Whether an origin is potentially trustworthy, so features restricted to secure contexts may
+/// be used by it.
+///
+/// `scheme` and `host` are the scheme and host of a URL with a tuple origin, the host is parsed
+/// as for [`classify_host`]. Loopback addresses are recognized in any IPv4 notation, such as
+/// `127.1` or `0x7f000001`, and `localhost` and its subdomains are assumed to resolve to a
+/// loopback address.
+///
+/// See [Secure Contexts](https://w3c.github.io/webappsec-secure-contexts/#is-origin-trustworthy)
+#[must_use]
+pub fn is_potentially_trustworthy_origin(scheme: &str, host: &str) -> bool {
+    // If origin's scheme is either "https" or "wss", return "Potentially Trustworthy".
+    if scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("wss") {
+        return true;
+    }
+
+    let trustworthy_host = match parse_host(host) {
+        // If origin's host matches one of the CIDR notations 127.0.0.0/8 or ::1/128, return
+        // "Potentially Trustworthy".
+        Ok(Host::Ipv4(addr)) => addr.is_loopback(),
+        Ok(Host::Ipv6 { addr, .. }) => addr == Ipv6Addr::LOCALHOST,
+        // If the user agent conforms to the name resolution rules in
+        // [let-localhost-be-localhost] and one of the following is true:
+        //     origin's host is "localhost" or "localhost."
+        //     origin's host ends with ".localhost" or ".localhost."
+        // then return "Potentially Trustworthy".
+        Ok(Host::Domain(domain)) => {
+            let domain = domain.strip_suffix('.').unwrap_or(&domain);
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Ok(Host::IpvFuture(_)) | Err(_) => false,
+    };
+
+    // If origin's scheme is "file", return "Potentially Trustworthy".
+    trustworthy_host || scheme.eq_ignore_ascii_case("file")
+}
+
+/// Whether a host matches a DNS name presented in a TLS certificate.
+///
+/// The host is parsed as for [`classify_host`] and compared in its A-label form, so a host
+/// written with Unicode labels matches the name in the certificate. The comparison ignores
+/// ASCII case and a trailing dot. A `*` is only a wildcard when it is the whole leftmost label,
+/// and it matches exactly one label, so `*.example.com` matches `www.example.com` but neither
+/// `example.com` nor `a.www.example.com`. Wildcards directly above a top level domain such as
+/// `*.com` never match.
+///
+/// IP addresses never match a DNS name, they must be compared against the IP address entries
+/// of the certificate instead.
+///
+/// See [RFC6125](https://www.rfc-editor.org/rfc/rfc6125#section-6.4)
+#[must_use]
+pub fn host_matches_dns_name(host: &str, dns_name: &str) -> bool {
+    let Ok(Host::Domain(host)) = parse_host(host) else {
+        return false;
+    };
+
+    let host = host.strip_suffix('.').unwrap_or(&host);
+    let dns_name = dns_name.strip_suffix('.').unwrap_or(dns_name);
+
+    // Certificates carry names already converted to A-labels
+    if !dns_name.is_ascii() {
+        return false;
+    }
+
+    match dns_name.strip_prefix("*.") {
+        Some(parent) if parent.contains('.') && !parent.contains('*') => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest.eq_ignore_ascii_case(parent)),
+        Some(_) => false,
+        None => !dns_name.contains('*') && host.eq_ignore_ascii_case(dns_name),
+    }
+}
+
+pub(crate) fn parse_host(host: &'_ str) -> Result<Host<'_>, HostErrorKind> {
+    // IP-literal = "[" ( IPv6address / IPvFuture  ) "]"
+    if let Some(literal) = host.strip_prefix('[') {
+        return parse_ip_literal(literal);
+    }
+
+    // Checked before IDNA processing as well as after, so the error points at the code point
+    if let Some(offset) = forbidden_code_point_offset(host) {
+        return Err(HostErrorKind::ForbiddenCodePoint(offset));
+    }
+
+    let domain = percent_decode_utf8_lossy(host);
+    let ascii_domain = domain_to_ascii(&domain, false).map_err(|err| {
+        if err.is_idna_disabled() {
+            HostErrorKind::IdnaDisabled
+        } else {
+            HostErrorKind::Domain
+        }
+    })?;
+
+    // In case IDNA processing mapped a code point to a forbidden one
+    if ascii_domain.contains(is_forbidden_domain_code_point) {
+        return Err(HostErrorKind::ForbiddenCodePoint(0));
+    }
+
+    if ends_in_a_number(&ascii_domain) {
+        return Ipv4Host::parse(&ascii_domain)
+            .map(|host| Host::Ipv4(host.addr()))
+            .ok_or(HostErrorKind::Ipv4);
+    }
+
+    if ascii_domain == host {
+        return Ok(Host::Domain(Cow::Borrowed(host)));
+    }
+
+    Ok(Host::Domain(Cow::Owned(ascii_domain.into_owned())))
+}
+
+// The input follows the opening "[", and offsets in errors are from the "["
+fn parse_ip_literal(literal: &'_ str) -> Result<Host<'_>, HostErrorKind> {
+    let is_future = literal.starts_with(['v', 'V']);
+    let Some(addr) = literal.strip_suffix(']') else {
+        let offset = literal.len() + 1;
+        return Err(if is_future {
+            HostErrorKind::IpvFuture(offset)
+        } else {
+            HostErrorKind::Ipv6(offset)
+        });
+    };
+
+    if is_future {
+        return match all_consuming(parse_ipv_future)(addr) {
+            Ok((_, addr)) => Ok(Host::IpvFuture(addr)),
+            Err(err) => Err(HostErrorKind::IpvFuture(
+                1 + addr.len() - nom_error_input(err, addr).len(),
+            )),
+        };
+    }
+
+    if let Ok((_, (addr, zone_id))) = all_consuming(ipv6::parse_with_zone_id)(addr) {
+        return Ok(Host::Ipv6 { addr, zone_id });
+    }
+
+    // Find where the literal stops being an address followed by a zone identifier
+    let Ok((rest, _)) = ipv6::parse(addr) else {
+        // Point at the first character that can not be part of an address, or else at the
+        // whole address
+        let offset = addr
+            .find(|c: char| !c.is_ascii_hexdigit() && c != ':' && c != '.')
+            .map_or(1, |n| n + 1);
+        return Err(HostErrorKind::Ipv6(offset));
+    };
+    let rest_offset = literal.len() - rest.len();
+
+    if !rest.starts_with('%') {
+        return Err(HostErrorKind::Ipv6(rest_offset));
+    }
+    let Some(zone_id) = rest.strip_prefix("%25") else {
+        return Err(HostErrorKind::ZoneId(rest_offset));
+    };
+
+    // ZoneID = 1*( unreserved / pct-encoded )
+    let mut n = 0;
+    while let Some(&c) = zone_id.as_bytes().get(n) {
+        if c.is_ascii_alphanumeric() || b"-._~".contains(&c) {
+            n += 1;
+        } else if decode_percent_encoded_byte(&zone_id.as_bytes()[n..]).is_some() {
+            n += 3;
+        } else {
+            break;
+        }
+    }
+    Err(HostErrorKind::ZoneId(rest_offset + 3 + n))
+}
+
+// IPvFuture = "v" 1*HEXDIG "." 1*( unreserved / sub-delims / ":" )
+// unreserved = ALPHA / DIGIT / "-" / "." / "_" / "~"
+// sub-delims = "!" / "$" / "&" / "'" / "(" / ")" / "*" / "+" / "," / ";" / "="
+fn parse_ipv_future(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    recognize(tuple((
+        one_of("vV"),
+        take_while1(|c: char| c.is_ascii_hexdigit()),
+        char('.'),
+        take_while1(|c: char| c.is_ascii_alphanumeric() || "-._~!$&'()*+,;=:".contains(c)),
+    )))(i)
+}
+
+// A forbidden host code point is U+0000 NULL, U+0009 TAB, U+000A LF, U+000D CR, U+0020 SPACE,
+// U+0023 (#), U+002F (/), U+003A (:), U+003C (<), U+003E (>), U+003F (?), U+0040 (@), U+005B ([),
+// U+005C (\), U+005D (]), U+005E (^), or U+007C (|).
+//
+// A forbidden domain code point is a forbidden host code point, a C0 control, U+0025 (%), or
+// U+007F DELETE.
+fn is_forbidden_domain_code_point(c: char) -> bool {
+    c.is_ascii_control() || " #%/:<>?@[\\]^|".contains(c)
+}
+
+// The byte offset of the first forbidden domain code point in the host, which may be
+// percent-encoded
+fn forbidden_code_point_offset(host: &str) -> Option<usize> {
+    host.char_indices()
+        .find(|&(n, c)| {
+            if c != '%' {
+                return is_forbidden_domain_code_point(c);
+            }
+            // A "%" that does not start a percent-encoded byte is kept as it is
+            decode_percent_encoded_byte(&host.as_bytes()[n..])
+                .is_none_or(|c| is_forbidden_domain_code_point(char::from(c)))
+        })
+        .map(|(n, _)| n)
+}
+
+// The byte offset of the first label of the host that is rejected on its own, or 0 if only the
+// whole domain is rejected. Only the last label may be empty, marking a fully qualified domain.
+fn invalid_label_offset(host: &str) -> usize {
+    let mut offset = 0;
+
+    for label in host.split('.') {
+        let is_valid = if label.is_empty() {
+            offset != 0 && offset == host.len()
+        } else {
+            domain_to_ascii(&percent_decode_utf8_lossy(label), false).is_ok()
+        };
+        if !is_valid {
+            return offset;
+        }
+        offset += label.len() + 1;
+    }
+
+    0
+}
+
+// The remaining input at which a nom parser failed
+fn nom_error_input<'a>(err: nom::Err<nom::error::Error<&'a str>>, input: &'a str) -> &'a str {
+    match err {
+        nom::Err::Error(err) | nom::Err::Failure(err) => err.input,
+        nom::Err::Incomplete(_) => input,
+    }
+}
+
+// The ends in a number checker takes an ASCII string input and then runs these steps. They
+// return a boolean.
+//     Let parts be the result of strictly splitting input on U+002E (.).
+//     If the last item in parts is the empty string, then:
+//         If parts’s size is 1, return false.
+//         Remove the last item from parts.
+//     Let last be the last item in parts.
+//     If last is non-empty and contains only ASCII digits, then return true.
+//     If parsing last as an IPv4 number does not return failure, then return true.
+//     Return false.
+// https://url.spec.whatwg.org/#ends-in-a-number-checker
+fn ends_in_a_number(input: &'_ str) -> bool {
+    let input = match input.strip_suffix('.') {
+        Some("") => return false,
+        Some(input) => input,
+        None => input,
+    };
+    let last = input.rsplit('.').next().unwrap_or(input);
+
+    if !last.is_empty() && last.bytes().all(|c| c.is_ascii_digit()) {
+        return true;
+    }
+
+    // Only the hexadecimal form of an IPv4 number can contain anything other than digits
+    last.strip_prefix("0x")
+        .or_else(|| last.strip_prefix("0X"))
+        .is_some_and(|hex| hex.bytes().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_host() {
+        let test_data = [
+            ("example.com", HostKind::Domain),
+            ("EXAMPLE.com.", HostKind::Domain),
+            ("localhost", HostKind::Domain),
+            ("1.2.3.4.example", HostKind::Domain),
+            ("example.1a", HostKind::Domain),
+            ("example.0xg", HostKind::Domain),
+            ("exa%6Dple.com", HostKind::Domain),
+            ("1.2.3.4", HostKind::Ipv4),
+            ("1.2.3.4.", HostKind::Ipv4),
+            ("010.010.010.010", HostKind::Ipv4),
+            ("0x7f.1", HostKind::Ipv4),
+            ("2130706433", HostKind::Ipv4),
+            ("%31.2.3.4", HostKind::Ipv4),
+            ("example.0x", HostKind::Invalid),
+            ("1.2.3.4.5", HostKind::Invalid),
+            ("1.2.3.09", HostKind::Invalid),
+            ("256.1.1.1", HostKind::Invalid),
+            ("example.123", HostKind::Invalid),
+            ("1.2.3.4..", HostKind::Invalid),
+            (".", HostKind::Invalid),
+            ("[::1]", HostKind::Ipv6),
+            ("[::ffff:1.2.3.4]", HostKind::Ipv6),
+            ("[::1", HostKind::Invalid),
+            ("[fe80::1%25eth0]", HostKind::Ipv6),
+            ("[fe80::1%eth0]", HostKind::Invalid),
+            ("[v1.fe80::a+en1]", HostKind::IpvFuture),
+            ("[V7.x]", HostKind::IpvFuture),
+            ("[v.x]", HostKind::Invalid),
+            ("[v1.]", HostKind::Invalid),
+            ("[v1x.a]", HostKind::Invalid),
+            ("[v1.a/b]", HostKind::Invalid),
+            ("[1.2.3.4]", HostKind::Invalid),
+            ("exa mple.com", HostKind::Invalid),
+            ("example.com:80", HostKind::Invalid),
+            ("exa%25mple.com", HostKind::Invalid),
+            ("a@b", HostKind::Invalid),
+            ("", HostKind::Invalid),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(expected, classify_host(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_is_potentially_trustworthy_origin() {
+        let test_data = [
+            ("https", "example.com", true),
+            ("WSS", "example.com", true),
+            ("http", "example.com", false),
+            ("ws", "example.com", false),
+            ("http", "127.0.0.1", true),
+            ("http", "127.255.0.1", true),
+            ("http", "127.1", true),
+            ("http", "0x7f.1", true),
+            ("http", "2130706433", true),
+            ("http", "0177.0.0.1", true),
+            ("http", "128.0.0.1", false),
+            ("http", "[::1]", true),
+            ("http", "[0:0:0:0:0:0:0:1]", true),
+            ("http", "[::ffff:127.0.0.1]", false),
+            ("http", "[::1%25lo]", true),
+            ("http", "[v1.::1]", false),
+            ("http", "localhost", true),
+            ("http", "LOCALHOST.", true),
+            ("http", "app.localhost", true),
+            ("http", "localhost.example", false),
+            ("http", "notlocalhost", false),
+            ("file", "", true),
+            ("http", "", false),
+        ];
+
+        for (scheme, host, expected) in test_data {
+            assert_eq!(
+                expected,
+                is_potentially_trustworthy_origin(scheme, host),
+                "{scheme}://{host}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_matches_dns_name() {
+        let test_data = [
+            ("example.com", "example.com", true),
+            ("EXAMPLE.com", "example.COM", true),
+            ("example.com.", "example.com", true),
+            ("example.com", "example.com.", true),
+            ("exa%6Dple.com", "example.com", true),
+            ("www.example.com", "example.com", false),
+            ("example.com", "www.example.com", false),
+            ("www.example.com", "*.example.com", true),
+            ("WWW.example.com.", "*.EXAMPLE.com", true),
+            ("example.com", "*.example.com", false),
+            ("a.www.example.com", "*.example.com", false),
+            ("www.example.com", "*.www.example.com", false),
+            ("example.com", "*.com", false),
+            ("www.example.com", "w*.example.com", false),
+            ("www.example.com", "*.*.com", false),
+            ("www.example.com", "www.*.com", false),
+            ("www.example.com", "*", false),
+            ("127.0.0.1", "127.0.0.1", false),
+            ("[::1]", "::1", false),
+            ("[v1.x]", "v1.x", false),
+            ("", "", false),
+        ];
+
+        for (host, dns_name, expected) in test_data {
+            assert_eq!(
+                expected,
+                host_matches_dns_name(host, dns_name),
+                "{host} {dns_name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_host_parse() {
+        let test_data = [
+            (
+                "example.com",
+                Host::Domain(Cow::Borrowed("example.com")),
+                "example.com",
+            ),
+            (
+                "EXAMPLE.com.",
+                Host::Domain(Cow::Borrowed("example.com.")),
+                "example.com.",
+            ),
+            (
+                "exa%6Dple.com",
+                Host::Domain(Cow::Borrowed("example.com")),
+                "example.com",
+            ),
+            ("0x7f.1", Host::Ipv4(Ipv4Addr::LOCALHOST), "127.0.0.1"),
+            ("1.2.3.4", Host::Ipv4(Ipv4Addr::new(1, 2, 3, 4)), "1.2.3.4"),
+            (
+                "[::1]",
+                Host::Ipv6 {
+                    addr: Ipv6Addr::LOCALHOST,
+                    zone_id: None,
+                },
+                "[::1]",
+            ),
+            (
+                "[FE80::1%25eth0]",
+                Host::Ipv6 {
+                    addr: Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+                    zone_id: Some("eth0"),
+                },
+                "[fe80::1%25eth0]",
+            ),
+            (
+                "[v1.fe80::a+en1]",
+                Host::IpvFuture("v1.fe80::a+en1"),
+                "[v1.fe80::a+en1]",
+            ),
+            (
+                "[2001:DB8:0:0:0:0:0:1]",
+                Host::Ipv6 {
+                    addr: Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1),
+                    zone_id: None,
+                },
+                "[2001:db8::1]",
+            ),
+        ];
+
+        for (input, expected, serialized) in test_data {
+            let host = Host::parse(input).unwrap();
+            assert_eq!(expected, host, "{input}");
+            assert_eq!(serialized, host.to_string());
+            assert_eq!(classify_host(input), host.kind());
+        }
+
+        assert!(matches!(
+            Host::parse("example.com").unwrap(),
+            Host::Domain(Cow::Borrowed(_))
+        ));
+        assert!(matches!(
+            Host::parse("EXAMPLE.com").unwrap(),
+            Host::Domain(Cow::Owned(_))
+        ));
+
+        assert_eq!(
+            Some("eth 0"),
+            Host::parse("[fe80::1%25eth%200]")
+                .unwrap()
+                .zone_id()
+                .as_deref()
+        );
+        assert_eq!(None, Host::parse("[fe80::1]").unwrap().zone_id());
+        assert_eq!(
+            "[fe80::1%25eth%200]",
+            Host::parse("[fe80::1%25eth%200]").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_host_parse_invalid() {
+        fn variant(err: &HostError) -> &'static str {
+            match err {
+                HostError::InvalidIpv6 { .. } => "InvalidIpv6",
+                HostError::InvalidIpvFuture { .. } => "InvalidIpvFuture",
+                HostError::InvalidZoneId { .. } => "InvalidZoneId",
+                HostError::ForbiddenCodePoint { .. } => "ForbiddenCodePoint",
+                HostError::InvalidIpv4 { .. } => "InvalidIpv4",
+                HostError::InvalidDomain { .. } => "InvalidDomain",
+                HostError::IdnaDisabled { .. } => "IdnaDisabled",
+            }
+        }
+
+        let test_data = [
+            ("", "InvalidDomain", 0),
+            ("a..b", "InvalidDomain", 2),
+            (".a", "InvalidDomain", 0),
+            ("exa mple.com", "ForbiddenCodePoint", 3),
+            ("a.exa%20mple", "ForbiddenCodePoint", 5),
+            ("a%b.com", "ForbiddenCodePoint", 1),
+            ("a<b", "ForbiddenCodePoint", 1),
+            #[cfg(feature = "idna")]
+            ("a.b\u{FF03}c", "InvalidDomain", 2),
+            #[cfg(not(feature = "idna"))]
+            ("a.b\u{FF03}c", "IdnaDisabled", 2),
+            #[cfg(feature = "idna")]
+            ("a.xn--.b", "InvalidDomain", 2),
+            ("1.2.3.4.5", "InvalidIpv4", 8),
+            ("1.2.3.256", "InvalidIpv4", 6),
+            ("256.1.1.1", "InvalidIpv4", 0),
+            ("1.2.09", "InvalidIpv4", 4),
+            ("a.1", "InvalidIpv4", 0),
+            ("[::1", "InvalidIpv6", 4),
+            ("[::g]", "InvalidIpv6", 3),
+            ("[1:2]", "InvalidIpv6", 1),
+            ("[::1]x]", "InvalidIpv6", 4),
+            ("[fe80::1%eth0]", "InvalidZoneId", 8),
+            ("[fe80::1%25]", "InvalidZoneId", 11),
+            ("[fe80::1%25eth/0]", "InvalidZoneId", 14),
+            ("[fe80::1%25%2]", "InvalidZoneId", 11),
+            ("[v1.x", "InvalidIpvFuture", 5),
+            ("[v1.x/]", "InvalidIpvFuture", 5),
+        ];
+
+        for (input, expected, offset) in test_data {
+            let err = Host::parse(input).unwrap_err();
+            assert_eq!(expected, variant(&err), "{input}");
+            assert_eq!(offset, err.offset(), "{input}");
+            assert_eq!(ErrorCode::InvalidHost, err.code(), "{input}");
+        }
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn test_classify_host_idna() {
+        assert_eq!(HostKind::Domain, classify_host("münchen.de"));
+        assert_eq!(HostKind::Ipv4, classify_host("１.２.３.４"));
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn test_host_matches_dns_name_idna() {
+        assert!(host_matches_dns_name(
+            "bücher.example",
+            "xn--bcher-kva.example"
+        ));
+        assert!(host_matches_dns_name(
+            "BÜCHER.example",
+            "XN--BCHER-KVA.example"
+        ));
+        assert!(host_matches_dns_name(
+            "www.bücher.example",
+            "*.xn--bcher-kva.example"
+        ));
+        assert!(!host_matches_dns_name("bücher.example", "bücher.example"));
+    }
+
+    #[cfg(not(feature = "idna"))]
+    #[test]
+    fn test_host_parse_idna_disabled() {
+        assert_eq!(HostKind::Invalid, classify_host("münchen.de"));
+
+        for input in ["münchen.de", "m%C3%BCnchen.de", "XN--mnchen-3ya.de"] {
+            let err = Host::parse(input).unwrap_err();
+            assert!(matches!(err, HostError::IdnaDisabled { .. }), "{input}");
+            assert_eq!(ErrorCode::InvalidHost, err.code());
+        }
+        assert!(matches!(
+            Host::parse("exa mple.com"),
+            Err(HostError::ForbiddenCodePoint { offset: 3, .. })
+        ));
+        assert!(matches!(
+            Host::parse("example.münchen.de"),
+            Err(HostError::IdnaDisabled { offset: 8, .. })
+        ));
+    }
+}
@@ -0,0 +1,11 @@
+//! Types and helpers for HTTP messages.
+
+pub use crate::{
+    accept::AcceptCharset,
+    cache::{CacheStatus, Warning},
+    event_stream::{Event, EventStreamParser},
+    framing::{response_body_length, BodyLength},
+    header::{is_hop_by_hop, sanitize_header, HeaderError, HeaderValue, SanitizeOptions},
+    json_seq::{RecordFormat, RecordSplitter, Records},
+    status::{StatusCode, StatusCodeError},
+};
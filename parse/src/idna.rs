@@ -9,9 +9,7 @@
  * IDNA Compatabilty Processing is defined in tr46 https://www.unicode.org/reports/tr46
  */
 
-#![allow(dead_code)]
-
-use std::{borrow::Cow, str::Utf8Error};
+use std::{borrow::Cow, fmt, str::Utf8Error};
 
 use unic::{
     normal::StrNormalForm,
@@ -21,6 +19,8 @@ use unic_idna_mapping::Mapping;
 use unicode_joining_type::{get_joining_type, JoiningType};
 use unicode_script::{Script, UnicodeScript};
 
+use crate::punycode;
+
 #[derive(Debug)]
 pub(crate) enum IDNAProcessingError {
     Utf8(Utf8Error),
@@ -32,6 +32,31 @@ pub(crate) enum IDNAProcessingError {
     InvalidDomain(String),
 }
 
+impl fmt::Display for IDNAProcessingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8(_) => f.write_str("invalid UTF-8"),
+            Self::InvalidCharacter(c) => write!(f, "disallowed character {c:?}"),
+            Self::InvalidLabel(label) => write!(f, "invalid label `{label}`"),
+            Self::InvalidPunycode(label) => write!(f, "invalid punycode label `{label}`"),
+            Self::InvalidLabelLength(label) => write!(f, "label `{label}` has an invalid length"),
+            Self::InvalidDomainLength(domain) => {
+                write!(f, "domain `{domain}` has an invalid length")
+            }
+            Self::InvalidDomain(domain) => write!(f, "invalid domain `{domain}`"),
+        }
+    }
+}
+
+impl std::error::Error for IDNAProcessingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 impl From<Utf8Error> for IDNAProcessingError {
     fn from(v: Utf8Error) -> Self {
         Self::Utf8(v)
@@ -57,7 +82,7 @@ fn idna_mapping(
     // skip building a new string and return the original one
     if domain_name
         .chars()
-        .all(|c| matches!(c, 'a'..='z') || c.is_ascii_digit() || c == '.' || c == '-')
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-')
     {
         return Ok(domain_name);
     }
@@ -127,7 +152,7 @@ fn label_has_valid_joiners(label: &'_ str) -> bool {
         if let Some(before) = before {
             if CanonicalCombiningClass::of(before) == CanonicalCombiningClass::Virama {
                 return true;
-            };
+            }
         }
 
         let label: Vec<_> = label.chars().collect();
@@ -343,7 +368,7 @@ fn valid_bidi_rtl(label: &'_ str) -> bool {
             | BidiClass::EuropeanTerminator
             | BidiClass::OtherNeutral
             | BidiClass::BoundaryNeutral
-            | BidiClass::NonspacingMark => continue,
+            | BidiClass::NonspacingMark => {}
             BidiClass::ArabicNumber => {
                 arabic_number = true;
                 if european_number {
@@ -396,7 +421,7 @@ fn valid_bidi_ltr(label: &'_ str) -> bool {
             | BidiClass::EuropeanTerminator
             | BidiClass::OtherNeutral
             | BidiClass::BoundaryNeutral
-            | BidiClass::NonspacingMark => continue,
+            | BidiClass::NonspacingMark => {}
             _ => return false,
         }
     }
@@ -469,7 +494,7 @@ fn label_is_valid(
     }
 
     // If CheckHyphens, the label must neither begin nor end with a U+002D HYPHEN-MINUS character.
-    if check_hypnens && (label.starts_with('-') || label.chars().rev().next() == Some('-')) {
+    if check_hypnens && (label.starts_with('-') || label.ends_with('-')) {
         return false;
     }
 
@@ -490,7 +515,7 @@ fn label_is_valid(
     //     For Nontransitional Processing, each value must be either valid or deviation.
     for c in label.chars() {
         match Mapping::of(c) {
-            Mapping::Valid => continue,
+            Mapping::Valid => {}
             Mapping::Deviation(_) => {
                 if transitional_processing {
                     return false;
@@ -568,16 +593,19 @@ fn process_idna(
         }
 
         // If the label starts with “xn--”:
-        //     Attempt to convert the rest of the label to Unicode according to Punycode
+        //     Attempt to convert the rest of the label to Unicode according to Punycode. If that
+        //     conversion fails or if the rest of the label is empty, record that there was an error
         //     Verify that the label meets the validity criteria in Section 4.1, Validity Criteria for Nontransitional Processing.
         // https://www.unicode.org/reports/tr46/#ProcessingStepPunycode
         if label.starts_with("xn--") {
             // Attempt to convert the rest of the label to Unicode according to Punycode
             let label: String = label.chars().skip(4).collect();
-            let label = match punycode::decode(&label) {
-                Ok(label) => label,
-                Err(_) => return Err(IDNAProcessingError::InvalidPunycode(label)),
+            let Ok(label) = punycode::decode(&label) else {
+                return Err(IDNAProcessingError::InvalidPunycode(label));
             };
+            if label.is_empty() {
+                return Err(IDNAProcessingError::InvalidPunycode(label));
+            }
 
             // Verify that the label meets the validity criteria in Section 4.1, Validity Criteria for Nontransitional Processing
             if !label_is_valid(&label, check_hypnens, check_joiners, false) {
@@ -630,7 +658,7 @@ pub(crate) fn idna_unicode_to_ascii(
     use_std3_ascii_rules: bool,
     transitional_processing: bool,
     verify_dns_length: bool,
-) -> Result<Cow<str>, IDNAProcessingError> {
+) -> Result<Cow<'_, str>, IDNAProcessingError> {
     // To the input domain_name, apply the Processing Steps in Section 4, Processing, using the input boolean flags Transitional_Processing, CheckHyphens, CheckBidi, CheckJoiners, and UseSTD3ASCIIRules
     let domain_name = process_idna(
         Cow::Borrowed(domain_name),
@@ -660,8 +688,11 @@ pub(crate) fn idna_unicode_to_ascii(
             if label.is_ascii() {
                 out.push_str(label);
             } else {
+                let Ok(encoded) = punycode::encode(label) else {
+                    return Err(IDNAProcessingError::InvalidPunycode(label.to_owned()));
+                };
                 out.push_str("xn--");
-                out.push_str(&punycode::encode(label).unwrap()); // NOTE: this should never fail, the unicode in label was verified by process_idna
+                out.push_str(&encoded);
             }
         }
         Cow::Owned(out)
@@ -704,16 +735,15 @@ pub(crate) fn idna_unicode_to_ascii(
 
 // IDNA ToUnicode
 // https://www.unicode.org/reports/tr46/#ToUnicode
-#[cfg(test)]
 #[allow(clippy::fn_params_excessive_bools)]
-fn idna_ascii_to_unicode(
+pub(crate) fn idna_ascii_to_unicode(
     domain_name: &'_ str,
     check_hypnens: bool,
     check_bidi: bool,
     check_joiners: bool,
     use_std3_ascii_rules: bool,
     transitional_processing: bool,
-) -> Result<Cow<str>, IDNAProcessingError> {
+) -> Result<Cow<'_, str>, IDNAProcessingError> {
     let domain_name = process_idna(
         Cow::Borrowed(domain_name),
         use_std3_ascii_rules,
@@ -2,31 +2,71 @@ use std::net::Ipv4Addr;
 
 use nom::{
     branch::alt,
-    bytes::complete::{take_while, take_while1},
+    bytes::complete::{take_while, take_while1, take_while_m_n},
     character::complete::char,
-    combinator::{fail, map_res},
+    combinator::{all_consuming, fail, map_res, verify},
     AsChar,
 };
 
 use crate::parse::{many_m_n_, ParseResult};
 
+/// An IPv4 address parsed from a URL host using the permissive
+/// [WHATWG](https://url.spec.whatwg.org/#concept-ipv4-parser) rules.
+///
+/// These rules accept octal (`010`) and hexadecimal (`0x8`) parts, fewer than four parts, and a
+/// trailing dot, so `010.010.010.010`, `0x8080808`, and `134744072` are all `8.8.8.8`. Hosts
+/// written like this are a common way of getting around filters that compare host strings, so
+/// [`Ipv4Host::is_canonical`] reports whether the address was written in plain dotted decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ipv4Host {
+    addr: Ipv4Addr,
+    canonical: bool,
+}
+
+impl Ipv4Host {
+    /// Parse a host as an IPv4 address, returning `None` if it is not one.
+    #[must_use]
+    pub fn parse(input: &str) -> Option<Self> {
+        let (_, addr) = all_consuming(parse)(input).ok()?;
+
+        Some(Self {
+            addr,
+            canonical: all_consuming(parse_ipv4_dotted_decimal)(input).is_ok(),
+        })
+    }
+
+    /// The address.
+    #[must_use]
+    pub fn addr(&self) -> Ipv4Addr {
+        self.addr
+    }
+
+    /// Whether the address was written as four decimal octets without leading zeros or a
+    /// trailing dot, the only notation accepted by
+    /// [RFC3986](https://tools.ietf.org/html/rfc3986#section-3.2.2).
+    #[must_use]
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+}
+
 #[allow(clippy::many_single_char_names)]
 #[allow(clippy::cast_possible_truncation)]
-pub(crate) fn parse(i: &'_ str) -> ParseResult<Ipv4Addr> {
+pub(crate) fn parse(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
     fn parse_ipv4_zero_dots(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
         let (i, section) = parse_ipv4_section(u32::MAX)(i)?;
-        let (i, _) = many_m_n_(0, 1, char('.'))(i)?;
+        let (i, ()) = many_m_n_(0, 1, char('.'))(i)?;
 
-        let [a, b, c, d] = section.to_ne_bytes();
+        let [a, b, c, d] = section.to_be_bytes();
 
         Ok((i, Ipv4Addr::new(a, b, c, d)))
     }
 
-    fn parse_ipv4_one_dot(i: &'_ str) -> ParseResult<Ipv4Addr> {
+    fn parse_ipv4_one_dot(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
         let (i, section_a) = parse_ipv4_section(0xFF)(i)?;
         let (i, _) = char('.')(i)?;
         let (i, section_b) = parse_ipv4_section(0x00FF_FFFF)(i)?;
-        let (i, _) = many_m_n_(0, 1, char('.'))(i)?;
+        let (i, ()) = many_m_n_(0, 1, char('.'))(i)?;
 
         let a = section_a as u8;
         let [_, b, c, d] = section_b.to_be_bytes();
@@ -34,13 +74,13 @@ pub(crate) fn parse(i: &'_ str) -> ParseResult<Ipv4Addr> {
         Ok((i, Ipv4Addr::new(a, b, c, d)))
     }
 
-    fn parse_ipv4_two_dots(i: &'_ str) -> ParseResult<Ipv4Addr> {
+    fn parse_ipv4_two_dots(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
         let (i, section_a) = parse_ipv4_section(0xFF)(i)?;
         let (i, _) = char('.')(i)?;
         let (i, section_b) = parse_ipv4_section(0xFF)(i)?;
         let (i, _) = char('.')(i)?;
         let (i, section_c) = parse_ipv4_section(0xFFFF)(i)?;
-        let (i, _) = many_m_n_(0, 1, char('.'))(i)?;
+        let (i, ()) = many_m_n_(0, 1, char('.'))(i)?;
 
         let a = section_a as u8;
         let b = section_b as u8;
@@ -57,9 +97,36 @@ pub(crate) fn parse(i: &'_ str) -> ParseResult<Ipv4Addr> {
     ))(i)
 }
 
+// The byte offset of the first part that stops a host from being an IPv4 address: a part that
+// is not a number, a part after the fourth, or a part too large for its position
+pub(crate) fn invalid_part_offset(input: &str) -> usize {
+    let input = input.strip_suffix('.').unwrap_or(input);
+    let count = input.split('.').count();
+    let mut offset = 0;
+
+    for (n, part) in input.split('.').enumerate() {
+        if n == 4 {
+            return offset;
+        }
+
+        // The last part fills the remaining bytes of the address
+        let max = match count - n {
+            1 => u32::MAX >> (8 * n),
+            _ => 0xFF,
+        };
+        if all_consuming(parse_ipv4_section(max))(part).is_err() {
+            return offset;
+        }
+
+        offset += part.len() + 1;
+    }
+
+    0
+}
+
 #[allow(clippy::many_single_char_names)]
 #[allow(clippy::cast_possible_truncation)]
-pub(crate) fn parse_ipv4_three_dots(i: &'_ str) -> ParseResult<Ipv4Addr> {
+pub(crate) fn parse_ipv4_three_dots(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
     let (i, section_a) = parse_ipv4_section(0xFF)(i)?;
     let (i, _) = char('.')(i)?;
     let (i, section_b) = parse_ipv4_section(0xFF)(i)?;
@@ -67,7 +134,7 @@ pub(crate) fn parse_ipv4_three_dots(i: &'_ str) -> ParseResult<Ipv4Addr> {
     let (i, section_c) = parse_ipv4_section(0xFF)(i)?;
     let (i, _) = char('.')(i)?;
     let (i, section_d) = parse_ipv4_section(0xFF)(i)?;
-    let (i, _) = many_m_n_(0, 1, char('.'))(i)?;
+    let (i, ()) = many_m_n_(0, 1, char('.'))(i)?;
 
     let a = section_a as u8;
     let b = section_b as u8;
@@ -77,11 +144,39 @@ pub(crate) fn parse_ipv4_three_dots(i: &'_ str) -> ParseResult<Ipv4Addr> {
     Ok((i, Ipv4Addr::new(a, b, c, d)))
 }
 
-fn parse_ipv4_section(max: u32) -> impl FnMut(&'_ str) -> ParseResult<u32>
+// IPv4address = dec-octet "." dec-octet "." dec-octet "." dec-octet
+#[allow(clippy::many_single_char_names)]
+pub(crate) fn parse_ipv4_dotted_decimal(i: &'_ str) -> ParseResult<'_, Ipv4Addr> {
+    let (i, a) = parse_dec_octet(i)?;
+    let (i, _) = char('.')(i)?;
+    let (i, b) = parse_dec_octet(i)?;
+    let (i, _) = char('.')(i)?;
+    let (i, c) = parse_dec_octet(i)?;
+    let (i, _) = char('.')(i)?;
+    let (i, d) = parse_dec_octet(i)?;
+
+    Ok((i, Ipv4Addr::new(a, b, c, d)))
+}
+
+// dec-octet = DIGIT                 ; 0-9
+//           / %x31-39 DIGIT         ; 10-99
+//           / "1" 2DIGIT            ; 100-199
+//           / "2" %x30-34 DIGIT     ; 200-249
+//           / "25" %x30-35          ; 250-255
+fn parse_dec_octet(i: &'_ str) -> ParseResult<'_, u8> {
+    map_res(
+        verify(take_while_m_n(1, 3, AsChar::is_dec_digit), |octet: &str| {
+            octet == "0" || !octet.starts_with('0')
+        }),
+        str::parse,
+    )(i)
+}
+
+fn parse_ipv4_section(max: u32) -> impl FnMut(&'_ str) -> ParseResult<'_, u32>
 where
 {
     move |i: &'_ str| {
-        fn parse_ipv4_hex_section(i: &'_ str) -> ParseResult<u32> {
+        fn parse_ipv4_hex_section(i: &'_ str) -> ParseResult<'_, u32> {
             let (i, _) = char('0')(i)?;
             let (i, _) = alt((char('x'), char('X')))(i)?;
             map_res(take_while(AsChar::is_hex_digit), |section: &'_ str| {
@@ -92,7 +187,7 @@ where
                 u32::from_str_radix(section, 16)
             })(i)
         }
-        fn parse_ipv4_octal_section(i: &'_ str) -> ParseResult<u32> {
+        fn parse_ipv4_octal_section(i: &'_ str) -> ParseResult<'_, u32> {
             let (i, _) = char('0')(i)?;
             map_res(take_while(AsChar::is_oct_digit), |section: &'_ str| {
                 if section.is_empty() {
@@ -103,9 +198,9 @@ where
             })(i)
         }
 
-        fn parse_ipv4_decimal_section(i: &'_ str) -> ParseResult<u32> {
+        fn parse_ipv4_decimal_section(i: &'_ str) -> ParseResult<'_, u32> {
             map_res(take_while1(AsChar::is_dec_digit), |section: &'_ str| {
-                u32::from_str_radix(section, 10)
+                section.parse::<u32>()
             })(i)
         }
 
@@ -134,7 +229,7 @@ mod tests {
         let test_data: Vec<(Ipv4Addr, &'_ str)> = vec![
             (Ipv4Addr::new(1, 1, 1, 1), "1.1.1.1"),
             (Ipv4Addr::new(8, 8, 8, 8), "010.010.010.010"),
-            (Ipv4Addr::new(255, 255, 255, 255), "0xFF.0XFF.255.0377"),
+            (Ipv4Addr::BROADCAST, "0xFF.0XFF.255.0377"),
             (Ipv4Addr::new(1, 0, 1, 0), "1.0.256"),
             (Ipv4Addr::new(1, 2, 3, 4), "1.2.3.4."),
             (Ipv4Addr::new(1, 253, 2, 255), "1.16581375"),
@@ -148,7 +243,7 @@ mod tests {
     #[test]
     fn test_parse_ipv4_invalid() {
         // Require a trailing slash to stop parsers from only consuming part of the input
-        fn test_parser(i: &'_ str) -> ParseResult<(Ipv4Addr, char)> {
+        fn test_parser(i: &'_ str) -> ParseResult<'_, (Ipv4Addr, char)> {
             tuple((parse, char('/')))(i)
         }
 
@@ -159,4 +254,62 @@ mod tests {
             assert!(assert_no_alloc(|| test_parser(input).is_err()));
         }
     }
+
+    #[test]
+    fn test_ipv4_host() {
+        let test_data = [
+            ("8.8.8.8", Some((Ipv4Addr::new(8, 8, 8, 8), true))),
+            ("0.0.0.0", Some((Ipv4Addr::UNSPECIFIED, true))),
+            ("255.255.255.255", Some((Ipv4Addr::BROADCAST, true))),
+            ("010.010.010.010", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("08.8.8.8", None),
+            ("0x8.0x8.0x8.0x8", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("0x08080808", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("134744072", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("2130706433", Some((Ipv4Addr::LOCALHOST, false))),
+            ("0x7F000001", Some((Ipv4Addr::LOCALHOST, false))),
+            ("8.526344", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("8.8.2056", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("8.8.8.8.", Some((Ipv4Addr::new(8, 8, 8, 8), false))),
+            ("0.8.8.8", Some((Ipv4Addr::new(0, 8, 8, 8), true))),
+            ("00.8.8.8", Some((Ipv4Addr::new(0, 8, 8, 8), false))),
+            ("0x.8.8.8", Some((Ipv4Addr::new(0, 8, 8, 8), false))),
+            ("127.1", Some((Ipv4Addr::LOCALHOST, false))),
+            ("0", Some((Ipv4Addr::UNSPECIFIED, false))),
+            ("256.8.8.8", None),
+            ("8.8.8.8.8", None),
+            ("8.8.8.8..", None),
+            ("4294967296", None),
+            ("example.com", None),
+            ("", None),
+        ];
+
+        for (input, expected) in test_data {
+            let actual = Ipv4Host::parse(input).map(|host| (host.addr(), host.is_canonical()));
+            assert_eq!(expected, actual, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_ipv4_dotted_decimal() {
+        let test_data = [
+            ("1.2.3.4", true),
+            ("0.0.0.0", true),
+            ("255.255.255.255", true),
+            ("01.2.3.4", false),
+            ("1.2.3.256", false),
+            ("0x1.2.3.4", false),
+            ("1.2.3", false),
+            ("1.2.3.4.", false),
+            ("1.2.3.1234", false),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(
+                expected,
+                assert_no_alloc(|| all_consuming(parse_ipv4_dotted_decimal)(input).is_ok()),
+                "{input}"
+            );
+        }
+    }
 }
@@ -2,14 +2,15 @@ use std::net::Ipv6Addr;
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while_m_n},
-    character::{complete::char, is_hex_digit},
-    combinator::{map, map_res, success},
-    sequence::tuple,
+    bytes::complete::{tag, take_while1, take_while_m_n},
+    character::complete::{char, satisfy},
+    combinator::{map, map_res, opt, recognize, success},
+    multi::many1_count,
+    sequence::{preceded, tuple},
     AsChar,
 };
 
-use crate::{ipv4::parse_ipv4_three_dots, parse::ParseResult};
+use crate::{ipv4::parse_ipv4_dotted_decimal, parse::ParseResult};
 
 /// Parse an ipv6 address using the syntax defined in
 /// [RFC3986](https://tools.ietf.org/html/rfc3986#section-3.2.2).
@@ -24,7 +25,7 @@ use crate::{ipv4::parse_ipv4_three_dots, parse::ParseResult};
 //                  / [ *4( h16 ":" ) h16 ] "::"              ls32
 //                  / [ *5( h16 ":" ) h16 ] "::"              h16
 //                  / [ *6( h16 ":" ) h16 ] "::"
-pub(crate) fn parse(i: &'_ str) -> ParseResult<Ipv6Addr> {
+pub(crate) fn parse(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     alt((
         parse_ipv6_1,
         parse_ipv6_2,
@@ -39,20 +40,39 @@ pub(crate) fn parse(i: &'_ str) -> ParseResult<Ipv6Addr> {
     ))(i)
 }
 
+/// Parse an ipv6 address followed by an optional zone identifier, using the syntax defined in
+/// [RFC6874](https://www.rfc-editor.org/rfc/rfc6874#section-2).
+///
+/// The zone identifier is returned as written, without the `%25` separating it from the address.
+// IPv6addrz = IPv6address "%25" ZoneID
+// ZoneID = 1*( unreserved / pct-encoded )
+pub(crate) fn parse_with_zone_id(i: &'_ str) -> ParseResult<'_, (Ipv6Addr, Option<&'_ str>)> {
+    let parse_zone_id = recognize(many1_count(alt((
+        take_while1(|c: char| c.is_ascii_alphanumeric() || "-._~".contains(c)),
+        recognize(tuple((
+            char('%'),
+            satisfy(|c| c.is_ascii_hexdigit()),
+            satisfy(|c| c.is_ascii_hexdigit()),
+        ))),
+    ))));
+
+    tuple((parse, opt(preceded(tag("%25"), parse_zone_id))))(i)
+}
+
 // h16 = 1*4HEXDIG
-fn parse_h16(i: &'_ str) -> ParseResult<u16> {
+fn parse_h16(i: &'_ str) -> ParseResult<'_, u16> {
     map_res(take_while_m_n(1, 4, AsChar::is_hex_digit), |h16| {
         u16::from_str_radix(h16, 16)
     })(i)
 }
 
 // ls32 = ( h16 ":" h16 ) / IPv4address
-fn parse_ls32(i: &'_ str) -> ParseResult<(u16, u16)> {
+fn parse_ls32(i: &'_ str) -> ParseResult<'_, (u16, u16)> {
     let parse_double_h16 = map(tuple((parse_h16, char(':'), parse_h16)), |(a, _, b)| (a, b));
 
     alt((
         parse_double_h16,
-        map(parse_ipv4_three_dots, |x| {
+        map(parse_ipv4_dotted_decimal, |x| {
             let x: u32 = x.into();
             let h16_a = (x >> 16) as u16;
             let h16_b = (x & 0x0000_FFFF) as u16;
@@ -62,7 +82,7 @@ fn parse_ls32(i: &'_ str) -> ParseResult<(u16, u16)> {
 }
 
 // h16_colon = h16 ":"
-fn parse_h16_colon(i: &'_ str) -> ParseResult<u16> {
+fn parse_h16_colon(i: &'_ str) -> ParseResult<'_, u16> {
     let (i, h16) = parse_h16(i)?;
     let (i, _) = char(':')(i)?;
 
@@ -70,7 +90,7 @@ fn parse_h16_colon(i: &'_ str) -> ParseResult<u16> {
 }
 
 // 6( h16 ":" ) ls32
-fn parse_ipv6_1(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_1(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, h16_a) = parse_h16_colon(i)?;
     let (i, h16_b) = parse_h16_colon(i)?;
     let (i, h16_c) = parse_h16_colon(i)?;
@@ -86,7 +106,7 @@ fn parse_ipv6_1(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // "::" 5( h16 ":" ) ls32
-fn parse_ipv6_2(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_2(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, _) = tag("::")(i)?;
     let (i, h16_b) = parse_h16_colon(i)?;
     let (i, h16_c) = parse_h16_colon(i)?;
@@ -116,8 +136,10 @@ fn parse_stuff<const N: usize>(mut i: &'_ str) -> (&'_ str, [u16; N]) {
                 if i.starts_with("::") {
                     return (i, out);
                 }
-                if i.starts_with(':') {
-                    i = &i[1..];
+                // Groups are separated by a colon, so a group can not be more than 4 digits
+                match i.strip_prefix(':') {
+                    Some(rest) => i = rest,
+                    None => break,
                 }
             }
             _ => break,
@@ -128,7 +150,7 @@ fn parse_stuff<const N: usize>(mut i: &'_ str) -> (&'_ str, [u16; N]) {
 }
 
 // [ h16 ] "::" 4( h16 ":" ) ls32
-fn parse_ipv6_3(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_3(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, h16_a) = alt((parse_h16, success(0)))(i)?;
     let (i, _) = tag("::")(i)?;
     let (i, h16_c) = parse_h16_colon(i)?;
@@ -144,7 +166,7 @@ fn parse_ipv6_3(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *1( h16 ":" ) h16 ] "::" 3( h16 ":" ) ls32
-fn parse_ipv6_4(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_4(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, [h16_a, h16_b]) = parse_stuff::<2>(i);
     let (i, _) = tag("::")(i)?;
     let (i, h16_d) = parse_h16_colon(i)?;
@@ -159,7 +181,7 @@ fn parse_ipv6_4(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *2( h16 ":" ) h16 ] "::" 2( h16 ":" ) ls32
-fn parse_ipv6_5(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_5(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, [h16_a, h16_b, h16_c]) = parse_stuff::<3>(i);
     let (i, _) = tag("::")(i)?;
     let (i, h16_e) = parse_h16_colon(i)?;
@@ -173,7 +195,7 @@ fn parse_ipv6_5(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *3( h16 ":" ) h16 ] "::" h16 ":" ls32
-fn parse_ipv6_6(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_6(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, [h16_a, h16_b, h16_c, h16_d]) = parse_stuff::<4>(i);
     let (i, _) = tag("::")(i)?;
     let (i, h16_f) = parse_h16_colon(i)?;
@@ -186,7 +208,7 @@ fn parse_ipv6_6(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *4( h16 ":" ) h16 ] "::" ls32
-fn parse_ipv6_7(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_7(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, arr) = parse_stuff::<5>(i);
     let [h16_a, h16_b, h16_c, h16_d, h16_e] = arr;
     let (i, _) = tag("::")(i)?;
@@ -199,7 +221,7 @@ fn parse_ipv6_7(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *5( h16 ":" ) h16 ] "::" h16
-fn parse_ipv6_8(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_8(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, [h16_a, h16_b, h16_c, h16_d, h16_e, h16_f]) = parse_stuff::<6>(i);
     let (i, _) = tag("::")(i)?;
     let (i, h16_h) = parse_h16(i)?;
@@ -211,7 +233,7 @@ fn parse_ipv6_8(i: &'_ str) -> ParseResult<Ipv6Addr> {
 }
 
 // [ *6( h16 ":" ) h 16 ] "::"
-fn parse_ipv6_9(i: &'_ str) -> ParseResult<Ipv6Addr> {
+fn parse_ipv6_9(i: &'_ str) -> ParseResult<'_, Ipv6Addr> {
     let (i, [h16_a, h16_b, h16_c, h16_d, h16_e, h16_f, h16_g]) = parse_stuff::<7>(i);
     let (i, _) = tag("::")(i)?;
 
@@ -278,4 +300,54 @@ mod tests {
             assert_eq!(addr, res);
         }
     }
+
+    #[test]
+    fn test_parse_ipv6_invalid() {
+        // Embedded IPv4 addresses must be dotted decimal
+        let test_data: Vec<&'_ str> = vec![
+            "::FFFF:010.144.52.38",
+            "::FFFF:0x81.144.52.38",
+            "::FFFF:129.144.13350",
+            "::FFFF:129.144.52.38.",
+            "::FFFF:129.144.52.256",
+            // A group is at most 4 digits
+            "12345::",
+            "1:23456::",
+            "1::2:34567",
+        ];
+
+        for input in test_data {
+            assert!(
+                assert_no_alloc(|| nom::combinator::all_consuming(parse)(input).is_err()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_ipv6_zone_id() {
+        let link_local = Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1);
+        let test_data = [
+            ("fe80::1", Some((link_local, None))),
+            ("fe80::1%25eth0", Some((link_local, Some("eth0")))),
+            (
+                "fe80::1%25en1.x_y~z-",
+                Some((link_local, Some("en1.x_y~z-"))),
+            ),
+            ("fe80::1%25%65th0", Some((link_local, Some("%65th0")))),
+            ("fe80::1%eth0", None),
+            ("fe80::1%25", None),
+            ("fe80::1%25eth%2", None),
+            ("fe80::1%25eth/0", None),
+        ];
+
+        for (input, expected) in test_data {
+            let actual = assert_no_alloc(|| {
+                nom::combinator::all_consuming(parse_with_zone_id)(input)
+                    .ok()
+                    .map(|(_, addr)| addr)
+            });
+            assert_eq!(expected, actual, "{input}");
+        }
+    }
 }
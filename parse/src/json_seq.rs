@@ -0,0 +1,196 @@
+use std::ops::Range;
+
+/// The framing of a body containing a sequence of JSON texts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    /// `application/json-seq`, each text is preceded by a U+001E RECORD SEPARATOR.
+    ///
+    /// See [RFC7464](https://www.rfc-editor.org/rfc/rfc7464#section-2)
+    JsonSeq,
+    /// Newline delimited JSON, each text is followed by a U+000A LINE FEED.
+    Ndjson,
+}
+
+/// Splits a streaming body into JSON text records without copying it.
+///
+/// Records are reported as byte ranges from the start of the body, so the caller can slice them
+/// out of whichever buffers it keeps. Trailing whitespace, including the line feed ending each
+/// record, is not part of the range, and records containing only whitespace are skipped.
+#[derive(Debug, Clone, Copy)]
+pub struct RecordSplitter {
+    format: RecordFormat,
+    offset: u64,
+    in_record: bool,
+    start: u64,
+    end: u64,
+}
+
+/// The records completed by one chunk of the body, see [`RecordSplitter::split`].
+///
+/// The splitter always moves past the whole chunk. Dropping the iterator before it is exhausted
+/// skips the remaining records of the chunk, and the ranges of later records are unaffected.
+#[derive(Debug)]
+pub struct Records<'a> {
+    splitter: &'a mut RecordSplitter,
+    chunk: &'a [u8],
+}
+
+impl RecordSplitter {
+    /// A splitter at the start of a body in the given format.
+    #[must_use]
+    pub fn new(format: RecordFormat) -> Self {
+        Self {
+            format,
+            offset: 0,
+            // Anything before the first record separator is not part of a record
+            in_record: format == RecordFormat::Ndjson,
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Scan the next chunk of the body, returning the records that end in it.
+    pub fn split<'a>(&'a mut self, chunk: &'a [u8]) -> Records<'a> {
+        Records {
+            splitter: self,
+            chunk,
+        }
+    }
+
+    /// The final record, at the end of the body.
+    ///
+    /// In a JSON text sequence this record was not followed by a line feed, so it may have been
+    /// truncated.
+    #[must_use]
+    pub fn finish(self) -> Option<Range<u64>> {
+        (self.in_record && self.end > self.start).then_some(self.start..self.end)
+    }
+
+    fn delimiter(&self) -> u8 {
+        match self.format {
+            RecordFormat::JsonSeq => 0x1E,
+            RecordFormat::Ndjson => b'\n',
+        }
+    }
+}
+
+impl Iterator for Records<'_> {
+    type Item = Range<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let splitter = &mut *self.splitter;
+        let delimiter = splitter.delimiter();
+
+        while let Some((&c, rest)) = self.chunk.split_first() {
+            self.chunk = rest;
+            let offset = splitter.offset;
+            splitter.offset += 1;
+
+            if c == delimiter {
+                let record = splitter.start..splitter.end;
+                let in_record = std::mem::replace(&mut splitter.in_record, true);
+                splitter.start = splitter.offset;
+                splitter.end = splitter.offset;
+
+                if in_record && !record.is_empty() {
+                    return Some(record);
+                }
+            } else if !matches!(c, b' ' | b'\t' | b'\r' | b'\n') {
+                splitter.end = offset + 1;
+            }
+        }
+
+        None
+    }
+}
+
+impl Drop for Records<'_> {
+    fn drop(&mut self) {
+        // Scan the rest of the chunk, so the splitter's offset and state stay in step with the
+        // body
+        self.for_each(drop);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_chunks(format: RecordFormat, chunks: &[&[u8]]) -> Vec<String> {
+        let body = chunks.concat();
+        let mut splitter = RecordSplitter::new(format);
+
+        let mut records: Vec<_> = chunks
+            .iter()
+            .flat_map(|chunk| splitter.split(chunk).collect::<Vec<_>>())
+            .collect();
+        records.extend(splitter.finish());
+
+        records
+            .into_iter()
+            .map(|range| {
+                let range =
+                    usize::try_from(range.start).unwrap()..usize::try_from(range.end).unwrap();
+                String::from_utf8_lossy(&body[range]).into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let test_data: Vec<(&'_ [u8], Vec<&'_ str>)> = vec![
+            (b"{\"a\":1}\n{\"b\":2}\n", vec!["{\"a\":1}", "{\"b\":2}"]),
+            (b"{\"a\":1}\r\n{\"b\":2}", vec!["{\"a\":1}", "{\"b\":2}"]),
+            (b"1\n\n \n2 \n", vec!["1", "2"]),
+            (b" \"x y\" \n", vec![" \"x y\""]),
+            (b"", vec![]),
+            (b"\n\n", vec![]),
+        ];
+
+        for (input, expected) in test_data {
+            // Every way of splitting the input in two gives the same records
+            for n in 0..=input.len() {
+                assert_eq!(
+                    expected,
+                    split_chunks(RecordFormat::Ndjson, &[&input[..n], &input[n..]]),
+                    "{input:?} split at {n}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_records_dropped_early() {
+        let body = b"1\n2\n3\n4\n";
+        let mut splitter = RecordSplitter::new(RecordFormat::Ndjson);
+
+        assert_eq!(Some(0..1), splitter.split(&body[..6]).next());
+        assert_eq!(vec![6..7], splitter.split(&body[6..]).collect::<Vec<_>>());
+        assert_eq!(None, splitter.finish());
+    }
+
+    #[test]
+    fn test_json_seq() {
+        let test_data: Vec<(&'_ [u8], Vec<&'_ str>)> = vec![
+            (
+                b"\x1E{\"a\":1}\n\x1E{\"b\":2}\n",
+                vec!["{\"a\":1}", "{\"b\":2}"],
+            ),
+            (b"\x1E1\n\x1E\x1E\x1E2\n", vec!["1", "2"]),
+            (b"\x1E\"multi\nline\"\n", vec!["\"multi\nline\""]),
+            (b"garbage\x1E1\n", vec!["1"]),
+            (b"\x1E{\"trunc", vec!["{\"trunc"]),
+            (b"no separator\n", vec![]),
+        ];
+
+        for (input, expected) in test_data {
+            for n in 0..=input.len() {
+                assert_eq!(
+                    expected,
+                    split_chunks(RecordFormat::JsonSeq, &[&input[..n], &input[n..]]),
+                    "{input:?} split at {n}"
+                );
+            }
+        }
+    }
+}
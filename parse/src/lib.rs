@@ -10,6 +10,18 @@
 #![cfg_attr(docsrs, deny(broken_intra_doc_links))]
 
 //! Parse http requests.
+//!
+//! The public API is split by the kind of input being parsed:
+//!
+//! - [`email`] email address validation
+//! - [`encoding`] base64 and base64url encoding
+//! - [`form_urlencoded`] `application/x-www-form-urlencoded` parsing and serializing
+//! - [`http`] HTTP message semantics, such as how a response body is framed
+//! - [`punycode`] punycode encoding and decoding for internationalized domain names
+//! - [`uri`] scheme-specific URI parsers such as `tel:`, `sip:`, `mailto:`, and `magnet:`, and IRI conversion
+//! - [`url`] URL helpers, including recognition of scp-like git URLs
+//!
+//! [`prelude`] re-exports the most commonly used types.
 
 #[cfg(all(test, debug_assertions))]
 use assert_no_alloc::AllocDisabler;
@@ -18,9 +30,56 @@ use assert_no_alloc::AllocDisabler;
 #[global_allocator]
 static A: AllocDisabler = AllocDisabler;
 
+mod accept;
+mod authority;
+mod cache;
+mod capabilities;
+mod data_url;
+#[cfg(feature = "idna")]
+mod display;
+mod domain;
+pub mod email;
+pub mod encoding;
+mod error;
+mod event_stream;
+mod file_url;
+pub mod form_urlencoded;
+mod framing;
+mod header;
+mod host;
+pub mod http;
+#[cfg(feature = "idna")]
 mod idna;
 mod ipv4;
 mod ipv6;
+mod iri;
+mod json_seq;
+mod literal;
+mod magnet;
+mod mailto;
+mod media_fragment;
+mod pac;
 mod parse;
+mod path;
 mod percent_encode;
-mod url;
+pub mod punycode;
+mod query;
+mod reject;
+mod scp;
+mod sip;
+mod status;
+mod tel;
+pub mod uri;
+pub mod url;
+
+pub use crate::capabilities::{capabilities, Capabilities};
+pub use crate::error::ErrorCode;
+pub use crate::reject::{set_rejection_hook, take_rejection_hook, Rejection, RejectionHook};
+
+pub mod prelude {
+    //! Commonly used types, intended to be glob imported.
+
+    pub use crate::http::StatusCode;
+    pub use crate::uri::{MagnetLink, MailtoUri, SipUri, TelUri};
+    pub use crate::url::{FtpTypeCode, ScpLikeUrl};
+}
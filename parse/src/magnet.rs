@@ -0,0 +1,277 @@
+use std::{borrow::Cow, fmt};
+
+use nom::{
+    bytes::complete::{tag_no_case, take_till},
+    character::complete::char,
+};
+
+use crate::{error::ErrorCode, parse::ParseResult, query::QueryPairs, reject};
+
+/// A parsed `magnet:` link.
+///
+/// See [BEP 9](https://www.bittorrent.org/beps/bep_0009.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagnetLink<'a> {
+    exact_topics: Vec<ExactTopic<'a>>,
+    display_name: Option<Cow<'a, str>>,
+    trackers: Vec<Cow<'a, str>>,
+    exact_length: Option<u64>,
+}
+
+/// An exact topic (`xt`) parameter, a URN identifying the content of a magnet link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactTopic<'a> {
+    namespace: Cow<'a, str>,
+    hash: Cow<'a, str>,
+}
+
+/// An error encountered while parsing a magnet link.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum MagnetError {
+    /// The input does not start with `magnet:?`.
+    InvalidScheme,
+    /// An `xt` parameter is not a URN of the form `urn:namespace:hash`.
+    InvalidExactTopic { offset: usize },
+    /// An `xl` parameter is not a length in bytes.
+    InvalidExactLength { offset: usize },
+}
+
+impl MagnetError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::InvalidExactTopic { .. } | Self::InvalidExactLength { .. } => {
+                ErrorCode::InvalidParameter
+            }
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::InvalidExactTopic { offset, .. } | Self::InvalidExactLength { offset, .. } => {
+                *offset
+            }
+        }
+    }
+}
+
+impl fmt::Display for MagnetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a magnet: URI"),
+            Self::InvalidExactTopic { offset, .. } => {
+                write!(f, "invalid exact topic (xt) at offset {offset}")
+            }
+            Self::InvalidExactLength { offset, .. } => {
+                write!(f, "invalid exact length (xl) at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MagnetError {}
+
+impl<'a> MagnetLink<'a> {
+    /// Parse a magnet link, decoding the `xt`, `dn`, `tr`, and `xl` parameters.
+    ///
+    /// Parameters may be repeated, either under the same key or with a numeric suffix such as
+    /// `xt.1`. Unknown parameters are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `magnet:` URI or if an `xt` or `xl` parameter is
+    /// malformed.
+    pub fn parse(input: &'a str) -> Result<Self, MagnetError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, MagnetError> {
+        let Ok((rest, query)) = parse_magnet_query(input) else {
+            return Err(MagnetError::InvalidScheme);
+        };
+        let query_offset = input.len() - rest.len() - query.len();
+
+        let mut link = Self {
+            exact_topics: Vec::new(),
+            display_name: None,
+            trackers: Vec::new(),
+            exact_length: None,
+        };
+
+        let mut pairs = QueryPairs::new(query);
+        while let Some((name, value)) = pairs.next() {
+            let offset = query_offset + pairs.offset();
+
+            if is_parameter(&name, "xt") {
+                match ExactTopic::parse(value) {
+                    Ok(exact_topic) => link.exact_topics.push(exact_topic),
+                    Err(_) => return Err(MagnetError::InvalidExactTopic { offset }),
+                }
+            } else if is_parameter(&name, "dn") {
+                link.display_name = Some(value);
+            } else if is_parameter(&name, "tr") {
+                link.trackers.push(value);
+            } else if is_parameter(&name, "xl") {
+                let Ok(exact_length) = value.parse() else {
+                    return Err(MagnetError::InvalidExactLength { offset });
+                };
+                link.exact_length = Some(exact_length);
+            }
+        }
+
+        Ok(link)
+    }
+
+    /// The exact topics (`xt`) of the link, in the order they appeared.
+    #[must_use]
+    pub fn exact_topics(&self) -> &[ExactTopic<'a>] {
+        &self.exact_topics
+    }
+
+    /// The display name (`dn`) of the link.
+    #[must_use]
+    pub fn display_name(&self) -> Option<&str> {
+        self.display_name.as_deref()
+    }
+
+    /// The tracker URLs (`tr`) of the link, in the order they appeared.
+    #[must_use]
+    pub fn trackers(&self) -> &[Cow<'a, str>] {
+        &self.trackers
+    }
+
+    /// The exact length (`xl`) of the content in bytes.
+    #[must_use]
+    pub fn exact_length(&self) -> Option<u64> {
+        self.exact_length
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MagnetLink<'a> {
+    type Error = MagnetError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+impl<'a> ExactTopic<'a> {
+    // xt = "urn:" namespace ":" hash
+    // Returns the value back if it is not a valid topic
+    fn parse(value: Cow<'a, str>) -> Result<Self, Cow<'a, str>> {
+        let split = value
+            .get(..4)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("urn:"))
+            .and_then(|_| value.rfind(':'))
+            .filter(|&n| n > 4 && n + 1 < value.len());
+
+        let Some(split) = split else {
+            return Err(value);
+        };
+
+        Ok(match value {
+            Cow::Borrowed(value) => Self {
+                namespace: Cow::Borrowed(&value[4..split]),
+                hash: Cow::Borrowed(&value[split + 1..]),
+            },
+            Cow::Owned(value) => Self {
+                namespace: Cow::Owned(value[4..split].to_owned()),
+                hash: Cow::Owned(value[split + 1..].to_owned()),
+            },
+        })
+    }
+
+    /// The URN namespace of the topic, such as `btih` or `sha1`.
+    #[must_use]
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// The hash identifying the content within the namespace.
+    #[must_use]
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+}
+
+// magnet = "magnet:?" query [ "#" fragment ]
+fn parse_magnet_query(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    let (i, _) = tag_no_case("magnet:")(i)?;
+    let (i, _) = char('?')(i)?;
+
+    take_till(|c| c == '#')(i)
+}
+
+// Parameters can be repeated by appending a numeric suffix to the key, e.g. "xt.1"
+fn is_parameter(name: &'_ str, key: &'_ str) -> bool {
+    match name.strip_prefix(key) {
+        Some("") => true,
+        Some(suffix) => suffix
+            .strip_prefix('.')
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|c| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_magnet() {
+        let link = MagnetLink::parse(
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Some+File%21\
+             &tr=udp%3A%2F%2Ftracker.example.org%3A6969&tr=http://tracker.example.com/announce\
+             &xl=10826029&xt.1=urn:sha1:YNCKHTQCWBTRNJIV4WNAE52SJUQCZO5C",
+        )
+        .unwrap();
+
+        let exact_topics: Vec<_> = link
+            .exact_topics()
+            .iter()
+            .map(|xt| (xt.namespace(), xt.hash()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("btih", "c12fe1c06bba254a9dc9f519b335aa7c1367a88a"),
+                ("sha1", "YNCKHTQCWBTRNJIV4WNAE52SJUQCZO5C"),
+            ],
+            exact_topics
+        );
+        assert_eq!(Some("Some File!"), link.display_name());
+        assert_eq!(
+            vec![
+                "udp://tracker.example.org:6969",
+                "http://tracker.example.com/announce"
+            ],
+            link.trackers()
+        );
+        assert_eq!(Some(10_826_029), link.exact_length());
+    }
+
+    #[test]
+    fn test_parse_magnet_invalid() {
+        let test_data: Vec<&'_ str> = vec![
+            "http://example.com/?xt=urn:btih:abc",
+            "magnet:xt=urn:btih:abc",
+            "magnet:?xt=btih:abc",
+            "magnet:?xt=urn:btih:",
+            "magnet:?xt=urn:btih:abc&xl=ten",
+        ];
+
+        for input in test_data {
+            assert!(MagnetLink::parse(input).is_err());
+        }
+
+        let err = MagnetLink::parse("magnet:?dn=x&xl=ten").unwrap_err();
+        assert_eq!(ErrorCode::InvalidParameter, err.code());
+        assert_eq!(13, err.offset());
+    }
+}
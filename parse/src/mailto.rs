@@ -0,0 +1,328 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    email,
+    error::ErrorCode,
+    percent_encode::{percent_decode_str, DecodePolicy},
+    reject,
+};
+
+/// A parsed `mailto:` URI.
+///
+/// See [RFC6068](https://www.rfc-editor.org/rfc/rfc6068)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailtoUri<'a> {
+    to: Vec<Cow<'a, str>>,
+    headers: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+/// An error encountered while parsing a `mailto:` URI.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum MailtoUriError {
+    /// The input does not start with `mailto:`.
+    InvalidScheme,
+    /// An address is empty, is not percent-encoded UTF-8, or is not a valid email address.
+    InvalidAddress { offset: usize },
+    /// A header field has no `=`, is not percent-encoded UTF-8, or has a line break outside
+    /// `body`.
+    InvalidHeader { offset: usize },
+}
+
+impl MailtoUriError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::InvalidAddress { .. } => ErrorCode::InvalidPath,
+            Self::InvalidHeader { .. } => ErrorCode::InvalidHeader,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::InvalidAddress { offset, .. } | Self::InvalidHeader { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for MailtoUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a mailto: URI"),
+            Self::InvalidAddress { offset, .. } => {
+                write!(f, "invalid address at offset {offset}")
+            }
+            Self::InvalidHeader { offset, .. } => {
+                write!(f, "invalid header field at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MailtoUriError {}
+
+impl<'a> MailtoUri<'a> {
+    /// Parse a `mailto:` URI, percent-decoding the addresses and header fields.
+    ///
+    /// Addresses given in a `to` header field are added to the addresses before the `?`. Unlike
+    /// form-urlencoded queries, `+` is not decoded as a space. Any fragment is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `mailto:` URI, if an address is not a valid email
+    /// address, or if a header field has no `=`, is not percent-encoded UTF-8, or has a line break
+    /// anywhere other than in the value of `body`.
+    pub fn parse(input: &'a str) -> Result<Self, MailtoUriError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    // mailtoURI = "mailto:" [ to ] [ hfields ]
+    fn parse_unreported(input: &'a str) -> Result<Self, MailtoUriError> {
+        if !input
+            .get(..7)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        {
+            return Err(MailtoUriError::InvalidScheme);
+        }
+        let uri = input[7..].split('#').next().unwrap_or_default();
+        let (to, hfields) = uri.split_once('?').unwrap_or((uri, ""));
+
+        let mut mailto = Self {
+            to: Vec::new(),
+            headers: Vec::new(),
+        };
+
+        if !to.is_empty() {
+            push_addresses(&mut mailto.to, to, 7)?;
+        }
+
+        // hfields = "?" hfield *( "&" hfield )
+        // hfield  = hfname "=" hfvalue
+        let mut offset = 7 + to.len() + 1;
+        for hfield in hfields.split('&') {
+            let invalid_header = || MailtoUriError::InvalidHeader { offset };
+
+            if !hfield.is_empty() {
+                let (name, value) = hfield.split_once('=').ok_or_else(invalid_header)?;
+                let value_offset = offset + name.len() + 1;
+                let name =
+                    percent_decode_str(name, DecodePolicy::Strict).map_err(|_| invalid_header())?;
+
+                if name.eq_ignore_ascii_case("to") {
+                    push_addresses(&mut mailto.to, value, value_offset)?;
+                } else {
+                    let value = percent_decode_str(value, DecodePolicy::Strict)
+                        .map_err(|_| invalid_header())?;
+                    // Only the body may span lines, a line break anywhere else would start a new
+                    // header field in the message
+                    let is_line_break = |c| c == '\r' || c == '\n';
+                    if name.contains(is_line_break)
+                        || (!name.eq_ignore_ascii_case("body") && value.contains(is_line_break))
+                    {
+                        return Err(invalid_header());
+                    }
+                    mailto.headers.push((name, value));
+                }
+            }
+
+            offset += hfield.len() + 1;
+        }
+
+        Ok(mailto)
+    }
+
+    /// The addresses, both before the `?` and from `to` header fields, in the order they
+    /// appeared.
+    #[must_use]
+    pub fn to(&self) -> &[Cow<'a, str>] {
+        &self.to
+    }
+
+    /// The `subject` header field.
+    #[must_use]
+    pub fn subject(&self) -> Option<&str> {
+        self.header("subject")
+    }
+
+    /// The `body` header field, the first text/plain body part of the message.
+    #[must_use]
+    pub fn body(&self) -> Option<&str> {
+        self.header("body")
+    }
+
+    /// The value of the first header field with this name, compared ASCII case-insensitively.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| &**value)
+    }
+
+    /// Every header field other than `to`, including `subject` and `body`, in the order they
+    /// appeared.
+    #[must_use]
+    pub fn headers(&self) -> &[(Cow<'a, str>, Cow<'a, str>)] {
+        &self.headers
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MailtoUri<'a> {
+    type Error = MailtoUriError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+// to = addr-spec *("," addr-spec )
+//
+// A "," inside a quoted local-part must be percent-encoded, so splitting before decoding is safe
+fn push_addresses<'a>(
+    to: &mut Vec<Cow<'a, str>>,
+    addresses: &'a str,
+    mut offset: usize,
+) -> Result<(), MailtoUriError> {
+    for address in addresses.split(',') {
+        let invalid_address = || MailtoUriError::InvalidAddress { offset };
+
+        let decoded =
+            percent_decode_str(address, DecodePolicy::Strict).map_err(|_| invalid_address())?;
+        email::validate_unreported(&decoded).map_err(|_| invalid_address())?;
+        to.push(decoded);
+
+        offset += address.len() + 1;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailto_uri() {
+        type Headers<'a> = Vec<(&'a str, &'a str)>;
+
+        let test_data: Vec<(Vec<&'_ str>, Headers, &'_ str)> = vec![
+            // https://www.rfc-editor.org/rfc/rfc6068#section-6.1
+            (
+                vec!["chris@example.com"],
+                vec![],
+                "mailto:chris@example.com",
+            ),
+            (
+                vec!["infobot@example.com"],
+                vec![("subject", "current-issue")],
+                "mailto:infobot@example.com?subject=current-issue",
+            ),
+            (
+                vec!["list@example.org"],
+                vec![("In-Reply-To", "<3469A91.D10AF4C@example.com>")],
+                "mailto:list@example.org?In-Reply-To=%3C3469A91.D10AF4C@example.com%3E",
+            ),
+            (
+                vec!["joe@example.com"],
+                vec![("cc", "bob@example.com"), ("body", "hello")],
+                "mailto:joe@example.com?cc=bob@example.com&body=hello",
+            ),
+            (
+                vec!["gorby%kremvax@example.com"],
+                vec![],
+                "mailto:gorby%25kremvax@example.com",
+            ),
+            (
+                vec!["\"not@me\"@example.org"],
+                vec![],
+                "mailto:%22not%40me%22@example.org",
+            ),
+            (
+                vec!["addr1@an.example", "addr2@an.example"],
+                vec![],
+                "mailto:?to=addr1@an.example,addr2@an.example",
+            ),
+            (
+                vec!["addr1@an.example", "addr2@an.example"],
+                vec![],
+                "mailto:addr1@an.example?TO=addr2@an.example",
+            ),
+            // https://www.rfc-editor.org/rfc/rfc6068#section-6.2
+            (
+                vec!["user@example.org"],
+                vec![("subject", "caf\u{e9}"), ("body", "a+b")],
+                "MAILTO:user@example.org?subject=caf%C3%A9&&body=a+b#fragment",
+            ),
+            (
+                vec!["a@example.com"],
+                vec![("Body", "line 1\r\nline 2")],
+                "mailto:a@example.com?Body=line%201%0D%0Aline%202",
+            ),
+            (vec![], vec![], "mailto:"),
+        ];
+
+        for (to, headers, input) in test_data {
+            let uri = MailtoUri::parse(input).unwrap();
+            assert_eq!(to, uri.to(), "{input}");
+            assert_eq!(
+                headers,
+                uri.headers()
+                    .iter()
+                    .map(|(name, value)| (&**name, &**value))
+                    .collect::<Vec<_>>(),
+                "{input}"
+            );
+        }
+
+        let uri = MailtoUri::parse("mailto:a@example.com?Subject=hi&body=there").unwrap();
+        assert_eq!(Some("hi"), uri.subject());
+        assert_eq!(Some("there"), uri.body());
+        assert_eq!(None, uri.header("cc"));
+        assert!(matches!(uri.to()[0], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_mailto_uri_invalid() {
+        let test_data = [
+            ("http://example.com", ErrorCode::InvalidScheme, 0),
+            ("mailto", ErrorCode::InvalidScheme, 0),
+            ("mailto:nobody", ErrorCode::InvalidPath, 7),
+            (
+                "mailto:a@example.com,,b@example.com",
+                ErrorCode::InvalidPath,
+                21,
+            ),
+            (
+                "mailto:a@example.com,%ZZ@example.com",
+                ErrorCode::InvalidPath,
+                21,
+            ),
+            ("mailto:a@example.com?subject", ErrorCode::InvalidHeader, 21),
+            (
+                "mailto:a@example.com?x=1&body=%FF",
+                ErrorCode::InvalidHeader,
+                25,
+            ),
+            ("mailto:?to=a@example.com,b", ErrorCode::InvalidPath, 25),
+            (
+                "mailto:a@b?subject=x%0D%0ABcc:evil@c",
+                ErrorCode::InvalidHeader,
+                11,
+            ),
+            ("mailto:a@b?x%0A=1", ErrorCode::InvalidHeader, 11),
+            ("mailto:a@b?body=1&cc=%0A", ErrorCode::InvalidHeader, 18),
+        ];
+
+        for (input, code, offset) in test_data {
+            let err = MailtoUri::parse(input).unwrap_err();
+            assert_eq!(code, err.code(), "{input}");
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::percent_encode::percent_decode_utf8_lossy;
+
+/// The temporal and spatial dimensions of a media fragment, such as `t=10,20` or
+/// `xywh=160,120,320,240`.
+///
+/// See [Media Fragments URI 1.0](https://www.w3.org/TR/media-frags/)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MediaFragment {
+    time: Option<TimeRange>,
+    region: Option<SpatialRegion>,
+}
+
+/// A range of time in seconds, from the `t` dimension of a media fragment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeRange {
+    start: f64,
+    end: Option<f64>,
+}
+
+/// A rectangular region, from the `xywh` dimension of a media fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpatialRegion {
+    unit: SpatialUnit,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// The unit of a [`SpatialRegion`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpatialUnit {
+    Pixel,
+    Percent,
+}
+
+impl MediaFragment {
+    /// Parse the fragment of a media URL, without its leading `#`.
+    ///
+    /// Invalid or unsupported dimensions are ignored, as required by the spec, so this never
+    /// fails. When a dimension is repeated the last valid one is used. Only normal play time
+    /// (`npt`) is supported for the `t` dimension.
+    #[must_use]
+    pub fn parse(fragment: &str) -> Self {
+        let mut media_fragment = Self::default();
+
+        // namevalues = namevalue *( "&" namevalue )
+        // namevalue  = name [ "=" value ]
+        for namevalue in fragment.split('&') {
+            let Some((name, value)) = namevalue.split_once('=') else {
+                continue;
+            };
+            let name = percent_decode_utf8_lossy(name);
+            let value = percent_decode_utf8_lossy(value);
+
+            match &*name {
+                "t" => {
+                    if let Some(time) = TimeRange::parse(&value) {
+                        media_fragment.time = Some(time);
+                    }
+                }
+                "xywh" => {
+                    if let Some(region) = SpatialRegion::parse(&value) {
+                        media_fragment.region = Some(region);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        media_fragment
+    }
+
+    /// The temporal dimension (`t`).
+    #[must_use]
+    pub fn time(&self) -> Option<TimeRange> {
+        self.time
+    }
+
+    /// The spatial dimension (`xywh`).
+    #[must_use]
+    pub fn region(&self) -> Option<SpatialRegion> {
+        self.region
+    }
+}
+
+impl TimeRange {
+    // timeprefix = %x74 ; "t"
+    // timeparam  = npttimedef / smptetimedef / clocktimedef
+    // npttimedef = [ deftimeformat ":"] ( npttime [ "," npttime ] ) / ( "," npttime )
+    fn parse(value: &'_ str) -> Option<Self> {
+        let value = value.strip_prefix("npt:").unwrap_or(value);
+        let (start, end) = value.split_once(',').unwrap_or((value, ""));
+
+        if start.is_empty() && end.is_empty() {
+            return None;
+        }
+
+        let start = if start.is_empty() {
+            0.0
+        } else {
+            parse_npt_time(start)?
+        };
+        let end = if end.is_empty() {
+            None
+        } else {
+            Some(parse_npt_time(end)?)
+        };
+
+        // The start time must be strictly less than the end time
+        if end.is_some_and(|end| start >= end) {
+            return None;
+        }
+
+        Some(Self { start, end })
+    }
+
+    /// The start of the range in seconds, `0` if it was omitted.
+    #[must_use]
+    pub fn start(&self) -> f64 {
+        self.start
+    }
+
+    /// The end of the range in seconds, `None` for the end of the media.
+    #[must_use]
+    pub fn end(&self) -> Option<f64> {
+        self.end
+    }
+}
+
+// npttime     = npt-sec / npt-mmss / npt-hhmmss
+// npt-sec     = 1*DIGIT [ "." *DIGIT ]
+// npt-mmss    = npt-mm ":" npt-ss [ "." *DIGIT]
+// npt-hhmmss  = npt-hh ":" npt-mm ":" npt-ss [ "." *DIGIT]
+// npt-hh      = 1*DIGIT
+// npt-mm      = 2DIGIT ; 0-59
+// npt-ss      = 2DIGIT ; 0-59
+fn parse_npt_time(i: &'_ str) -> Option<f64> {
+    fn is_digits(i: &'_ str) -> bool {
+        !i.is_empty() && i.bytes().all(|c| c.is_ascii_digit())
+    }
+
+    fn parse_sixty(i: &'_ str) -> Option<f64> {
+        if i.len() != 2 || !is_digits(i) {
+            return None;
+        }
+        i.parse().ok().filter(|&n: &f64| n < 60.0)
+    }
+
+    let (whole, fraction) = i.split_once('.').unwrap_or((i, ""));
+    if !fraction.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let fraction: f64 = if fraction.is_empty() {
+        0.0
+    } else {
+        i[whole.len()..].parse().ok()?
+    };
+
+    let mut parts = whole.rsplit(':');
+    let seconds = parts.next()?;
+    let (seconds, minutes, hours) = match (parts.next(), parts.next(), parts.next()) {
+        (None, None, None) => {
+            if !is_digits(seconds) {
+                return None;
+            }
+            (seconds.parse::<f64>().ok()?, 0.0, 0.0)
+        }
+        (Some(minutes), None, None) => (parse_sixty(seconds)?, parse_sixty(minutes)?, 0.0),
+        (Some(minutes), Some(hours), None) if is_digits(hours) => (
+            parse_sixty(seconds)?,
+            parse_sixty(minutes)?,
+            hours.parse::<f64>().ok()?,
+        ),
+        _ => return None,
+    };
+
+    Some(hours * 3600.0 + minutes * 60.0 + seconds + fraction)
+}
+
+impl SpatialRegion {
+    // xywhprefix = %x78.79.77.68 ; "xywh"
+    // xywhparam  = [ xywhunit ":" ] 1*DIGIT "," 1*DIGIT "," 1*DIGIT "," 1*DIGIT
+    // xywhunit   = %x70.69.78.65.6C ; "pixel"
+    //            / %x70.65.72.63.65.6E.74 ; "percent"
+    fn parse(value: &'_ str) -> Option<Self> {
+        let (unit, value) = match value.split_once(':') {
+            Some(("pixel", value)) => (SpatialUnit::Pixel, value),
+            Some(("percent", value)) => (SpatialUnit::Percent, value),
+            Some(_) => return None,
+            None => (SpatialUnit::Pixel, value),
+        };
+
+        let mut numbers = value.split(',').map(|n| {
+            if n.is_empty() || !n.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            n.parse::<u32>().ok()
+        });
+
+        let (Some(Some(x)), Some(Some(y)), Some(Some(width)), Some(Some(height)), None) = (
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+            numbers.next(),
+        ) else {
+            return None;
+        };
+
+        // The width and height must be greater than zero, and percentages must stay within the
+        // media
+        if width == 0 || height == 0 {
+            return None;
+        }
+        if unit == SpatialUnit::Percent
+            && (u64::from(x) + u64::from(width) > 100 || u64::from(y) + u64::from(height) > 100)
+        {
+            return None;
+        }
+
+        Some(Self {
+            unit,
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+
+    /// The unit of the coordinates.
+    #[must_use]
+    pub fn unit(&self) -> SpatialUnit {
+        self.unit
+    }
+
+    /// The horizontal offset of the region from the left edge of the media.
+    #[must_use]
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// The vertical offset of the region from the top edge of the media.
+    #[must_use]
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    /// The width of the region.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height of the region.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_media_fragment_time() {
+        let test_data = [
+            ("t=10,20", Some((10.0, Some(20.0)))),
+            ("t=npt:10,20", Some((10.0, Some(20.0)))),
+            ("t=10", Some((10.0, None))),
+            ("t=,20", Some((0.0, Some(20.0)))),
+            ("t=10.5,", Some((10.5, None))),
+            ("t=1:02:03.25", Some((3723.25, None))),
+            ("t=02:03", Some((123.0, None))),
+            ("t=0:00:10,0:01:00", Some((10.0, Some(60.0)))),
+            ("t=10,20&t=30", Some((30.0, None))),
+            ("t=10,20&t=x", Some((10.0, Some(20.0)))),
+            ("t%3D=10", None),
+            ("%74=10", Some((10.0, None))),
+            ("t=20,10", None),
+            ("t=10,10", None),
+            ("t=,", None),
+            ("t=", None),
+            ("t=1:60", None),
+            ("t=2:3", None),
+            ("t=-10", None),
+            ("t=smpte:0:02:00", None),
+            ("t=10s", None),
+            ("", None),
+        ];
+
+        for (input, expected) in test_data {
+            let actual = MediaFragment::parse(input)
+                .time()
+                .map(|time| (time.start(), time.end()));
+            assert_eq!(expected, actual, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_parse_media_fragment_region() {
+        let test_data = [
+            (
+                "xywh=160,120,320,240",
+                Some((SpatialUnit::Pixel, 160, 120, 320, 240)),
+            ),
+            (
+                "xywh=pixel:160,120,320,240",
+                Some((SpatialUnit::Pixel, 160, 120, 320, 240)),
+            ),
+            (
+                "xywh=percent:25,25,50,50",
+                Some((SpatialUnit::Percent, 25, 25, 50, 50)),
+            ),
+            ("t=10&xywh=0,0,1,1", Some((SpatialUnit::Pixel, 0, 0, 1, 1))),
+            ("xywh=percent:60,0,50,50", None),
+            ("xywh=0,0,0,10", None),
+            ("xywh=1,2,3", None),
+            ("xywh=1,2,3,4,5", None),
+            ("xywh=1,2,3,-4", None),
+            ("xywh=em:1,2,3,4", None),
+            ("xywh=", None),
+        ];
+
+        for (input, expected) in test_data {
+            let actual = MediaFragment::parse(input).region().map(|region| {
+                (
+                    region.unit(),
+                    region.x(),
+                    region.y(),
+                    region.width(),
+                    region.height(),
+                )
+            });
+            assert_eq!(expected, actual, "{input}");
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use nom::combinator::all_consuming;
+
+use crate::{
+    host::{parse_host, Host},
+    ipv4::parse_ipv4_dotted_decimal,
+};
+
+// The helpers below mirror the predicates available to proxy auto-config scripts, hosts are
+// expected as they appear in a URL, so names are compared ASCII case-insensitively.
+// https://developer.mozilla.org/en-US/docs/Web/HTTP/Proxy_servers_and_tunneling/Proxy_Auto-Configuration_PAC_file
+
+/// Whether a host name contains no dots, like the PAC `isPlainHostName` function.
+#[must_use]
+pub fn is_plain_host_name(host: &str) -> bool {
+    !host.contains('.')
+}
+
+/// Whether a host is in a domain, like the PAC `dnsDomainIs` function.
+///
+/// The domain is matched as a plain suffix, so it should usually start with a dot:
+/// `www.example.com` is in `.example.com`, but so is `example.com` in `ample.com`.
+#[must_use]
+pub fn dns_domain_is(host: &str, domain: &str) -> bool {
+    host.len() >= domain.len()
+        && host.is_char_boundary(host.len() - domain.len())
+        && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+}
+
+/// Whether a parsed host is a domain in a domain, see [`dns_domain_is`].
+///
+/// IP addresses are never in a domain.
+#[must_use]
+pub fn host_dns_domain_is(host: &Host<'_>, domain: &str) -> bool {
+    matches!(host, Host::Domain(name) if dns_domain_is(name, domain))
+}
+
+/// Whether a host is exactly `hostdom`, or is unqualified and is the first label of `hostdom`,
+/// like the PAC `localHostOrDomainIs` function.
+#[must_use]
+pub fn local_host_or_domain_is(host: &str, hostdom: &str) -> bool {
+    if is_plain_host_name(host) {
+        let first_label = hostdom.split('.').next().unwrap_or(hostdom);
+        return host.eq_ignore_ascii_case(first_label);
+    }
+
+    host.eq_ignore_ascii_case(hostdom)
+}
+
+/// The number of dots in a host name, like the PAC `dnsDomainLevels` function.
+#[must_use]
+pub fn dns_domain_levels(host: &str) -> usize {
+    host.matches('.').count()
+}
+
+/// Whether an IPv4 host is in the network given by `pattern` and `mask`, both in dotted decimal,
+/// like the PAC `isInNet` function.
+///
+/// The host is parsed as for [`classify_host`](crate::url::classify_host), so any IPv4 notation
+/// is accepted. Domains are not resolved, so they are never in a network, and neither is
+/// anything when the pattern or mask is not a dotted decimal address.
+///
+/// To check many hosts against the same network, parse it once with [`Ipv4Net::parse`].
+#[must_use]
+pub fn is_in_net(host: &str, pattern: &str, mask: &str) -> bool {
+    parse_host(host)
+        .is_ok_and(|host| Ipv4Net::parse(pattern, mask).is_some_and(|net| net.contains(&host)))
+}
+
+/// An IPv4 network, the `pattern` and `mask` arguments of the PAC `isInNet` function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ipv4Net {
+    addr: u32,
+    mask: u32,
+}
+
+impl Ipv4Net {
+    /// Parse a network from an address and a mask, both in dotted decimal, returning `None` if
+    /// either is not a dotted decimal address.
+    #[must_use]
+    pub fn parse(pattern: &str, mask: &str) -> Option<Self> {
+        let (Ok((_, pattern)), Ok((_, mask))) = (
+            all_consuming(parse_ipv4_dotted_decimal)(pattern),
+            all_consuming(parse_ipv4_dotted_decimal)(mask),
+        ) else {
+            return None;
+        };
+
+        let mask = u32::from(mask);
+        Some(Self {
+            addr: u32::from(pattern) & mask,
+            mask,
+        })
+    }
+
+    /// Whether a host is an IPv4 address in the network, see [`is_in_net`].
+    #[must_use]
+    pub fn contains(&self, host: &Host<'_>) -> bool {
+        matches!(host, Host::Ipv4(addr) if u32::from(*addr) & self.mask == self.addr)
+    }
+}
+
+/// Whether a string matches a shell expression, like the PAC `shExpMatch` function.
+///
+/// `*` matches any run of characters, including none, and `?` matches exactly one character.
+/// Every other character only matches itself, so the match is case-sensitive.
+#[must_use]
+pub fn sh_exp_match(input: &str, pattern: &str) -> bool {
+    let (mut i, mut p) = (input, pattern);
+    // The pattern following the last `*`, and the input that `*` has not consumed yet
+    let mut backtrack: Option<(&str, &str)> = None;
+
+    loop {
+        let mut pattern_chars = p.chars();
+        match pattern_chars.next() {
+            Some('*') => {
+                p = pattern_chars.as_str();
+                backtrack = Some((p, i));
+                continue;
+            }
+            Some(expected) => {
+                let mut input_chars = i.chars();
+                if let Some(c) = input_chars.next() {
+                    if expected == '?' || expected == c {
+                        p = pattern_chars.as_str();
+                        i = input_chars.as_str();
+                        continue;
+                    }
+                }
+            }
+            None if i.is_empty() => return true,
+            None => {}
+        }
+
+        // On a mismatch, let the last `*` consume one more character and try again
+        let Some((star_pattern, star_input)) = backtrack else {
+            return false;
+        };
+        let mut input_chars = star_input.chars();
+        if input_chars.next().is_none() {
+            return false;
+        }
+        backtrack = Some((star_pattern, input_chars.as_str()));
+        p = star_pattern;
+        i = input_chars.as_str();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_host_predicates() {
+        assert!(is_plain_host_name("www"));
+        assert!(!is_plain_host_name("www.example.com"));
+
+        assert!(dns_domain_is("www.example.com", ".example.com"));
+        assert!(dns_domain_is("WWW.Example.com", ".EXAMPLE.COM"));
+        assert!(!dns_domain_is("www", ".example.com"));
+        assert!(!dns_domain_is("www.example.org", ".example.com"));
+        assert!(!dns_domain_is("bücher.de", "xcher.de"));
+
+        let host = Host::parse("WWW.Example.com").unwrap();
+        assert!(host_dns_domain_is(&host, ".example.com"));
+        assert!(!host_dns_domain_is(&host, ".example.org"));
+        let host = Host::parse("10.0.0.1").unwrap();
+        assert!(!host_dns_domain_is(&host, ".0.0.1"));
+
+        assert!(local_host_or_domain_is(
+            "www.example.com",
+            "www.example.com"
+        ));
+        assert!(local_host_or_domain_is("www", "www.example.com"));
+        assert!(!local_host_or_domain_is(
+            "www.example.org",
+            "www.example.com"
+        ));
+        assert!(!local_host_or_domain_is("home", "www.example.com"));
+
+        assert_eq!(0, dns_domain_levels("www"));
+        assert_eq!(2, dns_domain_levels("www.example.com"));
+    }
+
+    #[test]
+    fn test_is_in_net() {
+        let test_data = [
+            ("198.51.100.7", "198.51.100.0", "255.255.255.0", true),
+            ("198.51.101.7", "198.51.100.0", "255.255.255.0", false),
+            ("10.1.2.3", "10.0.0.0", "255.0.0.0", true),
+            ("0x0a.1.2.3", "10.0.0.0", "255.0.0.0", true),
+            ("167838211", "10.0.0.0", "255.0.0.0", true),
+            ("10.1.2.3", "10.0.0.0", "0.0.0.0", true),
+            ("example.com", "10.0.0.0", "255.0.0.0", false),
+            ("[::1]", "127.0.0.0", "255.0.0.0", false),
+            ("10.1.2.3", "10.0.0", "255.0.0.0", false),
+            ("10.1.2.3", "10.0.0.0", "0xff.0.0.0", false),
+        ];
+
+        for (host, pattern, mask, expected) in test_data {
+            assert_eq!(
+                expected,
+                is_in_net(host, pattern, mask),
+                "{host} {pattern} {mask}"
+            );
+        }
+
+        let net = Ipv4Net::parse("10.0.0.0", "255.0.0.0").unwrap();
+        let hosts = ["10.1.2.3", "0x0a.1.2.3", "11.1.2.3", "example.com"].map(Host::parse);
+        assert_eq!(
+            [true, true, false, false],
+            hosts.map(|host| assert_no_alloc(|| net.contains(&host.unwrap())))
+        );
+        assert_eq!(None, Ipv4Net::parse("10.0.0.0", "0xff.0.0.0"));
+    }
+
+    #[test]
+    fn test_sh_exp_match() {
+        let test_data = [
+            ("http://example.com/index.html", "*/index.html", true),
+            ("http://example.com/a/b", "*.example.com/*", false),
+            ("http://www.example.com/a/b", "*.example.com/*", true),
+            ("example.com", "example.com", true),
+            ("example.com", "Example.com", false),
+            ("example.com", "example.co?", true),
+            ("example.co", "example.co?", false),
+            ("ab", "a*b*", true),
+            ("abcbd", "a*bd", true),
+            ("abcbc", "a*bd", false),
+            ("aaa", "a*a*a", true),
+            ("aa", "a*a*a", false),
+            ("bücher", "b?cher", true),
+            ("", "*", true),
+            ("", "", true),
+            ("", "?", false),
+            ("a", "", false),
+        ];
+
+        for (input, pattern, expected) in test_data {
+            let actual = assert_no_alloc(|| sh_exp_match(input, pattern));
+            assert_eq!(expected, actual, "{input} {pattern}");
+        }
+    }
+}
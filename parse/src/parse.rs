@@ -13,5 +13,15 @@ where
     F: Parser<I, O, E>,
     E: ParseError<I>,
 {
-    fold_many_m_n(min, max, parse, || (), |_, _| ())
+    fold_many_m_n(min, max, parse, || (), |(), _| ())
+}
+
+// The start of the character containing the byte at index, or the length of the input if index
+// is past its end
+pub(crate) fn floor_char_boundary(input: &str, index: usize) -> usize {
+    let mut index = index.min(input.len());
+    while !input.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
 }
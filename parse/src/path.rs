@@ -0,0 +1,304 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    error::ErrorCode,
+    percent_encode::{percent_encode, AsciiSet},
+};
+
+// The special-path percent-encode set, plus "%" and the "/" that would end the segment
+const SEGMENT: AsciiSet = AsciiSet::SPECIAL_PATH.add(b'/').add(b'%');
+
+/// What [`remove_dot_segments`] does with a `..` segment that would climb above the root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AboveRoot {
+    /// Drop the segment, so `/../a` becomes `/a`, as RFC 3986 specifies.
+    #[default]
+    Clamp,
+    /// Fail, for servers mapping paths onto a file system.
+    Reject,
+}
+
+/// An error encountered while removing dot segments from a path.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum PathError {
+    /// A `..` segment would climb above the root, and [`AboveRoot::Reject`] was given.
+    AboveRoot { offset: usize },
+}
+
+impl PathError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidPath
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::AboveRoot { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AboveRoot { offset, .. } => {
+                write!(f, "`..` segment above the root at offset {offset}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Interpret and remove the `.` and `..` segments of a path.
+///
+/// Segments are compared literally, so `%2E` is not treated as a dot, and empty segments are
+/// kept. Paths without dot segments are returned without allocating.
+///
+/// See [RFC3986](https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4)
+///
+/// # Errors
+///
+/// Returns an error if `above_root` is [`AboveRoot::Reject`] and a `..` segment has no segment
+/// before it to remove.
+pub fn remove_dot_segments(path: &str, above_root: AboveRoot) -> Result<Cow<'_, str>, PathError> {
+    if !path
+        .split('/')
+        .any(|segment| segment == "." || segment == "..")
+    {
+        return Ok(Cow::Borrowed(path));
+    }
+
+    // 1.  The input buffer is initialized with the now-appended path components and the output
+    //     buffer is initialized to the empty string.
+    let mut input = path;
+    let mut output = String::with_capacity(path.len());
+
+    let above_root_error = |input: &str| PathError::AboveRoot {
+        offset: path.len() - input.len(),
+    };
+
+    // 2.  While the input buffer is not empty, loop as follows:
+    while !input.is_empty() {
+        // A.  If the input buffer begins with a prefix of "../" or "./", then remove that prefix
+        //     from the input buffer; otherwise,
+        if let Some(rest) = input.strip_prefix("../") {
+            if above_root == AboveRoot::Reject {
+                return Err(above_root_error(input));
+            }
+            input = rest;
+        } else if let Some(rest) = input.strip_prefix("./") {
+            input = rest;
+        }
+        // B.  if the input buffer begins with a prefix of "/./" or "/.", where "." is a complete
+        //     path segment, then replace that prefix with "/" in the input buffer; otherwise,
+        else if input.starts_with("/./") || input == "/." {
+            input = &input[2..];
+            if input.is_empty() {
+                input = "/";
+            }
+        }
+        // C.  if the input buffer begins with a prefix of "/../" or "/..", where ".." is a
+        //     complete path segment, then replace that prefix with "/" in the input buffer and
+        //     remove the last segment and its preceding "/" (if any) from the output buffer;
+        //     otherwise,
+        else if input.starts_with("/../") || input == "/.." {
+            match output.rfind('/') {
+                Some(n) => output.truncate(n),
+                None if !output.is_empty() => output.clear(),
+                None if above_root == AboveRoot::Reject => return Err(above_root_error(input)),
+                None => {}
+            }
+            input = &input[3..];
+            if input.is_empty() {
+                input = "/";
+            }
+        }
+        // D.  if the input buffer consists only of "." or "..", then remove that from the input
+        //     buffer; otherwise,
+        else if input == "." || input == ".." {
+            if input == ".." && above_root == AboveRoot::Reject {
+                return Err(above_root_error(input));
+            }
+            input = "";
+        }
+        // E.  move the first path segment in the input buffer to the end of the output buffer,
+        //     including the initial "/" character (if any) and any subsequent characters up to,
+        //     but not including, the next "/" character or the end of the input buffer.
+        else {
+            let end = input[1..].find('/').map_or(input.len(), |n| n + 1);
+            output.push_str(&input[..end]);
+            input = &input[end..];
+        }
+    }
+
+    // 3.  Finally, the output buffer is returned as the result of remove_dot_segments.
+    Ok(Cow::Owned(output))
+}
+
+/// Append `/`-separated path segments to the path of a URL, such as an API endpoint to a base
+/// URL.
+///
+/// Each segment is percent-encoded, including `%`, so the segments are taken literally, and `.`
+/// and `..` are encoded so they can not climb out of the base path. Exactly one `/` separates the
+/// base path from the segments and empty segments are skipped, but a trailing `/` is kept. Any
+/// query or fragment of the base is dropped.
+///
+/// ```
+/// use parse::url::append_path_segments;
+///
+/// assert_eq!(
+///     "https://api.example.com/v1/users/a%20b",
+///     append_path_segments("https://api.example.com/v1/?key=x", "users/a b"),
+/// );
+/// ```
+#[must_use]
+pub fn append_path_segments(base: &str, segments: &str) -> String {
+    let base = &base[..base.find(['?', '#']).unwrap_or(base.len())];
+
+    let mut out = String::with_capacity(base.len() + segments.len() + 1);
+    out.push_str(base);
+
+    for segment in segments.split('/').filter(|segment| !segment.is_empty()) {
+        if !out.ends_with('/') {
+            out.push('/');
+        }
+
+        match segment {
+            "." => out.push_str("%2E"),
+            ".." => out.push_str("%2E%2E"),
+            segment => out.push_str(&percent_encode(segment, &SEGMENT)),
+        }
+    }
+
+    if segments.ends_with('/') && !out.ends_with('/') {
+        out.push('/');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_remove_dot_segments() {
+        let test_data = [
+            // https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4
+            ("/a/b/c/./../../g", "/a/g"),
+            ("mid/content=5/../6", "mid/6"),
+            ("/a/../b", "/b"),
+            ("/a/./b/", "/a/b/"),
+            ("/a/b/..", "/a/"),
+            ("/a/b/.", "/a/b/"),
+            ("/a/b/../../..", "/"),
+            ("/../a", "/a"),
+            ("../a", "a"),
+            ("./a", "a"),
+            (".", ""),
+            ("..", ""),
+            ("a/..", "/"),
+            ("/a//../b", "/a/b"),
+            ("/a/..b/.c", "/a/..b/.c"),
+            ("/a/%2E%2E/b", "/a/%2E%2E/b"),
+            ("", ""),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(
+                expected,
+                remove_dot_segments(input, AboveRoot::Clamp).unwrap(),
+                "{input}"
+            );
+        }
+
+        assert_no_alloc(|| {
+            assert!(matches!(
+                remove_dot_segments("/a//b/c.d/", AboveRoot::Reject),
+                Ok(Cow::Borrowed(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_append_path_segments() {
+        let test_data = [
+            ("https://example.com", "users", "https://example.com/users"),
+            ("https://example.com/", "users", "https://example.com/users"),
+            (
+                "https://example.com/v1",
+                "/users/",
+                "https://example.com/v1/users/",
+            ),
+            (
+                "https://example.com/v1/",
+                "a//b",
+                "https://example.com/v1/a/b",
+            ),
+            (
+                "https://example.com/v1",
+                "a b/c?d#e",
+                "https://example.com/v1/a%20b/c%3Fd%23e",
+            ),
+            (
+                "https://example.com/v1",
+                "100%\\x",
+                "https://example.com/v1/100%25%5Cx",
+            ),
+            (
+                "https://example.com/v1",
+                "../admin/.",
+                "https://example.com/v1/%2E%2E/admin/%2E",
+            ),
+            (
+                "https://example.com/v1?a=1#top",
+                "users",
+                "https://example.com/v1/users",
+            ),
+            (
+                "https://example.com/v1",
+                "caf\u{e9}",
+                "https://example.com/v1/caf%C3%A9",
+            ),
+            ("https://example.com/v1", "", "https://example.com/v1"),
+            ("", "a", "/a"),
+        ];
+
+        for (base, segments, expected) in test_data {
+            assert_eq!(
+                expected,
+                append_path_segments(base, segments),
+                "{base} {segments}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_remove_dot_segments_above_root() {
+        let test_data = [
+            ("/..", 0),
+            ("/../a", 0),
+            ("/a/../../b", 5),
+            ("/a/b/../../..", 10),
+            ("../a", 0),
+            ("..", 0),
+        ];
+
+        for (input, offset) in test_data {
+            let err = remove_dot_segments(input, AboveRoot::Reject).unwrap_err();
+            assert_eq!(ErrorCode::InvalidPath, err.code());
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+
+        assert_eq!(
+            "/b",
+            remove_dot_segments("/a/../b", AboveRoot::Reject).unwrap()
+        );
+    }
+}
@@ -1,56 +1,275 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, fmt};
 
-// A C0 control is a code point in the range U+0000 NULL to U+001F INFORMATION SEPARATOR ONE, inclusive.
-fn is_c0_control(c: char) -> bool {
-    matches!(c, '\u{00}'..='\u{1F}')
+use crate::error::ErrorCode;
+
+// Encode sets are stored as a bitmask over the ASCII code points, bit n is set if the code point
+// n is in the set. Every code point greater than U+007E (~) is in every set.
+//
+// Each set is defined by the spec as a smaller set plus some additional code points, and is
+// built from the table below in the same way.
+// https://url.spec.whatwg.org/#percent-encoded-bytes
+const fn ascii_set(base: u128, additional: &[u8]) -> u128 {
+    let mut set = base;
+    let mut i = 0;
+    while i < additional.len() {
+        set |= 1 << additional[i];
+        i += 1;
+    }
+    set
 }
 
+// A C0 control is a code point in the range U+0000 NULL to U+001F INFORMATION SEPARATOR ONE, inclusive.
 // The C0 control percent-encode set are the C0 controls and all code points greater than U+007E (~).
-fn is_c0_control_percent_encode(c: char) -> bool {
-    is_c0_control(c) || c > '\u{7E}'
-}
+const C0_CONTROL: u128 = ((1 << 0x20) - 1) | (1 << 0x7F);
 
 // The fragment percent-encode set is the C0 control percent-encode set and U+0020 SPACE, U+0022 ("), U+003C (<), U+003E (>), and U+0060 (`).
-pub(crate) fn is_fragment_percent_encode(c: char) -> bool {
-    is_c0_control_percent_encode(c) || c == ' ' || c == '"' || c == '<' || c == '>' || c == '`'
-}
+const FRAGMENT: u128 = ascii_set(C0_CONTROL, b" \"<>`");
 
 // The query percent-encode set is the C0 control percent-encode set and U+0020 SPACE, U+0022 ("), U+0023 (#), U+003C (<), and U+003E (>).
-pub(crate) fn is_query_percent_encode(c: char) -> bool {
-    is_c0_control_percent_encode(c)
-        || c == ' '
-        || c == '"'
-        || c == '#'
-        || c == '<'
-        || c == '<'
-        || c == '>'
-}
+const QUERY: u128 = ascii_set(C0_CONTROL, b" \"#<>");
 
 // The special-query percent-encode set is the query percent-encode set and U+0027 (').
-pub(crate) fn is_special_query_percent_encode(c: char) -> bool {
-    is_query_percent_encode(c) || c == '\''
+const SPECIAL_QUERY: u128 = ascii_set(QUERY, b"'");
+
+// The path percent-encode set is the query percent-encode set and U+003F (?), U+005E (^), U+0060 (`), U+007B ({), and U+007D (}).
+const PATH: u128 = ascii_set(QUERY, b"?^`{}");
+
+// Special URLs also treat U+005C (\) as a path separator, so a segment of one written from
+// outside the URL must encode it as well
+const SPECIAL_PATH: u128 = ascii_set(PATH, b"\\");
+
+// The userinfo percent-encode set is the path percent-encode set and U+002F (/), U+003A (:), U+003B (;), U+003D (=), U+0040 (@), U+005B ([) to U+005E (^), inclusive, and U+007C (|).
+const USERINFO: u128 = ascii_set(PATH, b"/:;=@[\\]^|");
+
+// The component percent-encode set is the userinfo percent-encode set and U+0024 ($) to U+0026 (&), inclusive, U+002B (+), and U+002C (,).
+const COMPONENT: u128 = ascii_set(USERINFO, b"$%&+,");
+
+// The application/x-www-form-urlencoded percent-encode set is the component percent-encode set and U+0021 (!), U+0027 (') to U+0029 RIGHT PARENTHESIS, inclusive, and U+007E (~).
+const FORM_URLENCODED: u128 = ascii_set(COMPONENT, b"!'()~");
+
+fn in_set(set: u128, c: char) -> bool {
+    !c.is_ascii() || set & (1 << u32::from(c)) != 0
 }
 
-// The path percent-encode set is the query percent-encode set and U+003F (?), U+0060 (`), U+007B ({), and U+007D (}).
-pub(crate) fn is_path_percent_encode(c: char) -> bool {
-    is_query_percent_encode(c) || c == '?' || c == '`' || c == '{' || c == '}'
+/// A set of ASCII code points to percent-encode, see [`percent_encode`].
+///
+/// Code points outside ASCII are always percent-encoded. Custom sets are built from the sets
+/// defined by the URL standard, or from [`EMPTY`](Self::EMPTY), in a const context:
+///
+/// ```
+/// use parse::url::AsciiSet;
+///
+/// const SEGMENT: AsciiSet = AsciiSet::PATH.add(b'/').add(b'%');
+/// ```
+///
+/// See [WHATWG URL](https://url.spec.whatwg.org/#percent-encoded-bytes)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AsciiSet {
+    mask: u128,
 }
 
-// The userinfo percent-encode set is the path percent-encode set and U+002F (/), U+003A (:), U+003B (;), U+003D (=), U+0040 (@), U+005B ([) to U+005E (^), inclusive, and U+007C (|).
-pub(crate) fn is_userinfo_percent_encode(c: char) -> bool {
-    is_path_percent_encode(c)
-        || c == '/'
-        || c == ':'
-        || c == ';'
-        || c == '='
-        || c == '@'
-        || matches!(c, '['..='^')
-        || c == '|'
+impl AsciiSet {
+    /// No ASCII code points, so only code points outside ASCII are encoded.
+    pub const EMPTY: Self = Self { mask: 0 };
+    /// The C0 controls and U+007F DELETE.
+    pub const C0_CONTROL: Self = Self { mask: C0_CONTROL };
+    /// The set used for fragments.
+    pub const FRAGMENT: Self = Self { mask: FRAGMENT };
+    /// The set used for queries of URLs with a non-special scheme.
+    pub const QUERY: Self = Self { mask: QUERY };
+    /// The set used for queries of URLs with a special scheme such as `http`.
+    pub const SPECIAL_QUERY: Self = Self {
+        mask: SPECIAL_QUERY,
+    };
+    /// The set used for path segments.
+    pub const PATH: Self = Self { mask: PATH };
+    /// The set for path segments of URLs with a special scheme such as `http`, which also treat
+    /// `\` as a separator.
+    pub const SPECIAL_PATH: Self = Self { mask: SPECIAL_PATH };
+    /// The set used for the opaque hosts of URLs with a non-special scheme.
+    ///
+    /// The hosts of special URLs are never percent-encoded, code points that are not allowed in
+    /// them make the host invalid instead.
+    pub const OPAQUE_HOST: Self = Self { mask: C0_CONTROL };
+    /// The set used for usernames and passwords.
+    pub const USERINFO: Self = Self { mask: USERINFO };
+    /// The set used by `encodeURIComponent`, which leaves the component unambiguous wherever
+    /// it appears in a URL.
+    pub const COMPONENT: Self = Self { mask: COMPONENT };
+    /// The set used for names and values in `application/x-www-form-urlencoded`.
+    pub const FORM_URLENCODED: Self = Self {
+        mask: FORM_URLENCODED,
+    };
+
+    /// This set with an ASCII byte added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte is not ASCII.
+    #[must_use]
+    pub const fn add(self, byte: u8) -> Self {
+        assert!(
+            byte.is_ascii(),
+            "only ASCII bytes can be added to an AsciiSet"
+        );
+        Self {
+            mask: self.mask | 1 << byte,
+        }
+    }
+
+    /// This set with an ASCII byte removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte is not ASCII.
+    #[must_use]
+    pub const fn remove(self, byte: u8) -> Self {
+        assert!(
+            byte.is_ascii(),
+            "only ASCII bytes can be removed from an AsciiSet"
+        );
+        Self {
+            mask: self.mask & !(1 << byte),
+        }
+    }
+
+    /// The code points in either set.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            mask: self.mask | other.mask,
+        }
+    }
+
+    /// Whether a code point is percent-encoded by this set.
+    #[must_use]
+    pub fn contains(&self, c: char) -> bool {
+        in_set(self.mask, c)
+    }
 }
 
-// The component percent-encode set is the userinfo percent-encode set and U+0024 ($) to U+0026 (&), inclusive, U+002B (+), and U+002C (,).
-pub(crate) fn is_component_percent_encode(c: char) -> bool {
-    is_userinfo_percent_encode(c) || matches!(c, '$'..='&') || c == '+' || c == ','
+pub(crate) fn is_form_urlencoded_percent_encode(c: char) -> bool {
+    in_set(FORM_URLENCODED, c)
+}
+
+/// A URL component, used to select the characters allowed in it.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// A username or password.
+    Userinfo,
+    /// A path, including its `/` separators.
+    Path,
+    /// The query of a URL with a non-special scheme.
+    Query,
+    /// The query of a URL with a special scheme such as `http`, which also encodes `'`.
+    SpecialQuery,
+    /// The fragment, without the leading `#`.
+    Fragment,
+    /// A name or value in an `application/x-www-form-urlencoded` query.
+    FormUrlencoded,
+}
+
+impl Component {
+    fn encode_set(self) -> u128 {
+        match self {
+            Self::Userinfo => USERINFO,
+            Self::Path => PATH,
+            Self::Query => QUERY,
+            Self::SpecialQuery => SPECIAL_QUERY,
+            Self::Fragment => FRAGMENT,
+            Self::FormUrlencoded => FORM_URLENCODED,
+        }
+    }
+}
+
+/// A byte that is not allowed in an already encoded component, see [`validate_component`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidByte {
+    component: Component,
+    offset: usize,
+    byte: u8,
+}
+
+impl InvalidByte {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self.component {
+            Component::Userinfo => ErrorCode::InvalidUserinfo,
+            Component::Path => ErrorCode::InvalidPath,
+            Component::Query | Component::SpecialQuery | Component::FormUrlencoded => {
+                ErrorCode::InvalidQuery
+            }
+            Component::Fragment => ErrorCode::InvalidFragment,
+        }
+    }
+
+    /// The byte offset of the invalid byte in the input.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The invalid byte, for non-ASCII characters this is the first byte of their UTF-8
+    /// encoding.
+    #[must_use]
+    pub fn byte(&self) -> u8 {
+        self.byte
+    }
+}
+
+impl fmt::Display for InvalidByte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let component = match self.component {
+            Component::Userinfo => "userinfo",
+            Component::Path => "path",
+            Component::Query | Component::SpecialQuery => "query",
+            Component::Fragment => "fragment",
+            Component::FormUrlencoded => "form-urlencoded value",
+        };
+        write!(
+            f,
+            "invalid byte 0x{:02X} in {component} at offset {}",
+            self.byte, self.offset
+        )
+    }
+}
+
+impl std::error::Error for InvalidByte {}
+
+/// Check that an already percent-encoded string can be used as the given component without
+/// being encoded again.
+///
+/// Every character must be outside the component's percent-encode set, and every `%` must start
+/// a percent-encoded byte. Builders can use this to reject input that claims to be encoded
+/// instead of silently encoding it a second time.
+///
+/// # Errors
+///
+/// Returns the first byte that would have to be encoded.
+pub fn validate_component(input: &str, component: Component) -> Result<(), InvalidByte> {
+    let set = component.encode_set();
+    let bytes = input.as_bytes();
+
+    for (offset, c) in input.char_indices() {
+        let valid = if c == '%' {
+            bytes.get(offset + 1).is_some_and(u8::is_ascii_hexdigit)
+                && bytes.get(offset + 2).is_some_and(u8::is_ascii_hexdigit)
+        } else {
+            !in_set(set, c)
+        };
+
+        if !valid {
+            return Err(InvalidByte {
+                component,
+                offset,
+                byte: bytes[offset],
+            });
+        }
+    }
+
+    Ok(())
 }
 
 fn u8_to_hex(c: u8) -> char {
@@ -93,7 +312,7 @@ pub(crate) fn percent_encode_char(
         return out;
     }
 
-    if space_as_plus {
+    if space_as_plus && c == ' ' {
         out.push('+');
         return out;
     }
@@ -110,7 +329,16 @@ pub(crate) fn percent_encode_char(
     out
 }
 
-pub(crate) fn percent_encode(
+/// Percent-encode every code point of the input that is in the set.
+///
+/// Input that needs no encoding is returned without allocating. Code points are encoded as the
+/// bytes of their UTF-8 encoding, with uppercase hexadecimal digits.
+#[must_use]
+pub fn percent_encode<'a>(input: &'a str, set: &AsciiSet) -> Cow<'a, str> {
+    percent_encode_with(Cow::Borrowed(input), false, |c| set.contains(c))
+}
+
+pub(crate) fn percent_encode_with(
     input: Cow<str>,
     space_as_plus: bool,
     percent_encode_set: impl Fn(char) -> bool,
@@ -129,6 +357,227 @@ pub(crate) fn percent_encode(
     Cow::Owned(out)
 }
 
+// Like percent_encode_with, for input that may not be UTF-8. Bytes outside ASCII are always
+// encoded.
+pub(crate) fn percent_encode_bytes_with(
+    input: &[u8],
+    space_as_plus: bool,
+    percent_encode_set: impl Fn(char) -> bool,
+) -> String {
+    let mut out = String::with_capacity(input.len());
+
+    for &byte in input {
+        if byte.is_ascii() {
+            out = percent_encode_char(char::from(byte), out, space_as_plus, &percent_encode_set);
+        } else {
+            out.push('%');
+            let (char_high, char_low) = u8_to_hex_pair(byte);
+            out.push(char_high);
+            out.push(char_low);
+        }
+    }
+
+    out
+}
+
+fn hex_to_u8(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        _ => None,
+    }
+}
+
+/// What [`percent_decode`] does with a `%` that is not followed by two hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodePolicy {
+    /// Keep the `%` as it is, as the URL parser does.
+    #[default]
+    PassThrough,
+    /// Replace the `%` with U+FFFD REPLACEMENT CHARACTER, and invalid UTF-8 when decoding a
+    /// string.
+    Lossy,
+    /// Fail, and fail on invalid UTF-8 when decoding a string.
+    Strict,
+}
+
+/// An error encountered while percent-decoding with [`DecodePolicy::Strict`].
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum PercentDecodeError {
+    /// A `%` is not followed by two hexadecimal digits.
+    InvalidPercentEncoding { offset: usize },
+    /// The decoded bytes are not valid UTF-8, which is only checked when decoding a string.
+    InvalidUtf8 { offset: usize },
+}
+
+impl PercentDecodeError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidSyntax
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidPercentEncoding { offset, .. } | Self::InvalidUtf8 { offset, .. } => {
+                *offset
+            }
+        }
+    }
+}
+
+impl fmt::Display for PercentDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPercentEncoding { offset, .. } => {
+                write!(f, "invalid percent-encoding at offset {offset}")
+            }
+            Self::InvalidUtf8 { offset, .. } => write!(f, "invalid UTF-8 at offset {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for PercentDecodeError {}
+
+/// Percent-decode a byte sequence.
+///
+/// Input without a `%` is returned without allocating.
+///
+/// See [WHATWG URL](https://url.spec.whatwg.org/#percent-decode)
+///
+/// # Errors
+///
+/// Returns an error if `policy` is [`DecodePolicy::Strict`] and a `%` is not followed by two
+/// hexadecimal digits.
+pub fn percent_decode(
+    input: &[u8],
+    policy: DecodePolicy,
+) -> Result<Cow<'_, [u8]>, PercentDecodeError> {
+    decode(input, policy).map_err(|offset| PercentDecodeError::InvalidPercentEncoding { offset })
+}
+
+/// Percent-decode a string, then decode the bytes as UTF-8.
+///
+/// Input without a `%` is returned without allocating. Unless `policy` is
+/// [`DecodePolicy::Strict`], invalid UTF-8 is replaced by U+FFFD REPLACEMENT CHARACTER.
+///
+/// # Errors
+///
+/// Returns an error if `policy` is [`DecodePolicy::Strict`] and a `%` is not followed by two
+/// hexadecimal digits, or the decoded bytes are not valid UTF-8.
+pub fn percent_decode_str(
+    input: &str,
+    policy: DecodePolicy,
+) -> Result<Cow<'_, str>, PercentDecodeError> {
+    let bytes = match decode(input.as_bytes(), policy) {
+        Ok(Cow::Borrowed(_)) => return Ok(Cow::Borrowed(input)),
+        Ok(Cow::Owned(bytes)) => bytes,
+        Err(offset) => return Err(PercentDecodeError::InvalidPercentEncoding { offset }),
+    };
+
+    match String::from_utf8(bytes) {
+        Ok(decoded) => Ok(Cow::Owned(decoded)),
+        Err(err) if policy == DecodePolicy::Strict => Err(PercentDecodeError::InvalidUtf8 {
+            offset: input_offset(input.as_bytes(), err.utf8_error().valid_up_to()),
+        }),
+        Err(err) => Ok(Cow::Owned(
+            String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        )),
+    }
+}
+
+// To percent-decode a byte sequence input, run these steps:
+//     Let output be an empty byte sequence.
+//     For each byte byte in input:
+//         If byte is not 0x25 (%), then append byte to output.
+//         Otherwise, if byte is 0x25 (%) and the next two bytes after byte in input are not in the
+//         ranges 0x30 (0) to 0x39 (9), 0x41 (A) to 0x46 (F), and 0x61 (a) to 0x66 (f), all
+//         inclusive, append byte to output.
+//         Otherwise:
+//             Let bytePoint be the two bytes after byte in input, decoded, and then interpreted as
+//             hexadecimal number.
+//             Append a byte whose value is bytePoint to output.
+//             Skip the next two bytes in input.
+//     Return output.
+// https://url.spec.whatwg.org/#percent-decode
+//
+// Returns the offset of the first malformed "%" if the policy is strict
+fn decode(input: &[u8], policy: DecodePolicy) -> Result<Cow<'_, [u8]>, usize> {
+    // Nothing to decode
+    if !input.contains(&b'%') {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < input.len() {
+        if input[i] == b'%' {
+            if let Some(byte) = decode_percent_encoded_byte(&input[i..]) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+
+            match policy {
+                DecodePolicy::PassThrough => {}
+                DecodePolicy::Lossy => {
+                    out.extend_from_slice("\u{FFFD}".as_bytes());
+                    i += 1;
+                    continue;
+                }
+                DecodePolicy::Strict => return Err(i),
+            }
+        }
+
+        out.push(input[i]);
+        i += 1;
+    }
+
+    Ok(Cow::Owned(out))
+}
+
+// The byte encoded by the "%" and two hexadecimal digits at the start of the input
+pub(crate) fn decode_percent_encoded_byte(input: &[u8]) -> Option<u8> {
+    match *input {
+        [b'%', high, low, ..] => Some(hex_to_u8(high)? << 4 | hex_to_u8(low)?),
+        _ => None,
+    }
+}
+
+// Map an offset in the output of a strict decode back to the input
+fn input_offset(input: &[u8], decoded_offset: usize) -> usize {
+    let mut i = 0;
+    for _ in 0..decoded_offset {
+        i += if input[i] == b'%' { 3 } else { 1 };
+    }
+    i
+}
+
+// Percent decode the input, leaving any "%" that does not start a percent-encoded byte as it is
+pub(crate) fn percent_decode_lenient(input: &[u8]) -> Cow<'_, [u8]> {
+    match decode(input, DecodePolicy::PassThrough) {
+        Ok(out) => out,
+        Err(_) => unreachable!("passing through malformed percent-encodings never fails"),
+    }
+}
+
+// Percent decode the input then run UTF-8 decode without BOM or fail, replacing invalid sequences
+// with U+FFFD
+pub(crate) fn percent_decode_utf8_lossy(input: &'_ str) -> Cow<'_, str> {
+    match percent_decode_lenient(input.as_bytes()) {
+        Cow::Borrowed(_) => Cow::Borrowed(input),
+        Cow::Owned(bytes) => Cow::Owned(
+            String::from_utf8(bytes)
+                .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,40 +587,253 @@ mod tests {
     fn test_percent_encode() {
         assert_eq!(
             "%23",
-            percent_encode(Cow::Borrowed("\u{23}"), false, |_| true)
+            percent_encode_with(Cow::Borrowed("\u{23}"), false, |_| true)
         );
 
         assert_eq!(
             "%7F",
-            percent_encode(Cow::Borrowed("\u{7F}"), false, |_| true)
+            percent_encode_with(Cow::Borrowed("\u{7F}"), false, |_| true)
         );
         assert_eq!(
             "%E2%89%A1",
-            percent_encode(Cow::Borrowed("≡"), false, is_userinfo_percent_encode)
+            percent_encode_with(Cow::Borrowed("≡"), false, |c| AsciiSet::USERINFO
+                .contains(c))
         );
         assert_eq!(
             "%E2%80%BD",
-            percent_encode(Cow::Borrowed("‽"), false, is_userinfo_percent_encode)
+            percent_encode_with(Cow::Borrowed("‽"), false, |c| AsciiSet::USERINFO
+                .contains(c))
         );
         assert_eq!(
             "Say%20what%E2%80%BD",
-            percent_encode(
-                Cow::Borrowed("Say what‽"),
-                false,
-                is_userinfo_percent_encode
+            percent_encode_with(Cow::Borrowed("Say what‽"), false, |c| AsciiSet::USERINFO
+                .contains(c))
+        );
+        assert_eq!(
+            "Say+what%E2%80%BD%3F",
+            percent_encode_with(
+                Cow::Borrowed("Say what‽?"),
+                true,
+                is_form_urlencoded_percent_encode
             )
         );
     }
 
+    #[test]
+    fn test_percent_encode_sets() {
+        // The printable ASCII code points in each set, derived from the spec text. C0 controls,
+        // U+007F DELETE, and non-ASCII code points are in every set.
+        let test_data = [
+            (AsciiSet::C0_CONTROL, ""),
+            (AsciiSet::FRAGMENT, " \"<>`"),
+            (AsciiSet::QUERY, " \"#<>"),
+            (AsciiSet::SPECIAL_QUERY, " \"#'<>"),
+            (AsciiSet::PATH, " \"#<>?^`{}"),
+            (AsciiSet::SPECIAL_PATH, " \"#<>?\\^`{}"),
+            (AsciiSet::OPAQUE_HOST, ""),
+            (AsciiSet::USERINFO, " \"#/:;<=>?@[\\]^`{|}"),
+            (AsciiSet::COMPONENT, " \"#$%&+,/:;<=>?@[\\]^`{|}"),
+            (AsciiSet::FORM_URLENCODED, " !\"#$%&'()+,/:;<=>?@[\\]^`{|}~"),
+        ];
+
+        for (set, expected) in test_data {
+            let printable: String = (' '..='~').filter(|&c| set.contains(c)).collect();
+            assert_eq!(expected, printable);
+
+            assert!(('\u{0}'..='\u{1F}').all(|c| set.contains(c)));
+            assert!(set.contains('\u{7F}'));
+            assert!(set.contains('\u{80}'));
+            assert!(set.contains('‽'));
+            assert!(set.contains(char::MAX));
+        }
+
+        assert!(!AsciiSet::C0_CONTROL.contains(' '));
+    }
+
+    #[test]
+    fn test_validate_component() {
+        let test_data = [
+            ("/a/b%20c", Component::Path, None),
+            ("/a/b c", Component::Path, Some((4, b' '))),
+            ("/a?b", Component::Path, Some((2, b'?'))),
+            ("/a^b", Component::Path, Some((2, b'^'))),
+            ("/%E2%80%BD", Component::Path, None),
+            ("/‽", Component::Path, Some((1, 0xE2))),
+            ("/100%", Component::Path, Some((4, b'%'))),
+            ("/%2", Component::Path, Some((1, b'%'))),
+            ("/%zz", Component::Path, Some((1, b'%'))),
+            ("a=1&b=it's", Component::Query, None),
+            ("a=1&b=it's", Component::SpecialQuery, Some((8, b'\''))),
+            ("a=1#b", Component::Query, Some((3, b'#'))),
+            ("a#b", Component::Fragment, None),
+            ("a\"b", Component::Fragment, Some((1, b'"'))),
+            ("frag/?ment", Component::Fragment, None),
+            ("user:pass", Component::Userinfo, Some((4, b':'))),
+            ("us%40er", Component::Userinfo, None),
+            ("a*b-c._", Component::FormUrlencoded, None),
+            ("a~b", Component::FormUrlencoded, Some((1, b'~'))),
+            ("", Component::Path, None),
+        ];
+
+        for (input, component, expected) in test_data {
+            let actual =
+                validate_component(input, component).map_err(|err| (err.offset(), err.byte()));
+            assert_eq!(expected.map_or(Ok(()), Err), actual, "{input}");
+        }
+
+        let err = validate_component("a b", Component::Fragment).unwrap_err();
+        assert_eq!(ErrorCode::InvalidFragment, err.code());
+
+        assert_no_alloc(|| {
+            assert!(validate_component("/a/b%20c", Component::Path).is_ok());
+        });
+    }
+
+    #[test]
+    fn test_percent_encode_ascii_set() {
+        const SEGMENT: AsciiSet = AsciiSet::PATH.add(b'/').add(b'%');
+
+        let test_data = [
+            ("a b/c%20d", AsciiSet::PATH, "a%20b/c%20d"),
+            ("a b/c%20d", SEGMENT, "a%20b%2Fc%2520d"),
+            ("a b‽", AsciiSet::EMPTY, "a b%E2%80%BD"),
+            ("a b", AsciiSet::PATH.remove(b' '), "a b"),
+            ("#?", AsciiSet::FRAGMENT.union(AsciiSet::QUERY), "%23?"),
+            ("a~b!", AsciiSet::FORM_URLENCODED, "a%7Eb%21"),
+            ("\u{7F}", AsciiSet::C0_CONTROL, "%7F"),
+        ];
+
+        for (input, set, expected) in test_data {
+            assert_eq!(expected, percent_encode(input, &set), "{input}");
+        }
+
+        assert!(AsciiSet::USERINFO.contains('@'));
+        assert!(!AsciiSet::USERINFO.contains('a'));
+        assert!(AsciiSet::EMPTY.contains('‽'));
+        assert_eq!(AsciiSet::QUERY.add(b'\''), AsciiSet::SPECIAL_QUERY);
+        assert_eq!(
+            AsciiSet::COMPONENT,
+            AsciiSet::USERINFO
+                .add(b'$')
+                .add(b'%')
+                .add(b'&')
+                .add(b'+')
+                .add(b',')
+        );
+
+        assert_no_alloc(|| {
+            assert!(matches!(
+                percent_encode("/a/b", &AsciiSet::PATH),
+                Cow::Borrowed(_)
+            ));
+        });
+    }
+
     #[test]
     fn percent_encode_fast_path() {
         assert_eq!(
             "Hello, World!",
-            assert_no_alloc(|| percent_encode(
-                Cow::Borrowed("Hello, World!"),
-                false,
-                is_c0_control_percent_encode
-            ))
+            assert_no_alloc(
+                || percent_encode_with(Cow::Borrowed("Hello, World!"), false, |c| {
+                    AsciiSet::C0_CONTROL.contains(c)
+                })
+            )
         );
     }
+
+    #[test]
+    fn test_percent_decode() {
+        let test_data: Vec<(&'_ [u8], &'_ [u8])> = vec![
+            (b"#", b"%23"),
+            (b"\xE2\x80\xBD", b"%E2%80%bd"),
+            (b"Say what\xE2\x80\xBD", b"Say%20what%E2%80%BD"),
+            (b"%", b"%"),
+            (b"%2", b"%2"),
+            (b"%zzA", b"%zz%41"),
+        ];
+
+        for (expected, input) in test_data {
+            assert_eq!(
+                expected,
+                &*percent_decode(input, DecodePolicy::PassThrough).unwrap()
+            );
+        }
+
+        assert_no_alloc(|| {
+            assert!(matches!(
+                percent_decode(b"Hello, World!", DecodePolicy::Strict),
+                Ok(Cow::Borrowed(_))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_percent_decode_policy() {
+        let test_data = [
+            ("100%", "100%", "100\u{FFFD}", Some(3)),
+            ("%2", "%2", "\u{FFFD}2", Some(0)),
+            ("%zz%41", "%zzA", "\u{FFFD}zzA", Some(0)),
+            ("a%41%%42", "aA%B", "aA\u{FFFD}B", Some(4)),
+            ("%E2%80%BD", "\u{203D}", "\u{203D}", None),
+            ("a%20b", "a b", "a b", None),
+        ];
+
+        for (input, pass_through, lossy, strict_offset) in test_data {
+            assert_eq!(
+                pass_through.as_bytes(),
+                &*percent_decode(input.as_bytes(), DecodePolicy::PassThrough).unwrap()
+            );
+            assert_eq!(
+                lossy.as_bytes(),
+                &*percent_decode(input.as_bytes(), DecodePolicy::Lossy).unwrap()
+            );
+            assert_eq!(
+                strict_offset,
+                percent_decode(input.as_bytes(), DecodePolicy::Strict)
+                    .err()
+                    .map(|err| err.offset()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_percent_decode_str() {
+        let test_data = [
+            ("caf%C3%A9", "caf\u{e9}", "caf\u{e9}", None),
+            ("%FF", "\u{FFFD}", "\u{FFFD}", Some(0)),
+            ("ab%E2%80", "ab\u{FFFD}", "ab\u{FFFD}", Some(2)),
+            ("%41%E2%80%41", "A\u{FFFD}A", "A\u{FFFD}A", Some(3)),
+            ("‽%", "‽%", "‽\u{FFFD}", Some(3)),
+        ];
+
+        for (input, pass_through, lossy, strict_offset) in test_data {
+            assert_eq!(
+                pass_through,
+                percent_decode_str(input, DecodePolicy::PassThrough).unwrap()
+            );
+            assert_eq!(
+                lossy,
+                percent_decode_str(input, DecodePolicy::Lossy).unwrap()
+            );
+            assert_eq!(
+                strict_offset,
+                percent_decode_str(input, DecodePolicy::Strict)
+                    .err()
+                    .map(|err| err.offset()),
+                "{input}"
+            );
+        }
+
+        let err = percent_decode_str("%FF", DecodePolicy::Strict).unwrap_err();
+        assert!(matches!(err, PercentDecodeError::InvalidUtf8 { .. }));
+        assert_eq!(ErrorCode::InvalidSyntax, err.code());
+
+        assert_no_alloc(|| {
+            assert!(matches!(
+                percent_decode_str("/a/b", DecodePolicy::Strict),
+                Ok(Cow::Borrowed(_))
+            ));
+        });
+    }
 }
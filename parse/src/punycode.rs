@@ -0,0 +1,348 @@
+//! Punycode encoding and decoding, the bootstring used for the `xn--` labels of
+//! internationalized domain names.
+//!
+//! All arithmetic is checked, so malformed or hostile input fails with an error instead of
+//! overflowing, and inputs longer than [`MAX_INPUT_LEN`] bytes are rejected to bound the work
+//! done, which grows with the square of the length.
+//!
+//! See [RFC3492](https://www.rfc-editor.org/rfc/rfc3492)
+
+use std::fmt;
+
+use crate::error::ErrorCode;
+use crate::parse::floor_char_boundary;
+
+/// The longest input, in bytes, that [`encode`] and [`decode`] accept.
+///
+/// A DNS label is at most 63 bytes, so this only limits input that could never be part of a
+/// domain name.
+pub const MAX_INPUT_LEN: usize = 2048;
+
+// 5. Parameter values for Punycode
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+const DELIMITER: char = '-';
+
+/// An error encountered while encoding or decoding punycode.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum PunycodeError {
+    /// A character is not a punycode digit, or the input ends inside a digit sequence.
+    InvalidDigit { offset: usize },
+    /// A digit sequence decodes to an ASCII code point or one that is not a Unicode scalar value.
+    InvalidCodePoint { offset: usize },
+    /// A digit sequence or code point delta does not fit in 32 bits.
+    Overflow { offset: usize },
+    /// The input is longer than [`MAX_INPUT_LEN`] bytes; the offset is the first character past
+    /// the limit.
+    TooLong { offset: usize },
+}
+
+impl PunycodeError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidSyntax
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidDigit { offset, .. }
+            | Self::InvalidCodePoint { offset, .. }
+            | Self::Overflow { offset, .. }
+            | Self::TooLong { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for PunycodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDigit { offset, .. } => {
+                write!(f, "invalid punycode digit at offset {offset}")
+            }
+            Self::InvalidCodePoint { offset, .. } => {
+                write!(f, "invalid decoded code point at offset {offset}")
+            }
+            Self::Overflow { offset, .. } => write!(f, "punycode overflow at offset {offset}"),
+            Self::TooLong { offset, .. } => {
+                write!(
+                    f,
+                    "input longer than {MAX_INPUT_LEN} bytes at offset {offset}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PunycodeError {}
+
+// 6.1 Bias adaptation function
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+
+    k + (((BASE - TMIN + 1) * delta) / (delta + SKEW))
+}
+
+// The threshold t for the digit at position k, clamped to the range tmin to tmax
+fn threshold(k: u32, bias: u32) -> u32 {
+    k.saturating_sub(bias).clamp(TMIN, TMAX)
+}
+
+// 0-25 are a-z, 26-35 are 0-9
+fn encode_digit(digit: u32) -> char {
+    debug_assert!(digit < BASE);
+    let digit = u8::try_from(digit).unwrap_or_default();
+    if digit < 26 {
+        char::from(b'a' + digit)
+    } else {
+        char::from(b'0' + digit - 26)
+    }
+}
+
+fn decode_digit(c: char) -> Option<u32> {
+    match c {
+        'a'..='z' => Some(u32::from(c) - u32::from('a')),
+        'A'..='Z' => Some(u32::from(c) - u32::from('A')),
+        '0'..='9' => Some(u32::from(c) - u32::from('0') + 26),
+        _ => None,
+    }
+}
+
+/// Encode a string as punycode, without the `xn--` prefix.
+///
+/// ASCII characters are copied to the start of the output, followed by `-` if there were any,
+/// so an input that is all ASCII is encoded with a trailing `-`.
+///
+/// # Errors
+///
+/// Returns an error if the input is longer than [`MAX_INPUT_LEN`] bytes.
+pub fn encode(input: &str) -> Result<String, PunycodeError> {
+    if input.len() > MAX_INPUT_LEN {
+        return Err(PunycodeError::TooLong {
+            offset: floor_char_boundary(input, MAX_INPUT_LEN),
+        });
+    }
+
+    let overflow = || PunycodeError::Overflow { offset: 0 };
+    // Every count below is at most MAX_INPUT_LEN, so it fits in a u32
+    let len = |count: usize| u32::try_from(count).map_err(|_| overflow());
+
+    let mut output: String = input.chars().filter(char::is_ascii).collect();
+    let basic = len(output.len())?;
+    if basic > 0 {
+        output.push(DELIMITER);
+    }
+
+    let input_len = len(input.chars().count())?;
+    let mut code_point = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut handled = basic;
+
+    while handled < input_len {
+        // Let m = the minimum code point >= n in the input
+        let next_code_point = input
+            .chars()
+            .map(u32::from)
+            .filter(|&c| c >= code_point)
+            .min()
+            .ok_or_else(overflow)?;
+
+        // Increase delta enough to advance the decoder's <n,i> state to <m,0>
+        delta = (next_code_point - code_point)
+            .checked_mul(handled + 1)
+            .and_then(|d| delta.checked_add(d))
+            .ok_or_else(overflow)?;
+        code_point = next_code_point;
+
+        for c in input.chars().map(u32::from) {
+            if c < code_point {
+                delta = delta.checked_add(1).ok_or_else(overflow)?;
+            }
+            if c == code_point {
+                // Represent delta as a generalized variable-length integer
+                let mut remaining = delta;
+                let mut position = BASE;
+                loop {
+                    let t = threshold(position, bias);
+                    if remaining < t {
+                        break;
+                    }
+                    output.push(encode_digit(t + (remaining - t) % (BASE - t)));
+                    remaining = (remaining - t) / (BASE - t);
+                    position += BASE;
+                }
+                output.push(encode_digit(remaining));
+
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+
+        delta = delta.checked_add(1).ok_or_else(overflow)?;
+        code_point = code_point.checked_add(1).ok_or_else(overflow)?;
+    }
+
+    Ok(output)
+}
+
+/// Decode punycode, without the `xn--` prefix, into a string.
+///
+/// Digits are accepted in either case, and basic code points are copied as they were written.
+///
+/// # Errors
+///
+/// Returns an error if the input is longer than [`MAX_INPUT_LEN`] bytes, contains non-ASCII
+/// characters, has an invalid or truncated digit sequence, decodes to a code point that is
+/// ASCII or not a Unicode scalar value, or overflows.
+pub fn decode(input: &str) -> Result<String, PunycodeError> {
+    if input.len() > MAX_INPUT_LEN {
+        return Err(PunycodeError::TooLong {
+            offset: floor_char_boundary(input, MAX_INPUT_LEN),
+        });
+    }
+
+    let invalid_digit = |offset| PunycodeError::InvalidDigit { offset };
+    let overflow = |offset| PunycodeError::Overflow { offset };
+
+    // Let b be the number of input code points before the last delimiter, or 0 if there is none,
+    // then copy the first b code points to the output
+    let (basic, digits, digits_offset) = match input.rfind(DELIMITER) {
+        Some(n) => (&input[..n], &input[n + 1..], n + 1),
+        None => ("", input, 0),
+    };
+    if let Some(offset) = input.find(|c: char| !c.is_ascii()) {
+        return Err(invalid_digit(offset));
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut code_point = INITIAL_N;
+    let mut index: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+
+    let mut digits = digits
+        .char_indices()
+        .map(|(offset, c)| (digits_offset + offset, c));
+    while let Some((start, first)) = digits.next() {
+        let old_index = index;
+        let mut weight: u32 = 1;
+        let mut position = BASE;
+        let mut next = Some((start, first));
+
+        loop {
+            let Some((offset, c)) = next else {
+                return Err(invalid_digit(input.len()));
+            };
+            let digit = decode_digit(c).ok_or_else(|| invalid_digit(offset))?;
+
+            index = digit
+                .checked_mul(weight)
+                .and_then(|d| index.checked_add(d))
+                .ok_or_else(|| overflow(offset))?;
+
+            let t = threshold(position, bias);
+            if digit < t {
+                break;
+            }
+            weight = weight
+                .checked_mul(BASE - t)
+                .ok_or_else(|| overflow(offset))?;
+            position += BASE;
+            next = digits.next();
+        }
+
+        // The output is at most MAX_INPUT_LEN code points, so its length fits in a u32
+        let len = u32::try_from(output.len() + 1).map_err(|_| overflow(start))?;
+        bias = adapt(index - old_index, len, old_index == 0);
+        code_point = code_point
+            .checked_add(index / len)
+            .ok_or_else(|| overflow(start))?;
+        index %= len;
+
+        // A basic code point would have been copied rather than encoded
+        let c = char::from_u32(code_point)
+            .filter(|c| !c.is_ascii())
+            .ok_or(PunycodeError::InvalidCodePoint { offset: start })?;
+        output.insert(index as usize, c);
+        index += 1;
+    }
+
+    Ok(output.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punycode() {
+        let test_data = [
+            // https://www.rfc-editor.org/rfc/rfc3492#section-7.1
+            (
+                "\u{644}\u{64A}\u{647}\u{645}\u{627}\u{628}\u{62A}\u{643}\u{644}\u{645}\u{648}\u{634}\u{639}\u{631}\u{628}\u{64A}\u{61F}",
+                "egbpdaj6bu4bxfgehfvwxn",
+            ),
+            (
+                "\u{4ED6}\u{4EEC}\u{4E3A}\u{4EC0}\u{4E48}\u{4E0D}\u{8BF4}\u{4E2D}\u{6587}",
+                "ihqwcrb4cv8a8dqg056pqjye",
+            ),
+            (
+                "Pro\u{10D}prost\u{11B}nemluv\u{ED}\u{10D}esky",
+                "Proprostnemluvesky-uyb24dma41a",
+            ),
+            ("3\u{5E74}B\u{7D44}\u{91D1}\u{516B}\u{5148}\u{751F}", "3B-ww4c5e180e575a65lsy2b"),
+            ("-> $1.00 <-", "-> $1.00 <--"),
+            ("b\u{fc}cher", "bcher-kva"),
+            ("\u{1F600}", "e28h"),
+            ("abc", "abc-"),
+            ("", ""),
+        ];
+
+        for (decoded, encoded) in test_data {
+            assert_eq!(encoded, encode(decoded).unwrap(), "{decoded}");
+            assert_eq!(decoded, decode(encoded).unwrap(), "{encoded}");
+        }
+
+        assert_eq!("b\u{fc}cher", decode("BCHER-KVA").unwrap().to_lowercase());
+    }
+
+    #[test]
+    fn test_punycode_invalid() {
+        let test_data = [
+            ("bcher-kv!", 8),
+            ("b\u{fc}cher-kva", 1),
+            ("bcher-kv", 8),
+            ("99999999999", 7),
+            ("a-b", 3),
+        ];
+
+        for (input, offset) in test_data {
+            let err = decode(input).unwrap_err();
+            assert_eq!(offset, err.offset(), "{input}");
+        }
+
+        let long = "a".repeat(MAX_INPUT_LEN + 1);
+        assert!(matches!(encode(&long), Err(PunycodeError::TooLong { .. })));
+        assert!(matches!(decode(&long), Err(PunycodeError::TooLong { .. })));
+        assert_eq!(MAX_INPUT_LEN, encode(&long).unwrap_err().offset());
+        let long = format!("a{}", "\u{e9}".repeat(MAX_INPUT_LEN / 2));
+        assert_eq!(MAX_INPUT_LEN - 1, encode(&long).unwrap_err().offset());
+        assert!(encode(&"\u{10FFFF}".repeat(MAX_INPUT_LEN / 4)).is_ok());
+    }
+}
@@ -0,0 +1,562 @@
+use std::borrow::Cow;
+
+use crate::form_urlencoded;
+
+/// Iterator over the name-value pairs of a query string.
+///
+/// Pairs are split and decoded using the
+/// [application/x-www-form-urlencoded parser](https://url.spec.whatwg.org/#urlencoded-parsing),
+/// so `+` is decoded as a space and invalid UTF-8 is replaced by U+FFFD REPLACEMENT CHARACTER.
+/// Names and values that need no decoding are borrowed from the query.
+#[derive(Debug, Clone)]
+pub struct QueryPairs<'a> {
+    input: &'a str,
+    len: usize,
+    offset: usize,
+}
+
+impl<'a> QueryPairs<'a> {
+    /// Iterate over the pairs of a query string, without its leading `?`.
+    #[must_use]
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            len: input.len(),
+            offset: 0,
+        }
+    }
+
+    // The byte offset of the most recently returned pair from the start of the query
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl<'a> Iterator for QueryPairs<'a> {
+    type Item = (Cow<'a, str>, Cow<'a, str>);
+
+    // Let sequences be the result of splitting input on 0x26 (&).
+    // For each byte sequence bytes in sequences:
+    //     If bytes is the empty byte sequence, then continue.
+    //     If bytes contains a 0x3D (=), then let name be the bytes from the start of bytes up to but
+    //     excluding its first 0x3D (=), and let value be the bytes, if any, after the first 0x3D (=)
+    //     up to the end of bytes. If 0x3D (=) is the first byte, then name will be the empty byte
+    //     sequence. If it is the last, then value will be the empty byte sequence.
+    //     Otherwise, let name have the value of bytes and let value be the empty byte sequence.
+    //     Replace any 0x2B (+) in name and value with 0x20 (SP).
+    //     Let nameString and valueString be the result of running UTF-8 decode without BOM on the
+    //     percent-decoding of name and value, respectively.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.input.is_empty() {
+                return None;
+            }
+
+            let offset = self.len - self.input.len();
+            let (bytes, rest) = self.input.split_once('&').unwrap_or((self.input, ""));
+            self.input = rest;
+
+            if bytes.is_empty() {
+                continue;
+            }
+
+            let (name, value) = bytes.split_once('=').unwrap_or((bytes, ""));
+            self.offset = offset;
+
+            return Some((
+                form_urlencoded::decode(name),
+                form_urlencoded::decode(value),
+            ));
+        }
+    }
+}
+
+/// Which pairs are kept when a name is repeated, see [`QueryMap::to_canonical_string`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Keep every pair.
+    #[default]
+    KeepAll,
+    /// Keep the first pair with each name.
+    KeepFirst,
+    /// Keep the last pair with each name.
+    KeepLast,
+}
+
+/// The name-value pairs of a query string, in the order they appeared.
+///
+/// Names are case-sensitive and may be repeated. Serializing a map that has not been modified
+/// gives back the original query string byte for byte, which signature schemes over the query
+/// rely on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMap<'a> {
+    input: &'a str,
+    pairs: Vec<Pair<'a>>,
+    modified: bool,
+}
+
+// A decoded pair, and the encoded pair it was decoded from until its value is replaced
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pair<'a> {
+    name: Cow<'a, str>,
+    value: Cow<'a, str>,
+    encoded: Option<&'a str>,
+}
+
+impl Pair<'_> {
+    // The decoded name and value with any invalid UTF-8 kept, rather than replaced by U+FFFD as in
+    // the strings
+    fn to_bytes(&self) -> (Cow<'_, [u8]>, Cow<'_, [u8]>) {
+        let Some(encoded) = self.encoded else {
+            return (
+                Cow::Borrowed(self.name.as_bytes()),
+                Cow::Borrowed(self.value.as_bytes()),
+            );
+        };
+
+        let (name, value) = encoded.split_once('=').unwrap_or((encoded, ""));
+        (
+            form_urlencoded::decode_to_bytes(name.as_bytes()),
+            form_urlencoded::decode_to_bytes(value.as_bytes()),
+        )
+    }
+}
+
+impl<'a> QueryMap<'a> {
+    /// Split and decode a query string, without its leading `?`, into its pairs.
+    ///
+    /// Pairs are decoded using the
+    /// [application/x-www-form-urlencoded parser](https://url.spec.whatwg.org/#urlencoded-parsing),
+    /// so this never fails.
+    #[must_use]
+    pub fn parse(query: &'a str) -> Self {
+        let mut pairs = QueryPairs::new(query);
+
+        Self {
+            input: query,
+            pairs: std::iter::from_fn(|| {
+                let (name, value) = pairs.next()?;
+                let encoded = &query[pairs.offset()..];
+                let encoded = encoded.split_once('&').map_or(encoded, |(pair, _)| pair);
+
+                Some(Pair {
+                    name,
+                    value,
+                    encoded: Some(encoded),
+                })
+            })
+            .collect(),
+            modified: false,
+        }
+    }
+
+    /// The value of the first pair with the given name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|pair| pair.name == name)
+            .map(|pair| &*pair.value)
+    }
+
+    /// The values of every pair with the given name, in the order they appeared.
+    pub fn get_all<'b>(&'b self, name: &'b str) -> impl Iterator<Item = &'b str> + 'b {
+        self.pairs
+            .iter()
+            .filter(move |pair| pair.name == name)
+            .map(|pair| &*pair.value)
+    }
+
+    /// Whether there is a pair with the given name.
+    #[must_use]
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Every pair, in the order they appeared.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|pair| (&*pair.name, &*pair.value))
+    }
+
+    /// The number of pairs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Whether there are no pairs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Add a pair after every other pair.
+    pub fn append(&mut self, name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) {
+        self.pairs.push(Pair {
+            name: name.into(),
+            value: value.into(),
+            encoded: None,
+        });
+        self.modified = true;
+    }
+
+    /// Remove every pair with the given name.
+    pub fn remove(&mut self, name: &str) {
+        let len = self.pairs.len();
+        self.pairs.retain(|pair| pair.name != name);
+        self.modified |= self.pairs.len() != len;
+    }
+
+    /// Replace the value of the first pair with the given name and remove the others, or append
+    /// a pair if there is none, like `URLSearchParams.set()`.
+    pub fn set(&mut self, name: impl Into<Cow<'a, str>>, value: impl Into<Cow<'a, str>>) {
+        let name = name.into();
+        let mut value = Some(value.into());
+
+        self.pairs.retain_mut(|pair| {
+            if pair.name != name {
+                return true;
+            }
+            match value.take() {
+                Some(value) => {
+                    pair.value = value;
+                    pair.encoded = None;
+                    true
+                }
+                None => false,
+            }
+        });
+
+        if let Some(value) = value {
+            self.pairs.push(Pair {
+                name,
+                value,
+                encoded: None,
+            });
+        }
+        self.modified = true;
+    }
+
+    /// Serialize the pairs back into a query string.
+    ///
+    /// Until the pairs are modified this is the original query string. After that the pairs are
+    /// serialized with the
+    /// [application/x-www-form-urlencoded serializer](https://url.spec.whatwg.org/#urlencoded-serializing),
+    /// which encodes names and values as needed.
+    #[must_use]
+    pub fn serialize(&self) -> Cow<'a, str> {
+        if self.modified {
+            return Cow::Owned(form_urlencoded::serialize(self.iter()));
+        }
+
+        Cow::Borrowed(self.input)
+    }
+
+    /// Serialize the pairs into a canonical query string, for use as a cache key.
+    ///
+    /// Queries that decode to the same bytes give the same string regardless of how they were
+    /// percent-encoded or ordered, and queries that decode to different bytes give different
+    /// strings, even where the bytes are not valid UTF-8. Repeated names are first reduced
+    /// according to `duplicates`, then the pairs are sorted bytewise by name and then by value,
+    /// and serialized with the
+    /// [application/x-www-form-urlencoded serializer](https://url.spec.whatwg.org/#urlencoded-serializing).
+    #[must_use]
+    pub fn to_canonical_string(&self, duplicates: DuplicatePolicy) -> String {
+        let mut pairs: Vec<_> = self.pairs.iter().map(Pair::to_bytes).collect();
+        if duplicates != DuplicatePolicy::KeepAll {
+            if duplicates == DuplicatePolicy::KeepLast {
+                pairs.reverse();
+            }
+            // The sort is stable, so dedup keeps whichever pair with each name came first above
+            pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+            pairs.dedup_by(|(a, _), (b, _)| a == b);
+        }
+        pairs.sort_unstable();
+
+        let mut out = String::new();
+        for (name, value) in pairs {
+            if !out.is_empty() {
+                out.push('&');
+            }
+            out.push_str(&form_urlencoded::encode_bytes(&name));
+            out.push('=');
+            out.push_str(&form_urlencoded::encode_bytes(&value));
+        }
+
+        out
+    }
+}
+
+/// An incremental parser for query strings too long to hold in memory at once.
+///
+/// Chunks of the query are passed to [`feed`](Self::feed) as they arrive, split at any point, and
+/// each pair is returned as soon as the `&` ending it has been seen. Only the bytes of the
+/// current incomplete pair are buffered. Pairs are decoded the same way as [`QueryMap`].
+#[derive(Debug, Default)]
+pub struct QueryPairParser {
+    partial: Vec<u8>,
+}
+
+/// The pairs completed by one chunk of a query, see [`QueryPairParser::feed`].
+///
+/// The parser always moves past the whole chunk. Dropping the iterator before it is exhausted
+/// skips the remaining pairs of the chunk.
+#[derive(Debug)]
+pub struct ChunkPairs<'a> {
+    parser: &'a mut QueryPairParser,
+    chunk: &'a [u8],
+}
+
+impl QueryPairParser {
+    /// Create a parser positioned at the start of a query, without its leading `?`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse the next chunk of the query, returning the pairs that end in it.
+    pub fn feed<'a>(&'a mut self, chunk: &'a [u8]) -> ChunkPairs<'a> {
+        ChunkPairs {
+            parser: self,
+            chunk,
+        }
+    }
+
+    /// Mark the end of the query, returning the final pair, if any.
+    #[must_use]
+    pub fn finish(self) -> Option<(String, String)> {
+        decode_pair(&self.partial)
+    }
+}
+
+impl Iterator for ChunkPairs<'_> {
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let partial = &mut self.parser.partial;
+
+        while let Some(n) = self.chunk.iter().position(|&c| c == b'&') {
+            let segment = &self.chunk[..n];
+            self.chunk = &self.chunk[n + 1..];
+
+            let pair = if partial.is_empty() {
+                decode_pair(segment)
+            } else {
+                partial.extend_from_slice(segment);
+                let pair = decode_pair(partial);
+                partial.clear();
+                pair
+            };
+            if pair.is_some() {
+                return pair;
+            }
+        }
+
+        // The rest of the chunk is not followed by a "&", so it may continue in the next chunk
+        partial.extend_from_slice(std::mem::take(&mut self.chunk));
+        None
+    }
+}
+
+impl Drop for ChunkPairs<'_> {
+    fn drop(&mut self) {
+        // Buffer the end of the chunk, so the next chunk continues the right pair
+        self.for_each(drop);
+    }
+}
+
+fn decode_pair(bytes: &'_ [u8]) -> Option<(String, String)> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let (name, value) = match bytes.iter().position(|&c| c == b'=') {
+        Some(n) => (&bytes[..n], &bytes[n + 1..]),
+        None => (bytes, &[][..]),
+    };
+
+    Some((
+        form_urlencoded::decode_bytes(name).into_owned(),
+        form_urlencoded::decode_bytes(value).into_owned(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_query_pairs() {
+        let test_data: Vec<(Vec<(&'_ str, &'_ str)>, &'_ str)> = vec![
+            (vec![("a", "1"), ("b", "2")], "a=1&b=2"),
+            (vec![("a", ""), ("b", "")], "a&&b="),
+            (vec![("", "x"), ("a b", "c d")], "=x&a+b=c%20d"),
+            (vec![("a", "b=c")], "a=b=c"),
+            (vec![("≡", "‽")], "%E2%89%A1=%E2%80%BD"),
+            (vec![("bad", "\u{FFFD}")], "bad=%FF"),
+            (vec![], ""),
+        ];
+
+        for (expected, input) in test_data {
+            let pairs: Vec<_> = QueryPairs::new(input).collect();
+            assert_eq!(
+                expected,
+                pairs
+                    .iter()
+                    .map(|(name, value)| (&**name, &**value))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_map() {
+        let input = "b=2&a=1&A=3&a=&&c&a=%E2%80%BD+x";
+        let map = QueryMap::parse(input);
+
+        assert_eq!(6, map.len());
+        assert_eq!(Some("1"), map.get("a"));
+        assert_eq!(Some("3"), map.get("A"));
+        assert_eq!(Some(""), map.get("c"));
+        assert_eq!(None, map.get("d"));
+        assert!(map.contains_key("b"));
+        assert_eq!(vec!["1", "", "‽ x"], map.get_all("a").collect::<Vec<_>>());
+        assert_eq!(
+            vec!["b", "a", "A", "a", "c", "a"],
+            map.iter().map(|(name, _)| name).collect::<Vec<_>>()
+        );
+        assert_eq!(input, map.serialize());
+
+        assert!(QueryMap::parse("").is_empty());
+        assert!(QueryMap::parse("&&").is_empty());
+        assert_eq!("&&", QueryMap::parse("&&").serialize());
+    }
+
+    #[test]
+    fn test_query_map_mutation() {
+        let mut map = QueryMap::parse("a=1&b=2&a=3&c=%7E");
+
+        map.append("d", "x y&z");
+        assert_eq!("a=1&b=2&a=3&c=%7E&d=x+y%26z", map.serialize());
+
+        map.set("a", "\u{203D}");
+        assert_eq!(vec!["\u{203D}"], map.get_all("a").collect::<Vec<_>>());
+        assert_eq!("a=%E2%80%BD&b=2&c=%7E&d=x+y%26z", map.serialize());
+
+        map.set("e", String::from("5"));
+        map.remove("b");
+        map.remove("missing");
+        assert_eq!("a=%E2%80%BD&c=%7E&d=x+y%26z&e=5", map.serialize());
+
+        let mut map = QueryMap::parse("a=1&b=%32");
+        map.remove("c");
+        assert!(matches!(map.serialize(), Cow::Borrowed("a=1&b=%32")));
+        map.remove("a");
+        assert_eq!("b=2", map.serialize());
+    }
+
+    #[test]
+    fn test_query_map_canonical() {
+        let test_data = [
+            ("b=2&a=1", DuplicatePolicy::KeepAll, "a=1&b=2"),
+            ("a=%62&a=a", DuplicatePolicy::KeepAll, "a=a&a=b"),
+            ("a=2&b&a=1", DuplicatePolicy::KeepFirst, "a=2&b="),
+            ("a=2&b&a=1", DuplicatePolicy::KeepLast, "a=1&b="),
+            ("B=1&a=1", DuplicatePolicy::KeepAll, "B=1&a=1"),
+            ("q=a%20b", DuplicatePolicy::KeepAll, "q=a+b"),
+            ("q=a+b", DuplicatePolicy::KeepAll, "q=a+b"),
+            ("q=%7e%21", DuplicatePolicy::KeepAll, "q=%7E%21"),
+            ("q=%E2%80%BD&&", DuplicatePolicy::KeepAll, "q=%E2%80%BD"),
+            ("q=\u{203D}", DuplicatePolicy::KeepAll, "q=%E2%80%BD"),
+            ("a=%FF", DuplicatePolicy::KeepAll, "a=%FF"),
+            ("a=%fe", DuplicatePolicy::KeepAll, "a=%FE"),
+            ("a=%EF%BF%BD", DuplicatePolicy::KeepAll, "a=%EF%BF%BD"),
+            ("%FF=1&%FE=2", DuplicatePolicy::KeepFirst, "%FE=2&%FF=1"),
+            ("", DuplicatePolicy::KeepAll, ""),
+        ];
+
+        for (input, duplicates, expected) in test_data {
+            assert_eq!(
+                expected,
+                QueryMap::parse(input).to_canonical_string(duplicates),
+                "{input}"
+            );
+        }
+
+        // Queries that only differ in invalid UTF-8 decode to the same strings, but not to the
+        // same keys, even once other pairs are modified
+        let mut a = QueryMap::parse("a=%FF&b=1");
+        let mut b = QueryMap::parse("a=%FE&b=1");
+        assert_eq!(a.get("a"), b.get("a"));
+        for duplicates in [DuplicatePolicy::KeepAll, DuplicatePolicy::KeepFirst] {
+            assert_ne!(
+                a.to_canonical_string(duplicates),
+                b.to_canonical_string(duplicates)
+            );
+        }
+        a.set("b", "2");
+        b.set("b", "2");
+        assert_eq!("a=%FF&b=2", a.to_canonical_string(DuplicatePolicy::KeepAll));
+        assert_eq!("a=%FE&b=2", b.to_canonical_string(DuplicatePolicy::KeepAll));
+    }
+
+    #[test]
+    fn test_query_pair_parser() {
+        let test_data = [
+            "a=1&b=2",
+            "a&&b=&",
+            "=x&a+b=c%20d",
+            "%E2%89%A1=%E2%80%BD&bad=%FF",
+            "",
+        ];
+
+        for input in test_data {
+            let expected: Vec<_> = QueryPairs::new(input)
+                .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                .collect();
+
+            // Every way of splitting the input in three gives the same pairs
+            for i in 0..=input.len() {
+                for j in i..=input.len() {
+                    let mut parser = QueryPairParser::new();
+                    let mut pairs = Vec::new();
+                    for chunk in [&input[..i], &input[i..j], &input[j..]] {
+                        pairs.extend(parser.feed(chunk.as_bytes()));
+                    }
+                    pairs.extend(parser.finish());
+
+                    assert_eq!(expected, pairs, "{input} split at {i} and {j}");
+                }
+            }
+        }
+
+        // Pairs are available as soon as they end
+        let mut parser = QueryPairParser::new();
+        let mut pairs = parser.feed(b"a=1&b");
+        assert_eq!(Some(("a".to_owned(), "1".to_owned())), pairs.next());
+        assert_eq!(None, pairs.next());
+        drop(pairs);
+        assert_eq!(None, parser.feed(b"=2").next());
+        assert_eq!(Some(("b".to_owned(), "2".to_owned())), parser.finish());
+
+        // Dropping the pairs of a chunk early still buffers its incomplete pair
+        let mut parser = QueryPairParser::new();
+        assert!(parser.feed(b"a=1&b=2&c").next().is_some());
+        assert_eq!(
+            Some(("c".to_owned(), "3".to_owned())),
+            parser.feed(b"=3&").next()
+        );
+        assert_eq!(None, parser.finish());
+    }
+
+    #[test]
+    fn test_query_pairs_no_alloc() {
+        assert_no_alloc(|| {
+            for (name, value) in QueryPairs::new("a=1&b=2&c") {
+                assert!(matches!(name, Cow::Borrowed(_)));
+                assert!(matches!(value, Cow::Borrowed(_)));
+            }
+        });
+    }
+}
@@ -0,0 +1,198 @@
+use std::{
+    borrow::Cow,
+    ops::Range,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::error::ErrorCode;
+
+/// A hook installed with [`set_rejection_hook`].
+pub type RejectionHook = Arc<dyn Fn(&Rejection<'_>) + Send + Sync>;
+
+static HOOK: RwLock<Option<RejectionHook>> = RwLock::new(None);
+
+// Bytes of context included on each side of the offending range in a snippet
+const SNIPPET_CONTEXT: usize = 16;
+
+/// Details of an input rejected by one of the crate's parsers, passed to the hook installed with
+/// [`set_rejection_hook`].
+#[derive(Debug, Clone)]
+pub struct Rejection<'a> {
+    code: ErrorCode,
+    range: Range<usize>,
+    input: &'a str,
+}
+
+impl<'a> Rejection<'a> {
+    fn new(input: &'a str, code: ErrorCode, offset: usize) -> Self {
+        // Parsers should report offsets on character boundaries, but a bad offset must not turn
+        // a clean error into a panic
+        let mut start = offset.min(input.len());
+        while !input.is_char_boundary(start) {
+            start -= 1;
+        }
+        let end = input[start..]
+            .chars()
+            .next()
+            .map_or(start, |c| start + c.len_utf8());
+
+        Self {
+            code,
+            range: start..end,
+            input,
+        }
+    }
+
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// The byte range of the input at which the error was detected.
+    ///
+    /// This covers the character at the error offset, and is empty if the error was detected at
+    /// the end of the input.
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The input surrounding the error with its contents redacted.
+    ///
+    /// ASCII letters and digits are replaced with `x`, and non-ASCII and control characters with
+    /// `?`, leaving only punctuation and spaces. This keeps the shape of the input, which is usually enough to
+    /// tell what was wrong with it, while not leaking credentials or other user data into logs.
+    #[must_use]
+    pub fn snippet(&self) -> String {
+        self.unredacted_snippet()
+            .chars()
+            .map(|c| match c {
+                c if c.is_ascii_alphanumeric() => 'x',
+                c if !c.is_ascii() || c.is_ascii_control() => '?',
+                c => c,
+            })
+            .collect()
+    }
+
+    /// The input surrounding the error as it appeared.
+    ///
+    /// This may contain credentials or other sensitive data, prefer [`Rejection::snippet`] unless
+    /// the input is known to be safe to log.
+    #[must_use]
+    pub fn unredacted_snippet(&self) -> Cow<'a, str> {
+        let mut start = self.range.start.saturating_sub(SNIPPET_CONTEXT);
+        while !self.input.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = (self.range.end + SNIPPET_CONTEXT).min(self.input.len());
+        while !self.input.is_char_boundary(end) {
+            end += 1;
+        }
+
+        Cow::Borrowed(&self.input[start..end])
+    }
+}
+
+/// Install a hook called whenever one of the crate's parsers rejects its input, replacing any
+/// previously installed hook.
+///
+/// The hook is process wide and is called synchronously on the thread that is parsing, before
+/// the error is returned. It is not called for inputs that parse successfully. No lock is held
+/// while the hook runs, so it may parse, or install or remove hooks, itself.
+///
+/// ```
+/// parse::set_rejection_hook(|rejection| {
+///     eprintln!("rejected {:?} at {:?}: {}", rejection.code(), rejection.range(), rejection.snippet());
+/// });
+///
+/// assert!(parse::uri::TelUri::parse("tel:+1 816").is_err());
+/// # parse::take_rejection_hook();
+/// ```
+pub fn set_rejection_hook<F>(hook: F)
+where
+    F: Fn(&Rejection<'_>) + Send + Sync + 'static,
+{
+    let mut current = HOOK.write().unwrap_or_else(PoisonError::into_inner);
+    *current = Some(Arc::new(hook));
+}
+
+/// Remove the hook installed with [`set_rejection_hook`], returning it.
+#[allow(clippy::must_use_candidate)]
+pub fn take_rejection_hook() -> Option<RejectionHook> {
+    HOOK.write().unwrap_or_else(PoisonError::into_inner).take()
+}
+
+// Call the installed hook, if any, for an input rejected at offset
+pub(crate) fn report(input: &'_ str, code: ErrorCode, offset: usize) {
+    // Clone the hook out so the lock is released before calling it
+    let hook = HOOK.read().unwrap_or_else(PoisonError::into_inner).clone();
+    let Some(hook) = hook else {
+        return;
+    };
+
+    hook(&Rejection::new(input, code, offset));
+}
+
+// Call the installed hook, if any, for a byte input such as a header field value. Bytes outside
+// ASCII are replaced one for one with `?` when the input is not UTF-8, so the offset still
+// points at the same byte.
+pub(crate) fn report_bytes(input: &'_ [u8], code: ErrorCode, offset: usize) {
+    if HOOK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_none()
+    {
+        return;
+    }
+
+    if let Ok(input) = std::str::from_utf8(input) {
+        report(input, code, offset);
+    } else {
+        let input: String = input
+            .iter()
+            .map(|&c| if c.is_ascii() { char::from(c) } else { '?' })
+            .collect();
+        report(&input, code, offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet() {
+        let test_data = [
+            (
+                "sip:alice:secret@exa mple.com",
+                20,
+                20..21,
+                "xxxxx:xxxxxx@xxx xxxx.xxx",
+                "alice:secret@exa mple.com",
+            ),
+            ("tel:+1", 6, 6..6, "xxx:+x", "tel:+1"),
+            (
+                "https://example.com/a/very/long/path/with/a space/in/it",
+                43,
+                43..44,
+                "xxxx/xxxx/xxxx/x xxxxx/xx/xx",
+                "long/path/with/a space/in/it",
+            ),
+            ("tel:ü€x", 6, 6..9, "xxx:??x", "tel:ü€x"),
+            // Offsets inside a character are moved back to its start
+            ("tel:ü€x", 7, 6..9, "xxx:??x", "tel:ü€x"),
+            ("tel:ü", 5, 4..6, "xxx:?", "tel:ü"),
+            ("tel:ü", 9, 6..6, "xxx:?", "tel:ü"),
+        ];
+
+        for (input, offset, range, snippet, unredacted) in test_data {
+            let rejection = Rejection::new(input, ErrorCode::InvalidSyntax, offset);
+
+            assert_eq!(range, rejection.range(), "{input}");
+            assert_eq!(snippet, rejection.snippet(), "{input}");
+            assert_eq!(unredacted, rejection.unredacted_snippet(), "{input}");
+        }
+    }
+}
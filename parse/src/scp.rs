@@ -0,0 +1,320 @@
+use std::fmt;
+
+use nom::{
+    bytes::complete::{take_till1, take_until},
+    character::complete::char,
+    combinator::{all_consuming, map_res, recognize},
+    sequence::delimited,
+};
+
+use crate::{
+    error::ErrorCode,
+    ipv6,
+    parse::ParseResult,
+    percent_encode::{percent_encode, AsciiSet},
+    reject,
+};
+
+// The user and path are literal, so a `%` in them must be encoded too
+const USER_ENCODE_SET: AsciiSet = AsciiSet::USERINFO.add(b'%');
+const PATH_ENCODE_SET: AsciiSet = AsciiSet::PATH.add(b'%');
+
+/// An "scp-like" URL such as `git@github.com:org/repo.git`, as accepted by git and ssh tooling.
+///
+/// These are not URIs, so they are recognized separately from the strict URI parsers. Use
+/// [`ScpLikeUrl::to_ssh_uri`] to convert one into an equivalent `ssh://` URI.
+///
+/// See [git-clone](https://git-scm.com/docs/git-clone#_git_urls)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScpLikeUrl<'a> {
+    user: Option<&'a str>,
+    host: &'a str,
+    path: &'a str,
+}
+
+/// An error encountered while recognizing an scp-like URL.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum ScpLikeUrlError {
+    /// The input is a URL or a local path rather than `[user@]host:path`.
+    NotScpLike,
+    /// The user is empty or contains whitespace, a control, or a delimiter.
+    InvalidUser,
+    /// The host is not a bracketed IPv6 address and contains a character a host can not.
+    InvalidHost { offset: usize },
+    /// Nothing follows the `:`.
+    EmptyPath { offset: usize },
+}
+
+impl ScpLikeUrlError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NotScpLike => ErrorCode::InvalidSyntax,
+            Self::InvalidUser { .. } => ErrorCode::InvalidUserinfo,
+            Self::InvalidHost { .. } => ErrorCode::InvalidHost,
+            Self::EmptyPath { .. } => ErrorCode::InvalidPath,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::NotScpLike | Self::InvalidUser { .. } => 0,
+            Self::InvalidHost { offset, .. } | Self::EmptyPath { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ScpLikeUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotScpLike => f.write_str("not an scp-like URL"),
+            Self::InvalidUser => f.write_str("invalid user"),
+            Self::InvalidHost { offset, .. } => write!(f, "invalid host at offset {offset}"),
+            Self::EmptyPath { offset } => write!(f, "empty path at offset {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for ScpLikeUrlError {}
+
+impl<'a> ScpLikeUrl<'a> {
+    /// Recognize an scp-like URL of the form `[user@]host:path`.
+    ///
+    /// Inputs containing `://`, a `/` before the first `:`, or a single letter before the `:`
+    /// (a Windows drive letter) are not scp-like.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not scp-like, or if the user, host, or path is invalid.
+    pub fn parse(input: &'a str) -> Result<Self, ScpLikeUrlError> {
+        Self::parse_unreported(input).inspect_err(|err| {
+            // Not being scp-like is expected when probing other kinds of URL, only report
+            // inputs that look scp-like but are malformed
+            if !matches!(err, ScpLikeUrlError::NotScpLike) {
+                reject::report(input, err.code(), err.offset());
+            }
+        })
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, ScpLikeUrlError> {
+        if input.contains("://") {
+            return Err(ScpLikeUrlError::NotScpLike);
+        }
+
+        let (user, i) = match input.split_once('@') {
+            Some((user, i)) if !user.contains(['/', '\\', ':']) => (Some(user), i),
+            _ => (None, input),
+        };
+
+        if let Some(user) = user {
+            if user.is_empty() || user.contains(is_invalid_char) {
+                return Err(ScpLikeUrlError::InvalidUser);
+            }
+        }
+
+        let Ok((path, host)) = parse_host(i) else {
+            return Err(ScpLikeUrlError::NotScpLike);
+        };
+
+        let Some(path) = path.strip_prefix(':') else {
+            return Err(ScpLikeUrlError::NotScpLike);
+        };
+
+        // A single letter followed by a colon is a Windows drive letter, not a host
+        if user.is_none() && host.len() == 1 && host.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(ScpLikeUrlError::NotScpLike);
+        }
+
+        if !host.starts_with('[') && host.contains(is_invalid_host_char) {
+            return Err(ScpLikeUrlError::InvalidHost {
+                offset: input.len() - i.len(),
+            });
+        }
+
+        if path.is_empty() {
+            return Err(ScpLikeUrlError::EmptyPath {
+                offset: input.len(),
+            });
+        }
+
+        Ok(Self { user, host, path })
+    }
+
+    /// The user, the part before `@`.
+    #[must_use]
+    pub fn user(&self) -> Option<&'a str> {
+        self.user
+    }
+
+    /// The host, IPv6 addresses include their enclosing brackets.
+    #[must_use]
+    pub fn host(&self) -> &'a str {
+        self.host
+    }
+
+    /// The path, relative paths are resolved against the user's home directory.
+    #[must_use]
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Convert the URL into an equivalent `ssh://` URI.
+    ///
+    /// The user and path are percent-encoded. Relative paths are rewritten to start with `/~/` so
+    /// they continue to resolve against the user's home directory.
+    #[must_use]
+    pub fn to_ssh_uri(&self) -> String {
+        let mut out = String::with_capacity(self.host.len() + self.path.len() + 16);
+
+        out.push_str("ssh://");
+        if let Some(user) = self.user {
+            out.push_str(&percent_encode(user, &USER_ENCODE_SET));
+            out.push('@');
+        }
+        out.push_str(self.host);
+
+        if !self.path.starts_with('/') {
+            out.push('/');
+            if !self.path.starts_with('~') {
+                out.push_str("~/");
+            }
+        }
+        out.push_str(&percent_encode(self.path, &PATH_ENCODE_SET));
+
+        out
+    }
+}
+
+impl<'a> TryFrom<&'a str> for ScpLikeUrl<'a> {
+    type Error = ScpLikeUrlError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+// Whitespace, controls, and delimiters can never appear in the user or host
+fn is_invalid_char(c: char) -> bool {
+    c.is_ascii_whitespace() || c.is_ascii_control() || matches!(c, '/' | '@' | '[' | ']')
+}
+
+// The host is copied into the ssh:// URI as is, so it also can not contain anything that would
+// end the authority there or that a URI does not allow
+fn is_invalid_host_char(c: char) -> bool {
+    is_invalid_char(c)
+        || matches!(
+            c,
+            '#' | '?' | '%' | '<' | '>' | '^' | '|' | '"' | '`' | '{' | '}'
+        )
+}
+
+// host = "[" IPv6address "]" / 1*( any char except ":", "/", and "\" )
+//
+// A path separator before the first colon makes the input a local path, as Windows paths may
+// use either separator.
+fn parse_host(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    if i.starts_with('[') {
+        return recognize(delimited(
+            char('['),
+            map_res(take_until("]"), all_consuming(ipv6::parse)),
+            char(']'),
+        ))(i);
+    }
+
+    take_till1(|c| matches!(c, ':' | '/' | '\\'))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scp_like() {
+        let test_data = [
+            (
+                "git@github.com:org/repo.git",
+                Some("git"),
+                "github.com",
+                "org/repo.git",
+                "ssh://git@github.com/~/org/repo.git",
+            ),
+            (
+                "host.xz:/srv/repo.git",
+                None,
+                "host.xz",
+                "/srv/repo.git",
+                "ssh://host.xz/srv/repo.git",
+            ),
+            (
+                "host.xz:~user/repo.git",
+                None,
+                "host.xz",
+                "~user/repo.git",
+                "ssh://host.xz/~user/repo.git",
+            ),
+            (
+                "git@[::1]:repo.git",
+                Some("git"),
+                "[::1]",
+                "repo.git",
+                "ssh://git@[::1]/~/repo.git",
+            ),
+            (
+                "git@host:my repo.git",
+                Some("git"),
+                "host",
+                "my repo.git",
+                "ssh://git@host/~/my%20repo.git",
+            ),
+            (
+                "host:/srv/100%/a#b?.git",
+                None,
+                "host",
+                "/srv/100%/a#b?.git",
+                "ssh://host/srv/100%25/a%23b%3F.git",
+            ),
+            (
+                "g%t@host:~/x.git",
+                Some("g%t"),
+                "host",
+                "~/x.git",
+                "ssh://g%25t@host/~/x.git",
+            ),
+        ];
+
+        for (input, user, host, path, ssh_uri) in test_data {
+            let url = ScpLikeUrl::parse(input).unwrap();
+            assert_eq!(user, url.user());
+            assert_eq!(host, url.host());
+            assert_eq!(path, url.path());
+            assert_eq!(ssh_uri, url.to_ssh_uri());
+        }
+    }
+
+    #[test]
+    fn test_parse_scp_like_invalid() {
+        let test_data: Vec<&'_ str> = vec![
+            "ssh://git@github.com/org/repo.git",
+            "./repo:name",
+            "C:\\repo.git",
+            "github.com",
+            "git@github.com:",
+            "@github.com:repo.git",
+            "git@[::g]:repo.git",
+            "git@git hub.com:repo.git",
+            "evil.com#.good.com:repo",
+            "a?b:repo",
+            "git@a%2fb:repo",
+            "\\\\server\\share:repo",
+            "dir\\git@host:repo",
+        ];
+
+        for input in test_data {
+            assert!(ScpLikeUrl::parse(input).is_err(), "{}", input);
+        }
+    }
+}
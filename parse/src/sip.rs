@@ -0,0 +1,460 @@
+use std::{borrow::Cow, fmt};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag_no_case, take_until, take_while, take_while1},
+    character::complete::{char, digit1},
+    combinator::{all_consuming, cut, fail, map, map_res, opt, recognize, success},
+    sequence::{delimited, preceded, tuple},
+};
+
+use crate::{
+    error::ErrorCode, ipv4::parse_ipv4_dotted_decimal, ipv6, parse::ParseResult,
+    percent_encode::percent_decode_utf8_lossy, reject,
+};
+
+/// A parsed `sip:` or `sips:` URI.
+///
+/// See [RFC3261](https://tools.ietf.org/html/rfc3261#section-19.1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SipUri<'a> {
+    secure: bool,
+    user: Option<Cow<'a, str>>,
+    password: Option<Cow<'a, str>>,
+    host: &'a str,
+    port: Option<u16>,
+    parameters: Vec<(Cow<'a, str>, Option<Cow<'a, str>>)>,
+    headers: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+/// An error encountered while parsing a `sip:` or `sips:` URI.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum SipUriError {
+    /// The input does not start with `sip:` or `sips:`.
+    InvalidScheme,
+    /// The user or password is malformed.
+    InvalidUserinfo { offset: usize },
+    /// The host is not a valid host name or IP address.
+    InvalidHost { offset: usize },
+    /// The port is not a number from 0 to 65535.
+    InvalidPort { offset: usize },
+    /// A URI parameter is malformed, or unexpected input follows the parameters.
+    InvalidParameter { offset: usize },
+    /// A header is malformed.
+    InvalidHeader { offset: usize },
+}
+
+impl SipUriError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::InvalidUserinfo { .. } => ErrorCode::InvalidUserinfo,
+            Self::InvalidHost { .. } => ErrorCode::InvalidHost,
+            Self::InvalidPort { .. } => ErrorCode::InvalidPort,
+            Self::InvalidParameter { .. } => ErrorCode::InvalidParameter,
+            Self::InvalidHeader { .. } => ErrorCode::InvalidHeader,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::InvalidUserinfo { offset, .. }
+            | Self::InvalidHost { offset, .. }
+            | Self::InvalidPort { offset, .. }
+            | Self::InvalidParameter { offset, .. }
+            | Self::InvalidHeader { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for SipUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a sip: or sips: URI"),
+            Self::InvalidUserinfo { offset, .. } => {
+                write!(f, "invalid userinfo at offset {offset}")
+            }
+            Self::InvalidHost { offset, .. } => write!(f, "invalid host at offset {offset}"),
+            Self::InvalidPort { offset, .. } => write!(f, "invalid port at offset {offset}"),
+            Self::InvalidParameter { offset, .. } => {
+                write!(f, "invalid parameter at offset {offset}")
+            }
+            Self::InvalidHeader { offset, .. } => write!(f, "invalid header at offset {offset}"),
+        }
+    }
+}
+
+impl std::error::Error for SipUriError {}
+
+impl<'a> SipUri<'a> {
+    /// Parse a `sip:` or `sips:` URI, percent-decoding the userinfo, parameters, and headers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `sip:` or `sips:` URI or if any component is
+    /// malformed.
+    pub fn parse(input: &'a str) -> Result<Self, SipUriError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, SipUriError> {
+        let Ok((i, secure)) = parse_sip_scheme(input) else {
+            return Err(SipUriError::InvalidScheme);
+        };
+
+        let userinfo_offset = input.len() - i.len();
+
+        // "@" can only appear in the userinfo, every other component requires it to be escaped
+        let (userinfo, i) = match i.split_once('@') {
+            Some((userinfo, i)) => (Some(userinfo), i),
+            None => (None, i),
+        };
+
+        let (user, password) = match userinfo {
+            Some(userinfo) => {
+                let Ok((_, (user, password))) = all_consuming(parse_userinfo)(userinfo) else {
+                    return Err(SipUriError::InvalidUserinfo {
+                        offset: userinfo_offset,
+                    });
+                };
+                (
+                    Some(percent_decode_utf8_lossy(user)),
+                    password.map(percent_decode_utf8_lossy),
+                )
+            }
+            None => (None, None),
+        };
+
+        let Ok((i, host)) = parse_host(i) else {
+            return Err(SipUriError::InvalidHost {
+                offset: input.len() - i.len(),
+            });
+        };
+
+        let Ok((mut i, port)) = parse_port(i) else {
+            return Err(SipUriError::InvalidPort {
+                offset: input.len() - i.len(),
+            });
+        };
+
+        let mut parameters = Vec::new();
+        while i.starts_with(';') {
+            let Ok((rest, (name, value))) = parse_parameter(i) else {
+                return Err(SipUriError::InvalidParameter {
+                    offset: input.len() - i.len(),
+                });
+            };
+            parameters.push((
+                percent_decode_utf8_lossy(name),
+                value.map(percent_decode_utf8_lossy),
+            ));
+            i = rest;
+        }
+
+        let mut headers = Vec::new();
+        if let Some(mut i) = i.strip_prefix('?') {
+            loop {
+                let Ok((rest, (name, value))) = parse_header(i) else {
+                    return Err(SipUriError::InvalidHeader {
+                        offset: input.len() - i.len(),
+                    });
+                };
+                headers.push((
+                    percent_decode_utf8_lossy(name),
+                    percent_decode_utf8_lossy(value),
+                ));
+
+                match rest.strip_prefix('&') {
+                    Some(rest) => i = rest,
+                    None if rest.is_empty() => break,
+                    None => {
+                        return Err(SipUriError::InvalidHeader {
+                            offset: input.len() - rest.len(),
+                        })
+                    }
+                }
+            }
+        } else if !i.is_empty() {
+            return Err(SipUriError::InvalidParameter {
+                offset: input.len() - i.len(),
+            });
+        }
+
+        Ok(Self {
+            secure,
+            user,
+            password,
+            host,
+            port,
+            parameters,
+            headers,
+        })
+    }
+
+    /// Whether the URI uses the `sips` scheme.
+    #[must_use]
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+
+    /// The user part of the userinfo.
+    #[must_use]
+    pub fn user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// The password part of the userinfo.
+    #[must_use]
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+
+    /// The host, IPv6 addresses include their enclosing brackets.
+    #[must_use]
+    pub fn host(&self) -> &'a str {
+        self.host
+    }
+
+    /// The explicit port of the URI.
+    #[must_use]
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// The URI parameters, in the order they appeared.
+    #[must_use]
+    pub fn parameters(&self) -> &[(Cow<'a, str>, Option<Cow<'a, str>>)] {
+        &self.parameters
+    }
+
+    /// The first parameter matching `name`, compared case-insensitively.
+    ///
+    /// Returns `Some(None)` for parameters without a value such as `lr`.
+    #[must_use]
+    pub fn parameter(&self, name: &'_ str) -> Option<Option<&str>> {
+        self.parameters
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_deref())
+    }
+
+    /// The headers, in the order they appeared.
+    #[must_use]
+    pub fn headers(&self) -> &[(Cow<'a, str>, Cow<'a, str>)] {
+        &self.headers
+    }
+}
+
+impl<'a> TryFrom<&'a str> for SipUri<'a> {
+    type Error = SipUriError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+// unreserved = alphanum / mark
+// mark       = "-" / "_" / "." / "!" / "~" / "*" / "'" / "(" / ")"
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_.!~*'()".contains(c)
+}
+
+// escaped = "%" HEXDIG HEXDIG
+//
+// Escapes are validated by percent decoding
+fn is_unreserved_or_escaped(c: char) -> bool {
+    is_unreserved(c) || c == '%'
+}
+
+// SIP-URI  = "sip:" [ userinfo ] hostport uri-parameters [ headers ]
+// SIPS-URI = "sips:" [ userinfo ] hostport uri-parameters [ headers ]
+fn parse_sip_scheme(i: &'_ str) -> ParseResult<'_, bool> {
+    alt((
+        map(tag_no_case("sips:"), |_| true),
+        map(tag_no_case("sip:"), |_| false),
+    ))(i)
+}
+
+// userinfo        = ( user / telephone-subscriber ) [ ":" password ] "@"
+// user            = 1*( unreserved / escaped / user-unreserved )
+// user-unreserved = "&" / "=" / "+" / "$" / "," / ";" / "?" / "/"
+// password        = *( unreserved / escaped / "&" / "=" / "+" / "$" / "," )
+fn parse_userinfo(i: &'_ str) -> ParseResult<'_, (&'_ str, Option<&'_ str>)> {
+    fn is_user_char(c: char) -> bool {
+        is_unreserved_or_escaped(c) || "&=+$,;?/".contains(c)
+    }
+
+    fn is_password_char(c: char) -> bool {
+        is_unreserved_or_escaped(c) || "&=+$,".contains(c)
+    }
+
+    let (i, user) = take_while1(is_user_char)(i)?;
+    let (i, password) = alt((
+        map(
+            tuple((char(':'), take_while(is_password_char))),
+            |(_, password)| Some(password),
+        ),
+        success(None),
+    ))(i)?;
+
+    Ok((i, (user, password)))
+}
+
+// host          = hostname / IPv4address / IPv6reference
+// hostname      = *( domainlabel "." ) toplabel [ "." ]
+// domainlabel   = alphanum / alphanum *( alphanum / "-" ) alphanum
+// toplabel      = ALPHA / ALPHA *( alphanum / "-" ) alphanum
+// IPv6reference = "[" IPv6address "]"
+fn parse_host(i: &'_ str) -> ParseResult<'_, &'_ str> {
+    fn parse_ipv6_reference(i: &'_ str) -> ParseResult<'_, &'_ str> {
+        recognize(delimited(
+            char('['),
+            map_res(take_until("]"), all_consuming(ipv6::parse)),
+            char(']'),
+        ))(i)
+    }
+
+    fn parse_hostname_or_ipv4(i: &'_ str) -> ParseResult<'_, &'_ str> {
+        fn is_valid_hostname(host: &'_ str) -> bool {
+            let host = host.strip_suffix('.').unwrap_or(host);
+            let toplabel = host.rsplit('.').next().unwrap_or_default();
+
+            toplabel.starts_with(|c: char| c.is_ascii_alphabetic())
+                && host.split('.').all(|label| {
+                    !label.is_empty() && !label.starts_with('-') && !label.ends_with('-')
+                })
+        }
+
+        let (rest, host) =
+            take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.')(i)?;
+
+        if all_consuming(parse_ipv4_dotted_decimal)(host).is_ok() || is_valid_hostname(host) {
+            return Ok((rest, host));
+        }
+
+        fail(i)
+    }
+
+    alt((parse_ipv6_reference, parse_hostname_or_ipv4))(i)
+}
+
+// port = 1*DIGIT
+fn parse_port(i: &'_ str) -> ParseResult<'_, Option<u16>> {
+    opt(preceded(char(':'), cut(map_res(digit1, str::parse::<u16>))))(i)
+}
+
+// uri-parameter    = ";" pname [ "=" pvalue ]
+// pname            = 1*paramchar
+// pvalue           = 1*paramchar
+// paramchar        = param-unreserved / unreserved / escaped
+// param-unreserved = "[" / "]" / "/" / ":" / "&" / "+" / "$"
+fn parse_parameter(i: &'_ str) -> ParseResult<'_, (&'_ str, Option<&'_ str>)> {
+    fn is_paramchar(c: char) -> bool {
+        is_unreserved_or_escaped(c) || "[]/:&+$".contains(c)
+    }
+
+    let (i, _) = char(';')(i)?;
+    let (i, pname) = take_while1(is_paramchar)(i)?;
+    let (i, pvalue) = alt((
+        map(tuple((char('='), take_while1(is_paramchar))), |(_, v)| {
+            Some(v)
+        }),
+        success(None),
+    ))(i)?;
+
+    Ok((i, (pname, pvalue)))
+}
+
+// header          = hname "=" hvalue
+// hname           = 1*( hnv-unreserved / unreserved / escaped )
+// hvalue          = *( hnv-unreserved / unreserved / escaped )
+// hnv-unreserved  = "[" / "]" / "/" / "?" / ":" / "+" / "$"
+fn parse_header(i: &'_ str) -> ParseResult<'_, (&'_ str, &'_ str)> {
+    fn is_hnv_char(c: char) -> bool {
+        is_unreserved_or_escaped(c) || "[]/?:+$".contains(c)
+    }
+
+    let (i, hname) = take_while1(is_hnv_char)(i)?;
+    let (i, _) = char('=')(i)?;
+    let (i, hvalue) = take_while(is_hnv_char)(i)?;
+
+    Ok((i, (hname, hvalue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sip() {
+        let uri = SipUri::parse("sip:alice@atlanta.com").unwrap();
+        assert!(!uri.is_secure());
+        assert_eq!(Some("alice"), uri.user());
+        assert_eq!("atlanta.com", uri.host());
+        assert_eq!(None, uri.port());
+
+        let uri = SipUri::parse("sip:alice:secretword@atlanta.com;transport=tcp").unwrap();
+        assert_eq!(Some("secretword"), uri.password());
+        assert_eq!(Some(Some("tcp")), uri.parameter("Transport"));
+
+        let uri =
+            SipUri::parse("sips:alice@atlanta.com?subject=project%20x&priority=urgent").unwrap();
+        assert!(uri.is_secure());
+        let headers: Vec<_> = uri.headers().iter().map(|(n, v)| (&**n, &**v)).collect();
+        assert_eq!(
+            vec![("subject", "project x"), ("priority", "urgent")],
+            headers
+        );
+
+        let uri = SipUri::parse("sip:+1-212-555-1212:1234@gateway.com;user=phone").unwrap();
+        assert_eq!(Some("+1-212-555-1212"), uri.user());
+        assert_eq!(Some("1234"), uri.password());
+        assert_eq!(Some(Some("phone")), uri.parameter("user"));
+
+        let uri = SipUri::parse("sip:alice;day=tuesday@atlanta.com").unwrap();
+        assert_eq!(Some("alice;day=tuesday"), uri.user());
+
+        let uri = SipUri::parse("sip:atlanta.com;method=REGISTER?to=alice%40atlanta.com").unwrap();
+        assert_eq!(None, uri.user());
+        assert_eq!("alice@atlanta.com", uri.headers()[0].1);
+
+        let uri = SipUri::parse("sip:[2001:db8::10]:5070;lr").unwrap();
+        assert_eq!("[2001:db8::10]", uri.host());
+        assert_eq!(Some(5070), uri.port());
+        assert_eq!(Some(None), uri.parameter("lr"));
+
+        let uri = SipUri::parse("sip:192.0.2.4").unwrap();
+        assert_eq!("192.0.2.4", uri.host());
+    }
+
+    #[test]
+    fn test_parse_sip_invalid() {
+        let test_data: Vec<&'_ str> = vec![
+            "tel:+1-816-555-1212",
+            "sip:alice@",
+            "sip:alice@-atlanta.com",
+            "sip:alice@192.0.2.256",
+            "sip:alice@[2001:db8::10",
+            "sip:alice@atlanta.com:65536",
+            "sip:alice@atlanta.com;=tcp",
+            "sip:alice@atlanta.com?subject",
+            "sip:alice@atlanta.com/path",
+            "sip:al ice@atlanta.com",
+        ];
+
+        for input in test_data {
+            assert!(SipUri::parse(input).is_err(), "{}", input);
+        }
+
+        let err = SipUri::parse("sip:alice@atlanta.com:65536").unwrap_err();
+        assert_eq!(ErrorCode::InvalidPort, err.code());
+        assert_eq!(21, err.offset());
+    }
+}
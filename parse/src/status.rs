@@ -0,0 +1,263 @@
+use std::{fmt, str::FromStr};
+
+use crate::{error::ErrorCode, reject};
+
+/// An HTTP response status code, in the range 100 to 599.
+///
+/// See [RFC9110](https://www.rfc-editor.org/rfc/rfc9110#section-15)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusCode(u16);
+
+/// An error encountered while parsing a status code.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum StatusCodeError {
+    /// The input is not three ASCII digits.
+    InvalidSyntax,
+    /// The status code is outside the range 100 to 599.
+    OutOfRange { status: u16 },
+}
+
+impl StatusCodeError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidSyntax { .. } => ErrorCode::InvalidSyntax,
+            Self::OutOfRange { .. } => ErrorCode::InvalidStatus,
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        0
+    }
+}
+
+impl fmt::Display for StatusCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSyntax => f.write_str("invalid status code"),
+            Self::OutOfRange { status } => {
+                write!(f, "status code {status} is not in the range 100 to 599")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatusCodeError {}
+
+impl StatusCode {
+    /// Create a status code, returning `None` if it is outside the range 100 to 599.
+    #[must_use]
+    pub const fn new(status: u16) -> Option<Self> {
+        if status >= 100 && status <= 599 {
+            Some(Self(status))
+        } else {
+            None
+        }
+    }
+
+    /// The status code as a number.
+    #[must_use]
+    pub const fn as_u16(self) -> u16 {
+        self.0
+    }
+
+    /// Whether the status code is 1xx (Informational).
+    #[must_use]
+    pub const fn is_informational(self) -> bool {
+        self.0 / 100 == 1
+    }
+
+    /// Whether the status code is 2xx (Successful).
+    #[must_use]
+    pub const fn is_success(self) -> bool {
+        self.0 / 100 == 2
+    }
+
+    /// Whether the status code is 3xx (Redirection).
+    #[must_use]
+    pub const fn is_redirect(self) -> bool {
+        self.0 / 100 == 3
+    }
+
+    /// Whether the status code is 4xx (Client Error).
+    #[must_use]
+    pub const fn is_client_error(self) -> bool {
+        self.0 / 100 == 4
+    }
+
+    /// Whether the status code is 5xx (Server Error).
+    #[must_use]
+    pub const fn is_server_error(self) -> bool {
+        self.0 / 100 == 5
+    }
+
+    /// The reason phrase registered for the status code, such as `Not Found` for 404.
+    ///
+    /// Returns `None` for unregistered status codes. The reason phrase carries no meaning, so
+    /// clients should not depend on it matching the one received.
+    #[must_use]
+    pub const fn canonical_reason(self) -> Option<&'static str> {
+        // https://www.iana.org/assignments/http-status-codes/http-status-codes.xhtml
+        Some(match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            102 => "Processing",
+            103 => "Early Hints",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            203 => "Non-Authoritative Information",
+            204 => "No Content",
+            205 => "Reset Content",
+            206 => "Partial Content",
+            207 => "Multi-Status",
+            208 => "Already Reported",
+            226 => "IM Used",
+            300 => "Multiple Choices",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            305 => "Use Proxy",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            402 => "Payment Required",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            407 => "Proxy Authentication Required",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            411 => "Length Required",
+            412 => "Precondition Failed",
+            413 => "Content Too Large",
+            414 => "URI Too Long",
+            415 => "Unsupported Media Type",
+            416 => "Range Not Satisfiable",
+            417 => "Expectation Failed",
+            421 => "Misdirected Request",
+            422 => "Unprocessable Content",
+            423 => "Locked",
+            424 => "Failed Dependency",
+            425 => "Too Early",
+            426 => "Upgrade Required",
+            428 => "Precondition Required",
+            429 => "Too Many Requests",
+            431 => "Request Header Fields Too Large",
+            451 => "Unavailable For Legal Reasons",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            505 => "HTTP Version Not Supported",
+            506 => "Variant Also Negotiates",
+            507 => "Insufficient Storage",
+            508 => "Loop Detected",
+            511 => "Network Authentication Required",
+            _ => return None,
+        })
+    }
+}
+
+impl TryFrom<u16> for StatusCode {
+    type Error = StatusCodeError;
+
+    fn try_from(status: u16) -> Result<Self, Self::Error> {
+        Self::new(status).ok_or(StatusCodeError::OutOfRange { status })
+    }
+}
+
+// status-code = 3DIGIT
+impl FromStr for StatusCode {
+    type Err = StatusCodeError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if input.len() != 3 || !input.bytes().all(|c| c.is_ascii_digit()) {
+            reject::report(input, ErrorCode::InvalidSyntax, 0);
+            return Err(StatusCodeError::InvalidSyntax);
+        }
+
+        let status = input
+            .bytes()
+            .fold(0, |status, c| status * 10 + u16::from(c - b'0'));
+        Self::try_from(status).inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> Self {
+        status.0
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_code() {
+        let test_data = [
+            (100, "Continue", [true, false, false, false, false]),
+            (204, "No Content", [false, true, false, false, false]),
+            (
+                308,
+                "Permanent Redirect",
+                [false, false, true, false, false],
+            ),
+            (404, "Not Found", [false, false, false, true, false]),
+            (599, "", [false, false, false, false, true]),
+        ];
+
+        for (input, reason, class) in test_data {
+            let status = StatusCode::try_from(input).unwrap();
+            assert_eq!(input, status.as_u16());
+            assert_eq!(input.to_string(), status.to_string());
+            assert_eq!(
+                class,
+                [
+                    status.is_informational(),
+                    status.is_success(),
+                    status.is_redirect(),
+                    status.is_client_error(),
+                    status.is_server_error(),
+                ]
+            );
+            assert_eq!(reason, status.canonical_reason().unwrap_or_default());
+        }
+    }
+
+    #[test]
+    fn test_parse_status_code() {
+        assert_eq!(StatusCode::new(200), "200".parse().ok());
+        assert_eq!(StatusCode::new(599), "599".parse().ok());
+
+        let test_data: Vec<&'_ str> = vec!["", "20", "2000", "+20", " 200", "abc", "099", "600"];
+
+        for input in test_data {
+            assert!(input.parse::<StatusCode>().is_err(), "{input}");
+        }
+
+        let err = "600".parse::<StatusCode>().unwrap_err();
+        assert_eq!(ErrorCode::InvalidStatus, err.code());
+        assert_eq!(
+            "status code 600 is not in the range 100 to 599",
+            err.to_string()
+        );
+        assert!(StatusCode::try_from(99).is_err());
+    }
+}
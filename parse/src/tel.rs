@@ -0,0 +1,381 @@
+use std::{borrow::Cow, fmt};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag_no_case, take_while, take_while1},
+    character::complete::char,
+    combinator::{consumed, map, success, verify},
+    sequence::{preceded, tuple},
+};
+
+use crate::{error::ErrorCode, parse::ParseResult, reject};
+
+/// A parsed `tel:` URI.
+///
+/// See [RFC3966](https://tools.ietf.org/html/rfc3966)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelUri<'a> {
+    number: &'a str,
+    global: bool,
+    extension: Option<&'a str>,
+    isdn_subaddress: Option<&'a str>,
+    phone_context: Option<&'a str>,
+    parameters: Vec<(&'a str, Option<&'a str>)>,
+}
+
+/// An error encountered while parsing a `tel:` URI.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum TelUriError {
+    /// The input does not start with `tel:`.
+    InvalidScheme,
+    /// The number is not a valid global or local telephone number.
+    InvalidNumber { offset: usize },
+    /// A parameter is malformed, or an `ext` or `phone-context` value is invalid.
+    InvalidParameter { offset: usize },
+    /// A local number has no `phone-context` parameter.
+    MissingPhoneContext { offset: usize },
+}
+
+impl TelUriError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidScheme => ErrorCode::InvalidScheme,
+            Self::InvalidNumber { .. } => ErrorCode::InvalidPath,
+            Self::InvalidParameter { .. } | Self::MissingPhoneContext { .. } => {
+                ErrorCode::InvalidParameter
+            }
+        }
+    }
+
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme => 0,
+            Self::InvalidNumber { offset, .. }
+            | Self::InvalidParameter { offset, .. }
+            | Self::MissingPhoneContext { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TelUriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme => f.write_str("not a tel: URI"),
+            Self::InvalidNumber { offset, .. } => {
+                write!(f, "invalid telephone number at offset {offset}")
+            }
+            Self::InvalidParameter { offset, .. } => {
+                write!(f, "invalid parameter at offset {offset}")
+            }
+            Self::MissingPhoneContext { .. } => {
+                f.write_str("local number without a phone-context parameter")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TelUriError {}
+
+impl<'a> TelUri<'a> {
+    /// Parse a `tel:` URI.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a `tel:` URI, if the number or any parameter is
+    /// malformed, or if a local number has no `phone-context` parameter.
+    pub fn parse(input: &'a str) -> Result<Self, TelUriError> {
+        Self::parse_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn parse_unreported(input: &'a str) -> Result<Self, TelUriError> {
+        let Ok((i, _)) = tag_no_case::<_, _, ()>("tel:")(input) else {
+            return Err(TelUriError::InvalidScheme);
+        };
+
+        let Ok((mut i, (number, global))) = parse_telephone_subscriber(i) else {
+            return Err(TelUriError::InvalidNumber {
+                offset: input.len() - i.len(),
+            });
+        };
+
+        let mut uri = Self {
+            number,
+            global,
+            extension: None,
+            isdn_subaddress: None,
+            phone_context: None,
+            parameters: Vec::new(),
+        };
+
+        while !i.is_empty() {
+            let Ok((rest, (name, value))) = parse_parameter(i) else {
+                return Err(TelUriError::InvalidParameter {
+                    offset: input.len() - i.len(),
+                });
+            };
+
+            match (name, value) {
+                (name, Some(value)) if name.eq_ignore_ascii_case("ext") => {
+                    if !is_phonedigits(value) {
+                        return Err(TelUriError::InvalidParameter {
+                            offset: input.len() - i.len(),
+                        });
+                    }
+                    uri.extension = Some(value);
+                }
+                (name, Some(value)) if name.eq_ignore_ascii_case("isub") => {
+                    uri.isdn_subaddress = Some(value);
+                }
+                (name, Some(value)) if name.eq_ignore_ascii_case("phone-context") => {
+                    if !is_descriptor(value) {
+                        return Err(TelUriError::InvalidParameter {
+                            offset: input.len() - i.len(),
+                        });
+                    }
+                    uri.phone_context = Some(value);
+                }
+                (name, value) => uri.parameters.push((name, value)),
+            }
+
+            i = rest;
+        }
+
+        // A local number MUST have a phone-context parameter
+        if !uri.global && uri.phone_context.is_none() {
+            return Err(TelUriError::MissingPhoneContext {
+                offset: input.len(),
+            });
+        }
+
+        Ok(uri)
+    }
+
+    /// The number as it appeared in the URI, including any visual separators.
+    #[must_use]
+    pub fn number(&self) -> &'a str {
+        self.number
+    }
+
+    /// The number with visual separators removed.
+    #[must_use]
+    pub fn digits(&self) -> Cow<'a, str> {
+        if !self.number.contains(is_visual_separator) {
+            return Cow::Borrowed(self.number);
+        }
+
+        Cow::Owned(
+            self.number
+                .chars()
+                .filter(|&c| !is_visual_separator(c))
+                .collect(),
+        )
+    }
+
+    /// The number in E.164 form (`+` followed by up to fifteen digits).
+    ///
+    /// Returns `None` for local numbers, which are not globally unique.
+    #[must_use]
+    pub fn e164(&self) -> Option<Cow<'a, str>> {
+        let digits = self.digits();
+
+        if !self.global || !matches!(digits.len() - 1, 1..=15) {
+            return None;
+        }
+
+        Some(digits)
+    }
+
+    /// Whether the number is a global number, prefixed with `+`.
+    #[must_use]
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
+
+    /// The `ext` parameter.
+    #[must_use]
+    pub fn extension(&self) -> Option<&'a str> {
+        self.extension
+    }
+
+    /// The `isub` parameter.
+    #[must_use]
+    pub fn isdn_subaddress(&self) -> Option<&'a str> {
+        self.isdn_subaddress
+    }
+
+    /// The `phone-context` parameter, either a domain name or global number digits.
+    #[must_use]
+    pub fn phone_context(&self) -> Option<&'a str> {
+        self.phone_context
+    }
+
+    /// Any parameters other than `ext`, `isub`, and `phone-context`, in the order they appeared.
+    #[must_use]
+    pub fn parameters(&self) -> &[(&'a str, Option<&'a str>)] {
+        &self.parameters
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TelUri<'a> {
+    type Error = TelUriError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+// visual-separator = "-" / "." / "(" / ")"
+fn is_visual_separator(c: char) -> bool {
+    c == '-' || c == '.' || c == '(' || c == ')'
+}
+
+// phonedigit = DIGIT / [ visual-separator ]
+fn is_phonedigit(c: char) -> bool {
+    c.is_ascii_digit() || is_visual_separator(c)
+}
+
+// phonedigit-hex = HEXDIG / "*" / "#" / [ visual-separator ]
+fn is_phonedigit_hex(c: char) -> bool {
+    c.is_ascii_hexdigit() || c == '*' || c == '#' || is_visual_separator(c)
+}
+
+// 1*phonedigit containing at least one DIGIT
+fn is_phonedigits(i: &'_ str) -> bool {
+    i.chars().all(is_phonedigit) && i.chars().any(|c| c.is_ascii_digit())
+}
+
+// descriptor = domainname / global-number-digits
+fn is_descriptor(i: &'_ str) -> bool {
+    if let Some(digits) = i.strip_prefix('+') {
+        return is_phonedigits(digits);
+    }
+
+    // domainname = *( domainlabel "." ) toplabel [ "." ]
+    let domain = i.strip_suffix('.').unwrap_or(i);
+    !domain.is_empty()
+        && domain.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+// telephone-subscriber = global-number-digits / local-number-digits
+// global-number-digits = "+" *phonedigit DIGIT *phonedigit
+// local-number-digits  = *phonedigit-hex (HEXDIG / "*" / "#") *phonedigit-hex
+fn parse_telephone_subscriber(i: &'_ str) -> ParseResult<'_, (&'_ str, bool)> {
+    let global_number_digits = map(
+        consumed(preceded(
+            char('+'),
+            verify(take_while(is_phonedigit), |digits: &str| {
+                digits.chars().any(|c| c.is_ascii_digit())
+            }),
+        )),
+        |(number, _)| (number, true),
+    );
+
+    let local_number_digits = map(
+        verify(take_while1(is_phonedigit_hex), |digits: &str| {
+            !digits.chars().all(is_visual_separator)
+        }),
+        |number| (number, false),
+    );
+
+    alt((global_number_digits, local_number_digits))(i)
+}
+
+// parameter = ";" pname ["=" pvalue ]
+// pname     = 1*( alphanum / "-" )
+// pvalue    = 1*paramchar
+// paramchar = param-unreserved / unreserved / pct-encoded
+fn parse_parameter(i: &'_ str) -> ParseResult<'_, (&'_ str, Option<&'_ str>)> {
+    fn is_pname_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '-'
+    }
+
+    // param-unreserved = "[" / "]" / "/" / ":" / "&" / "+" / "$"
+    // unreserved       = alphanum / mark
+    // mark             = "-" / "_" / "." / "!" / "~" / "*" / "'" / "(" / ")"
+    fn is_paramchar(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '%' || "[]/:&+$-_.!~*'()".contains(c)
+    }
+
+    let (i, _) = char(';')(i)?;
+    let (i, pname) = take_while1(is_pname_char)(i)?;
+    let (i, pvalue) = alt((
+        map(tuple((char('='), take_while1(is_paramchar))), |(_, v)| {
+            Some(v)
+        }),
+        success(None),
+    ))(i)?;
+
+    Ok((i, (pname, pvalue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tel() {
+        let uri = TelUri::parse("tel:+1-816-555-1212").unwrap();
+        assert!(uri.is_global());
+        assert_eq!("+1-816-555-1212", uri.number());
+        assert_eq!("+18165551212", uri.digits());
+        assert_eq!(Some(Cow::Borrowed("+18165551212")), uri.e164());
+
+        let uri = TelUri::parse("tel:7042;phone-context=example.com").unwrap();
+        assert!(!uri.is_global());
+        assert_eq!(Some("example.com"), uri.phone_context());
+        assert_eq!(None, uri.e164());
+
+        let uri =
+            TelUri::parse("tel:863-1234;phone-context=+1-914-555;ext=22;foo=bar;baz").unwrap();
+        assert_eq!("8631234", uri.digits());
+        assert_eq!(Some("+1-914-555"), uri.phone_context());
+        assert_eq!(Some("22"), uri.extension());
+        assert_eq!(&[("foo", Some("bar")), ("baz", None)], uri.parameters());
+
+        let uri = TelUri::parse("TEL:+(1)212.555.0100;isub=1411").unwrap();
+        assert_eq!(Some("1411"), uri.isdn_subaddress());
+        assert_eq!(Some(Cow::Borrowed("+12125550100")), uri.e164());
+    }
+
+    #[test]
+    fn test_parse_tel_invalid() {
+        let test_data: Vec<&'_ str> = vec![
+            "sip:+1-816-555-1212",
+            "tel:+",
+            "tel:+--",
+            "tel:+1 816",
+            "tel:7042",
+            "tel:7042;phone-context=",
+            "tel:+1-816-555-1212;ext=abc",
+            "tel:+1-816-555-1212;=x",
+        ];
+
+        for input in test_data {
+            assert!(TelUri::parse(input).is_err());
+        }
+
+        let err = TelUri::parse("tel:+1-816-555-1212;ext=abc").unwrap_err();
+        assert_eq!(ErrorCode::InvalidParameter, err.code());
+        assert_eq!(19, err.offset());
+        assert_eq!("invalid parameter at offset 19", err.to_string());
+    }
+
+    #[test]
+    fn test_try_from_tel() {
+        fn parse<'a, T: TryFrom<&'a str>>(input: &'a str) -> Result<T, T::Error> {
+            T::try_from(input)
+        }
+
+        let uri: TelUri = parse("tel:+1-201-555-0123").unwrap();
+        assert_eq!(TelUri::parse("tel:+1-201-555-0123").unwrap(), uri);
+        assert!(parse::<TelUri>("tel:7042").is_err());
+    }
+}
@@ -0,0 +1,11 @@
+//! Parsers for scheme-specific URIs.
+
+pub use crate::{
+    authority::authority_str,
+    iri::{iri_to_uri, IriError},
+    literal::{is_valid_uri, UriLiteral},
+    magnet::{ExactTopic, MagnetError, MagnetLink},
+    mailto::{MailtoUri, MailtoUriError},
+    sip::{SipUri, SipUriError},
+    tel::{TelUri, TelUriError},
+};
@@ -1,38 +1,54 @@
-#![allow(dead_code)]
+//! Parsers and helpers for URLs.
 
-use std::{borrow::Cow, marker::PhantomData};
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{PoisonError, RwLock},
+};
 
 use nom::{
     branch::alt,
-    bytes::complete::{take_while, take_while_m_n},
-    character::complete::char,
-    combinator::{consumed, map, success},
+    bytes::complete::{tag, take_while, take_while_m_n},
+    character::complete::one_of,
+    combinator::{consumed, map},
     sequence::tuple,
 };
 
-use crate::{
-    parse::ParseResult,
-    percent_encode::{is_userinfo_percent_encode, percent_encode},
-};
-
-struct Url<'a> {
-    _tag: PhantomData<&'a ()>,
-}
+use crate::{error::ErrorCode, parse::ParseResult, reject};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Authority<'a> {
-    username: Cow<'a, str>,
-    password: Option<Cow<'a, str>>,
-}
+#[cfg(feature = "idna")]
+pub use crate::display::{assess_display_risk, DisplayRisk};
+pub use crate::{
+    data_url::{DataUrl, DataUrlError},
+    file_url::{FileUrl, FileUrlError},
+    host::{
+        classify_host, host_matches_dns_name, is_potentially_trustworthy_origin, Host, HostError,
+        HostKind,
+    },
+    ipv4::Ipv4Host,
+    media_fragment::{MediaFragment, SpatialRegion, SpatialUnit, TimeRange},
+    pac::{
+        dns_domain_is, dns_domain_levels, host_dns_domain_is, is_in_net, is_plain_host_name,
+        local_host_or_domain_is, sh_exp_match, Ipv4Net,
+    },
+    path::{append_path_segments, remove_dot_segments, AboveRoot, PathError},
+    percent_encode::{
+        percent_decode, percent_decode_str, percent_encode, validate_component, AsciiSet,
+        Component, DecodePolicy, InvalidByte, PercentDecodeError,
+    },
+    query::{ChunkPairs, DuplicatePolicy, QueryMap, QueryPairParser, QueryPairs},
+    scp::{ScpLikeUrl, ScpLikeUrlError},
+};
 
 // An ASCII upper alpha is a code point in the range U+0041 (A) to U+005A (Z), inclusive.
 fn is_ascii_upper_alpha(c: char) -> bool {
-    matches!(c, '\u{41}'..='\u{5A}')
+    c.is_ascii_uppercase()
 }
 
 // An ASCII lower alpha is a code point in the range U+0061 (a) to U+007A (z), inclusive.
 fn is_ascii_lower_alpha(c: char) -> bool {
-    matches!(c, '\u{61}'..='\u{7A}')
+    c.is_ascii_lowercase()
 }
 
 // An ASCII alpha is an ASCII upper alpha or ASCII lower alpha.
@@ -42,7 +58,7 @@ fn is_ascii_alpha(c: char) -> bool {
 
 // An ASCII digit is a code point in the range U+0030 (0) to U+0039 (9), inclusive.
 fn is_ascii_digit(c: char) -> bool {
-    matches!(c, '\u{30}'..='\u{39}')
+    c.is_ascii_digit()
 }
 
 // An ASCII alphanumeric is an ASCII digit or ASCII alpha.
@@ -50,33 +66,7 @@ fn is_ascii_alphanumeric(c: char) -> bool {
     is_ascii_alpha(c) || is_ascii_digit(c)
 }
 
-// The URL code points are ASCII alphanumeric, U+0021 (!), U+0024 ($), U+0026 (&), U+0027 ('),
-// U+0028 LEFT PARENTHESIS, U+0029 RIGHT PARENTHESIS, U+002A (*), U+002B (+), U+002C (,), U+002D (-),
-// U+002E (.), U+002F (/), U+003A (:), U+003B (;), U+003D (=), U+003F (?), U+0040 (@), U+005F (_),
-// U+007E (~), and code points in the range U+00A0 to U+10FFFD, inclusive, excluding surrogates and noncharacters.
-fn is_url_code_point(c: char) -> bool {
-    c.is_ascii_alphanumeric()
-        || c == '!'
-        || c == '$'
-        || c == '&'
-        || c == '\''
-        || c == '.'
-        || c == '/'
-        || c == ':'
-        || c == ';'
-        || c == '='
-        || c == '?'
-        || c == '@'
-        || c == '_'
-        || c == '~'
-        || matches!(c, '\u{A0}'..='\u{10FFFD}')
-}
-
-fn is_scheme_special(c: &'_ str) -> bool {
-    c == "ftp" || c == "file" || c == "http" || c == "https" || c == "ws" || c == "wss"
-}
-
-fn parse_scheme(i: &'_ str) -> ParseResult<Cow<'_, str>> {
+fn parse_scheme(i: &'_ str) -> ParseResult<'_, Scheme<'_>> {
     fn is_valid_scheme_char(c: char) -> bool {
         is_ascii_alphanumeric(c) || c == '+' || c == '-' || c == '.'
     }
@@ -86,45 +76,495 @@ fn parse_scheme(i: &'_ str) -> ParseResult<Cow<'_, str>> {
         take_while(is_valid_scheme_char),
     )))(i)?;
 
-    if scheme.chars().any(is_ascii_upper_alpha) {
-        return Ok((i, Cow::Owned(scheme.to_lowercase())));
+    Ok((i, Scheme { input: scheme }))
+}
+
+/// A URL scheme.
+///
+/// Schemes are ASCII case-insensitive, so comparing and hashing ignore case, but the scheme is
+/// kept as it was written and only lowercased when asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct Scheme<'a> {
+    input: &'a str,
+}
+
+impl<'a> Scheme<'a> {
+    /// Parse a scheme, without its trailing `:`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not an ASCII alpha followed by ASCII alphanumerics, `+`,
+    /// `-`, and `.`.
+    pub fn parse(input: &'a str) -> Result<Self, SchemeError> {
+        match parse_scheme(input) {
+            Ok(("", scheme)) => Ok(scheme),
+            result => {
+                // The first character that can not be part of a scheme
+                let offset = result.map_or(0, |(rest, _)| input.len() - rest.len());
+                reject::report(input, ErrorCode::InvalidScheme, offset);
+                Err(SchemeError::InvalidScheme { offset })
+            }
+        }
+    }
+
+    /// The scheme as it was written.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.input
+    }
+
+    /// The scheme in lowercase, only allocating if it contains uppercase letters.
+    #[must_use]
+    pub fn as_lowercase(&self) -> Cow<'a, str> {
+        if self.input.chars().any(is_ascii_upper_alpha) {
+            return Cow::Owned(self.input.to_ascii_lowercase());
+        }
+
+        Cow::Borrowed(self.input)
+    }
+
+    /// Whether this is one of the special schemes, whose URLs are parsed differently.
+    #[must_use]
+    pub fn is_special(&self) -> bool {
+        ["ftp", "file", "http", "https", "ws", "wss"]
+            .iter()
+            .any(|special| self == special)
     }
 
-    // TODO: username and password should be percent encoded
-    Ok((i, Cow::Borrowed(scheme)))
+    /// The port used when a URL with this scheme does not specify one.
+    ///
+    /// The special schemes use their registered ports, `ftp` 21, `http` and `ws` 80, and `https`
+    /// and `wss` 443, while `file` has none. Other schemes use the port given to
+    /// [`register_default_port`], if any.
+    #[must_use]
+    pub fn default_port(&self) -> Option<u16> {
+        if self.is_special() {
+            return [
+                ("ftp", 21),
+                ("http", 80),
+                ("https", 443),
+                ("ws", 80),
+                ("wss", 443),
+            ]
+            .iter()
+            .find(|(scheme, _)| self == scheme)
+            .map(|&(_, port)| port);
+        }
+
+        DEFAULT_PORTS
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(scheme, _)| self == scheme.as_str())
+            .map(|&(_, port)| port)
+    }
 }
 
-fn parse_authority(url_is_special: bool) -> impl FnMut(&'_ str) -> ParseResult<Authority> {
-    fn is_valid_authority_char(url_is_special: bool, c: char) -> bool {
-        if url_is_special && c == '\\' {
-            return false;
+impl<'a> TryFrom<&'a str> for Scheme<'a> {
+    type Error = SchemeError;
+
+    fn try_from(input: &'a str) -> Result<Self, Self::Error> {
+        Self::parse(input)
+    }
+}
+
+static DEFAULT_PORTS: RwLock<Vec<(String, u16)>> = RwLock::new(Vec::new());
+
+/// Register the default port of a scheme, returned by [`Scheme::default_port`].
+///
+/// The registry is process wide, and registering a scheme again replaces its port.
+///
+/// ```
+/// use parse::url::{register_default_port, Scheme};
+///
+/// register_default_port("gopher", 70).unwrap();
+/// assert_eq!(Some(70), Scheme::parse("gopher").unwrap().default_port());
+/// ```
+///
+/// # Errors
+///
+/// Returns an error if the scheme is not valid, or if it is one of the special schemes such as
+/// `http`, whose ports can not be changed.
+pub fn register_default_port(scheme: &str, port: u16) -> Result<(), SchemeError> {
+    let scheme = Scheme::parse(scheme)?;
+    if scheme.is_special() {
+        return Err(SchemeError::SchemeNotAllowed {
+            offset: 0,
+            scheme: scheme.as_lowercase().into_owned(),
+        });
+    }
+
+    let mut ports = DEFAULT_PORTS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    match ports
+        .iter_mut()
+        .find(|(registered, _)| scheme == registered.as_str())
+    {
+        Some((_, registered_port)) => *registered_port = port,
+        None => ports.push((scheme.as_lowercase().into_owned(), port)),
+    }
+
+    Ok(())
+}
+
+impl PartialEq for Scheme<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.input.eq_ignore_ascii_case(other.input)
+    }
+}
+
+impl Eq for Scheme<'_> {}
+
+impl PartialEq<str> for Scheme<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.input.eq_ignore_ascii_case(other)
+    }
+}
+
+impl PartialEq<&str> for Scheme<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.input.eq_ignore_ascii_case(other)
+    }
+}
+
+impl Hash for Scheme<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for c in self.input.bytes() {
+            state.write_u8(c.to_ascii_lowercase());
         }
+        // Terminate the scheme the same way str does, so it cannot run into the next value
+        state.write_u8(0xFF);
+    }
+}
+
+impl fmt::Display for Scheme<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.input)
+    }
+}
+
+/// Options controlling which URLs are accepted.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions<'a> {
+    allowed_schemes: Option<&'a [&'a str]>,
+}
+
+/// An error encountered while parsing or checking a scheme.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum SchemeError {
+    /// The input does not start with a scheme, or the scheme is not followed by `:`.
+    InvalidScheme { offset: usize },
+    /// The scheme is not one of the allowed schemes, or is a special scheme given to
+    /// [`register_default_port`]; the scheme is lowercased.
+    SchemeNotAllowed { offset: usize, scheme: String },
+}
 
-        c != '/' && c != '?' && c != '#' && c != ':'
+impl SchemeError {
+    /// The class of the error.
+    #[must_use]
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::InvalidScheme
     }
 
-    move |i| {
-        let (i, username) =
-            take_while::<_, &'_ str, _>(|c| is_valid_authority_char(url_is_special, c))(i)?;
+    /// The byte offset in the input at which the error was detected.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::InvalidScheme { offset } | Self::SchemeNotAllowed { offset, .. } => *offset,
+        }
+    }
+}
 
-        let (i, password) = alt((
-            map(
-                tuple((
-                    char(':'),
-                    take_while(|c| is_valid_authority_char(url_is_special, c)),
-                )),
-                |(_, password)| Some(password),
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidScheme { offset } => write!(f, "invalid scheme at offset {offset}"),
+            Self::SchemeNotAllowed { offset, scheme } => {
+                write!(f, "scheme `{scheme}` at offset {offset} is not allowed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemeError {}
+
+impl<'a> ParseOptions<'a> {
+    /// Options that accept every URL.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accept URLs with one of these schemes, compared ASCII case-insensitively.
+    #[must_use]
+    pub fn allowed_schemes(mut self, schemes: &'a [&'a str]) -> Self {
+        self.allowed_schemes = Some(schemes);
+        self
+    }
+
+    /// Check the scheme at the start of the input against the allowed schemes.
+    ///
+    /// Only the scheme is examined, so disallowed URLs are rejected before any further work is
+    /// done to parse them. Leading C0 controls and spaces are skipped, as the URL parser strips
+    /// them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input does not start with a scheme followed by `:`, or if the
+    /// scheme is not allowed.
+    pub fn check_scheme(&self, input: &str) -> Result<(), SchemeError> {
+        self.check_scheme_unreported(input)
+            .inspect_err(|err| reject::report(input, err.code(), err.offset()))
+    }
+
+    fn check_scheme_unreported(&self, input: &str) -> Result<(), SchemeError> {
+        let Some(allowed_schemes) = self.allowed_schemes else {
+            return Ok(());
+        };
+
+        let trimmed = input.trim_start_matches(|c: char| c.is_ascii_control() || c == ' ');
+        let start = input.len() - trimmed.len();
+        let Ok((rest, scheme)) = parse_scheme(trimmed) else {
+            return Err(SchemeError::InvalidScheme { offset: start });
+        };
+        if !rest.starts_with(':') {
+            return Err(SchemeError::InvalidScheme {
+                offset: input.len() - rest.len(),
+            });
+        }
+
+        if !allowed_schemes.iter().any(|allowed| scheme == *allowed) {
+            return Err(SchemeError::SchemeNotAllowed {
+                offset: start,
+                scheme: scheme.as_lowercase().into_owned(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// The transfer type requested by the `;type=` suffix of an `ftp` URL path.
+///
+/// See [RFC1738](https://tools.ietf.org/html/rfc1738#section-3.2.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FtpTypeCode {
+    /// `;type=a`, transfer the file as ASCII text.
+    Ascii,
+    /// `;type=i`, transfer the file as a binary image.
+    Image,
+    /// `;type=d`, list the contents of the directory.
+    Directory,
+}
+
+impl FtpTypeCode {
+    /// The typecode as it should appear after `;type=`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ascii => "a",
+            Self::Image => "i",
+            Self::Directory => "d",
+        }
+    }
+}
+
+// ftptype = "A" | "I" | "D" | "a" | "i" | "d"
+fn parse_ftp_type_code(i: &'_ str) -> ParseResult<'_, FtpTypeCode> {
+    let (i, _) = tag(";type=")(i)?;
+
+    alt((
+        map(one_of("Aa"), |_| FtpTypeCode::Ascii),
+        map(one_of("Ii"), |_| FtpTypeCode::Image),
+        map(one_of("Dd"), |_| FtpTypeCode::Directory),
+    ))(i)
+}
+
+/// Split the `;type=` typecode off the last segment of an `ftp` URL path.
+///
+/// Returns the path without the typecode and the typecode if one was present. Paths with a
+/// malformed typecode are returned unchanged.
+#[must_use]
+pub fn ftp_type_code(path: &'_ str) -> (&'_ str, Option<FtpTypeCode>) {
+    // The typecode can only appear in the last segment of the path
+    let segment_start = path.rfind('/').map_or(0, |n| n + 1);
+
+    if let Some(n) = path[segment_start..].rfind(';') {
+        let split = segment_start + n;
+        if let Ok(("", type_code)) = parse_ftp_type_code(&path[split..]) {
+            return (&path[..split], Some(type_code));
+        }
+    }
+
+    (path, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_no_alloc::assert_no_alloc;
+
+    #[test]
+    fn test_ftp_type_code() {
+        let test_data: Vec<((&'_ str, Option<FtpTypeCode>), &'_ str)> = vec![
+            (
+                ("/pub/file.txt", Some(FtpTypeCode::Ascii)),
+                "/pub/file.txt;type=a",
+            ),
+            (
+                ("/pub/file.bin", Some(FtpTypeCode::Image)),
+                "/pub/file.bin;type=I",
             ),
-            success(None),
-        ))(i)?;
+            (("/pub/", Some(FtpTypeCode::Directory)), "/pub/;type=d"),
+            (("/pub/file.txt", None), "/pub/file.txt"),
+            (("/pub/file.txt;type=x", None), "/pub/file.txt;type=x"),
+            (("/pub/file.txt;type=aa", None), "/pub/file.txt;type=aa"),
+            (("/pub;type=a/file.txt", None), "/pub;type=a/file.txt"),
+        ];
 
-        let (i, _) = char('@')(i)?;
+        for (expected, input) in test_data {
+            assert_eq!(expected, assert_no_alloc(|| ftp_type_code(input)));
+        }
+    }
 
-        let username = percent_encode(Cow::Borrowed(username), false, is_userinfo_percent_encode);
-        let password = password
-            .map(Cow::Borrowed)
-            .map(|p| percent_encode(p, false, is_userinfo_percent_encode));
+    #[test]
+    fn test_scheme() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(scheme: &Scheme) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            scheme.hash(&mut hasher);
+            hasher.finish()
+        }
 
-        Ok((i, Authority { username, password }))
+        let test_data = [
+            ("http", "http", true),
+            ("HTTPS", "https", true),
+            ("Git+SSH", "git+ssh", false),
+            ("a1.b-c", "a1.b-c", false),
+        ];
+
+        for (input, lowercase, special) in test_data {
+            let scheme = Scheme::parse(input).unwrap();
+            assert_eq!(input, scheme.as_str());
+            assert_eq!(input, scheme.to_string());
+            assert_eq!(lowercase, scheme.as_lowercase());
+            assert_eq!(special, scheme.is_special());
+            assert_eq!(scheme, lowercase);
+            assert_eq!(scheme, Scheme::parse(lowercase).unwrap());
+            assert_eq!(hash(&scheme), hash(&Scheme::parse(lowercase).unwrap()));
+        }
+
+        assert_ne!(Scheme::parse("http").unwrap(), "https");
+        assert!(matches!(
+            Scheme::parse("HTTP").unwrap().as_lowercase(),
+            Cow::Owned(_)
+        ));
+        assert!(matches!(
+            assert_no_alloc(|| Scheme::parse("http").unwrap().as_lowercase()),
+            Cow::Borrowed(_)
+        ));
+
+        let test_data = [("", 0), ("1http", 0), ("ht tp", 2), ("http:", 4), ("-x", 0)];
+
+        for (input, offset) in test_data {
+            assert_eq!(
+                offset,
+                Scheme::parse(input).unwrap_err().offset(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_port() {
+        let test_data = [
+            ("http", Some(80)),
+            ("HTTPS", Some(443)),
+            ("ws", Some(80)),
+            ("wss", Some(443)),
+            ("ftp", Some(21)),
+            ("file", None),
+            ("x-test-unregistered", None),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(
+                expected,
+                Scheme::parse(input).unwrap().default_port(),
+                "{input}"
+            );
+        }
+
+        register_default_port("X-Test-Registered", 1234).unwrap();
+        assert_eq!(
+            Some(1234),
+            Scheme::parse("x-test-registered").unwrap().default_port()
+        );
+        register_default_port("x-test-registered", 4321).unwrap();
+        assert_eq!(
+            Some(4321),
+            Scheme::parse("X-TEST-REGISTERED").unwrap().default_port()
+        );
+
+        assert!(matches!(
+            register_default_port("HTTP", 8080),
+            Err(SchemeError::SchemeNotAllowed { scheme, .. }) if scheme == "http"
+        ));
+        assert!(register_default_port("file", 8080).is_err());
+        assert!(register_default_port("", 8080).is_err());
+        assert!(register_default_port("x test", 8080).is_err());
+        assert_eq!(Some(80), Scheme::parse("http").unwrap().default_port());
+        assert_eq!(None, Scheme::parse("file").unwrap().default_port());
+    }
+
+    #[test]
+    fn test_check_scheme() {
+        let options = ParseOptions::new().allowed_schemes(&["http", "https"]);
+
+        let test_data = [
+            ("https://example.com", true),
+            ("HTTP://example.com", true),
+            (" \thttp://example.com", true),
+            ("http:example.com", true),
+            ("javascript:alert(1)", false),
+            ("JavaScript:alert(1)", false),
+            ("file:///etc/passwd", false),
+            ("https+x://example.com", false),
+            ("//example.com", false),
+            ("/relative", false),
+            ("https", false),
+            ("", false),
+        ];
+
+        for (input, expected) in test_data {
+            assert_eq!(expected, options.check_scheme(input).is_ok(), "{input}");
+        }
+
+        assert!(matches!(
+            options.check_scheme("javascript:alert(1)"),
+            Err(SchemeError::SchemeNotAllowed { scheme, .. }) if scheme == "javascript"
+        ));
+
+        let test_data = [
+            ("javascript:alert(1)", 0),
+            (" \t javascript:alert(1)", 3),
+            ("\0https", 6),
+            ("  1https:", 2),
+            ("", 0),
+        ];
+
+        for (input, offset) in test_data {
+            assert_eq!(
+                offset,
+                options.check_scheme(input).unwrap_err().offset(),
+                "{input}"
+            );
+        }
+        assert!(ParseOptions::new().check_scheme("anything").is_ok());
+        assert_no_alloc(|| assert!(options.check_scheme("https://example.com").is_ok()));
     }
 }
@@ -0,0 +1,336 @@
+// Allocation budgets for the public parsers.
+//
+// Every input in the corpus is parsed with a counting allocator installed, and the number of
+// allocations is checked against the budget for that input. A budget of 0 marks a fast path that
+// must never allocate, raising any other budget should be a deliberate decision made in review.
+//
+// The URL and header corpora in tests/corpus hold common URL shapes and the request header fields
+// browsers send, and their budgets are worked out from the shape of each input.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    fs,
+    path::PathBuf,
+};
+
+use parse::{form_urlencoded, http, uri, url};
+
+struct CountingAllocator;
+
+thread_local! {
+    // Counted per thread so that tests running in parallel do not see each other's allocations
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static A: CountingAllocator = CountingAllocator;
+
+// The number of allocations made by f, including any made while dropping its result
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    drop(f());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn check(corpus: &[(&str, usize)], parse: impl Fn(&str) -> bool) {
+    for &(input, budget) in corpus {
+        let allocations = count_allocations(|| parse(input));
+        assert!(
+            allocations <= budget,
+            "{input}: {allocations} allocations, budget is {budget}"
+        );
+    }
+}
+
+// The inputs in a file in tests/corpus, skipping blank lines and comments
+fn corpus(name: &str) -> Vec<String> {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "corpus", name]
+        .iter()
+        .collect();
+
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect()
+}
+
+// The header fields in the header corpus, as name and value
+fn header_fields(lines: &[String]) -> impl Iterator<Item = (&str, &str)> {
+    lines.iter().map(|line| line.split_once(": ").unwrap())
+}
+
+// The host of a URL with an authority, without any userinfo or port
+fn url_host(url: &str) -> Option<&str> {
+    let authority = uri::authority_str(url)?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    Some(strip_port(host))
+}
+
+fn strip_port(host: &str) -> &str {
+    match host.find(']') {
+        Some(end) if host.starts_with('[') => &host[..=end],
+        _ => host.split(':').next().unwrap_or_default(),
+    }
+}
+
+fn url_query(url: &str) -> Option<&str> {
+    let (_, query) = url.split('#').next()?.split_once('?')?;
+    Some(query)
+}
+
+// Allocations for a Vec of n items, allowing one each time it doubles
+fn growth(n: usize) -> usize {
+    n.checked_ilog2().map_or(0, |log| log as usize + 1)
+}
+
+// Names and values that are percent-decoded or have + replaced, each of which allocates
+fn decoded_parts(query: &str) -> usize {
+    query
+        .split(['&', '='])
+        .filter(|part| part.contains(['%', '+']))
+        .count()
+}
+
+#[test]
+fn allocations_authority() {
+    let urls = corpus("urls.txt");
+    let headers = corpus("headers.txt");
+    let mut inputs: Vec<_> = urls.iter().map(|url| (url.as_str(), 0)).collect();
+    inputs.extend(
+        header_fields(&headers)
+            .filter(|(name, _)| matches!(*name, "Referer" | "Origin"))
+            .map(|(_, value)| (value, 0)),
+    );
+
+    check(&inputs, |input| uri::authority_str(input).is_some());
+}
+
+#[test]
+fn allocations_host() {
+    // Hosts in their serialized form are borrowed, other domains are lowercased into a new
+    // string. A-labels are decoded to validate them.
+    let budget = |host: &str| {
+        if host.contains("xn--") {
+            16
+        } else {
+            usize::from(host.bytes().any(|c| c.is_ascii_uppercase()))
+        }
+    };
+
+    let urls = corpus("urls.txt");
+    let headers = corpus("headers.txt");
+    let mut inputs: Vec<_> = urls
+        .iter()
+        .filter_map(|url| url_host(url))
+        .map(|host| (host, budget(host)))
+        .collect();
+    inputs.extend(
+        header_fields(&headers)
+            .filter(|(name, _)| *name == "Host")
+            .map(|(_, value)| (strip_port(value), budget(value))),
+    );
+
+    check(&inputs, |input| url::Host::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_query_map() {
+    let urls = corpus("urls.txt");
+    let inputs: Vec<_> = urls
+        .iter()
+        .filter_map(|url| url_query(url))
+        .map(|query| {
+            let pairs = query.split('&').filter(|pair| !pair.is_empty()).count();
+            (query, growth(pairs) + decoded_parts(query))
+        })
+        .collect();
+
+    check(&inputs, |input| !url::QueryMap::parse(input).is_empty());
+}
+
+#[test]
+fn allocations_form_urlencoded() {
+    let urls = corpus("urls.txt");
+    let inputs: Vec<_> = urls
+        .iter()
+        .filter_map(|url| url_query(url))
+        .map(|query| (query, decoded_parts(query)))
+        .collect();
+
+    check(&inputs, |input| {
+        form_urlencoded::parse(input.as_bytes()).count() > 0
+    });
+}
+
+#[test]
+fn allocations_data_url() {
+    // Payloads that are not base64 or percent-encoded are borrowed, and a media type without
+    // parameters needs no parameter list
+    let budget = |url: &str| {
+        let (media_type, payload) = url["data:".len()..].split_once(',').unwrap();
+        let (media_type, base64) = match media_type.strip_suffix(";base64") {
+            Some(media_type) => (media_type, true),
+            None => (media_type, false),
+        };
+        usize::from(base64 || payload.contains('%'))
+            + usize::from(media_type.is_empty() || media_type.contains(';'))
+    };
+
+    let urls = corpus("urls.txt");
+    let inputs: Vec<_> = urls
+        .iter()
+        .filter(|url| url.starts_with("data:"))
+        .map(|url| (url.as_str(), budget(url)))
+        .collect();
+
+    check(&inputs, |input| url::DataUrl::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_mailto() {
+    // One for the addresses, one for the header fields, and one for each field that is
+    // percent-decoded
+    let budget = |uri: &str| {
+        let fields = uri.split_once('?').map_or("", |(_, fields)| fields);
+        1 + usize::from(!fields.is_empty())
+            + fields
+                .split('&')
+                .filter(|field| field.contains('%'))
+                .count()
+    };
+
+    let urls = corpus("urls.txt");
+    let inputs: Vec<_> = urls
+        .iter()
+        .filter(|url| url.starts_with("mailto:"))
+        .map(|url| (url.as_str(), budget(url)))
+        .collect();
+
+    check(&inputs, |input| uri::MailtoUri::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_headers() {
+    let headers = corpus("headers.txt");
+
+    // Valid field values are borrowed
+    let values: Vec<_> = header_fields(&headers)
+        .map(|(_, value)| (value, 0))
+        .collect();
+    check(&values, |input| {
+        http::HeaderValue::parse(input.as_bytes()).is_ok()
+    });
+    check(&values, |input| {
+        http::sanitize_header("x", input, http::SanitizeOptions::default()).is_ok()
+    });
+
+    let names: Vec<_> = header_fields(&headers).map(|(name, _)| (name, 0)).collect();
+    check(&names, |input| {
+        http::sanitize_header(input, "x", http::SanitizeOptions::default()).is_ok()
+    });
+
+    let accept_charset: Vec<_> = header_fields(&headers)
+        .filter(|(name, _)| *name == "Accept-Charset")
+        .map(|(_, value)| (value, growth(value.split(',').count())))
+        .collect();
+    check(&accept_charset, |input| {
+        http::AcceptCharset::parse(input).is_ok()
+    });
+}
+
+#[test]
+fn allocations_tel() {
+    let corpus = [
+        ("tel:+1-816-555-1212", 0),
+        ("tel:+18165551212;ext=22", 0),
+        ("tel:7042;phone-context=example.com", 0),
+        ("TEL:+1-816-555-1212;isub=1411", 0),
+        ("tel:+1-816-555-1212;foo=bar;baz", 2),
+        // Errors only record an offset
+        ("tel:+1-816-555-1212;ext=abc", 0),
+        ("tel:7042", 0),
+    ];
+
+    check(&corpus, |input| uri::TelUri::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_sip() {
+    let corpus = [
+        ("sip:alice@atlanta.com", 0),
+        ("sips:alice@atlanta.com:5061", 0),
+        ("sip:alice:secretword@atlanta.com", 0),
+        ("sip:[2001:db8::10]:5070", 0),
+        ("sip:alice@atlanta.com;transport=tcp", 1),
+        ("sip:atlanta.com;method=REGISTER?to=alice%40atlanta.com", 4),
+        // Decoding the userinfo allocates
+        ("sip:al%69ce@atlanta.com", 1),
+        ("sip:alice@atlanta.com:99999", 0),
+    ];
+
+    check(&corpus, |input| uri::SipUri::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_magnet() {
+    let corpus = [
+        ("magnet:?dn=file", 0),
+        (
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&xl=10826029",
+            1,
+        ),
+        (
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Some+File%21\
+             &tr=udp%3A%2F%2Ftracker.example.org%3A6969&tr=http://tracker.example.com/announce",
+            7,
+        ),
+        ("magnet:?xt=urn:btih:abc&xl=ten", 1),
+    ];
+
+    check(&corpus, |input| uri::MagnetLink::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_scp_like() {
+    let corpus = [
+        ("git@github.com:org/repo.git", 0),
+        ("host.xz:/srv/repo.git", 0),
+        ("git@[::1]:repo.git", 0),
+        ("ssh://git@github.com/org/repo.git", 0),
+        ("git@git hub.com:repo.git", 0),
+    ];
+
+    check(&corpus, |input| url::ScpLikeUrl::parse(input).is_ok());
+}
+
+#[test]
+fn allocations_ftp_type_code() {
+    let corpus = [
+        ("/pub/file.txt;type=a", 0),
+        ("/pub/file.txt", 0),
+        ("/pub/file.txt;type=x", 0),
+    ];
+
+    check(&corpus, |input| url::ftp_type_code(input).1.is_some());
+}
@@ -0,0 +1,220 @@
+// Golden tests for the output of the public parsers.
+//
+// Each file in tests/golden lists inputs on lines starting with "> ", each followed by the
+// expected output. Run with UPDATE_GOLDEN=1 to rewrite the files from the current behavior, then
+// review the diff.
+
+use std::{env, fmt::Write, fs, path::PathBuf};
+
+use parse::{form_urlencoded, http, punycode, uri, url};
+
+fn check(name: &str, render: impl Fn(&str) -> String) {
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect();
+    let expected = fs::read_to_string(&path).unwrap();
+
+    let mut actual = String::new();
+    for input in expected.lines().filter_map(|line| line.strip_prefix("> ")) {
+        writeln!(actual, "> {input}\n{}\n", render(input)).unwrap();
+    }
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    assert_eq!(
+        expected, actual,
+        "{name} is out of date, rerun with UPDATE_GOLDEN=1 and review the diff"
+    );
+}
+
+fn error(code: parse::ErrorCode, offset: usize) -> String {
+    format!("error: {code:?} at {offset}")
+}
+
+#[test]
+fn golden_scp_like() {
+    check("scp_like.txt", |input| {
+        match url::ScpLikeUrl::parse(input) {
+            Ok(url) => url.to_ssh_uri(),
+            Err(err) => error(err.code(), err.offset()),
+        }
+    });
+}
+
+#[test]
+fn golden_tel() {
+    check("tel.txt", |input| match uri::TelUri::parse(input) {
+        Ok(uri) => format!("digits={} e164={:?}", uri.digits(), uri.e164()),
+        Err(err) => error(err.code(), err.offset()),
+    });
+}
+
+#[test]
+fn golden_sip() {
+    check("sip.txt", |input| match uri::SipUri::parse(input) {
+        Ok(uri) => {
+            let mut out = format!(
+                "secure={} user={} password={} host={} port={}",
+                uri.is_secure(),
+                uri.user().unwrap_or("-"),
+                uri.password().unwrap_or("-"),
+                uri.host(),
+                uri.port().map_or("-".to_owned(), |port| port.to_string()),
+            );
+            for (name, value) in uri.parameters() {
+                match value {
+                    Some(value) => write!(out, "\nparameter {name}={value}").unwrap(),
+                    None => write!(out, "\nparameter {name}").unwrap(),
+                }
+            }
+            for (name, value) in uri.headers() {
+                write!(out, "\nheader {name}={value}").unwrap();
+            }
+            out
+        }
+        Err(err) => error(err.code(), err.offset()),
+    });
+}
+
+#[test]
+fn golden_magnet() {
+    check("magnet.txt", |input| match uri::MagnetLink::parse(input) {
+        Ok(link) => {
+            let mut out = format!(
+                "name={} length={}",
+                link.display_name().unwrap_or("-"),
+                link.exact_length()
+                    .map_or("-".to_owned(), |length| length.to_string()),
+            );
+            for topic in link.exact_topics() {
+                write!(out, "\ntopic {}:{}", topic.namespace(), topic.hash()).unwrap();
+            }
+            for tracker in link.trackers() {
+                write!(out, "\ntracker {tracker}").unwrap();
+            }
+            out
+        }
+        Err(err) => error(err.code(), err.offset()),
+    });
+}
+
+#[test]
+fn golden_ftp_type_code() {
+    check("ftp_type_code.txt", |input| {
+        let (path, code) = url::ftp_type_code(input);
+        format!("{path} {:?}", code.map(url::FtpTypeCode::as_str))
+    });
+}
+
+fn render_host(input: &str) -> String {
+    match url::Host::parse(input) {
+        Ok(host) => host.to_string(),
+        Err(err) => format!("error: {err}"),
+    }
+}
+
+#[test]
+fn golden_host() {
+    check("host.txt", render_host);
+}
+
+#[cfg(feature = "idna")]
+#[test]
+fn golden_host_idna() {
+    check("host_idna.txt", render_host);
+}
+
+#[test]
+fn golden_form_urlencoded() {
+    check("form_urlencoded.txt", |input| {
+        form_urlencoded::serialize(form_urlencoded::parse(input.as_bytes()))
+    });
+}
+
+#[test]
+fn golden_query() {
+    check("query.txt", |input| {
+        let mut map = url::QueryMap::parse(input);
+        let mut out = String::new();
+        for policy in [
+            url::DuplicatePolicy::KeepAll,
+            url::DuplicatePolicy::KeepFirst,
+            url::DuplicatePolicy::KeepLast,
+        ] {
+            writeln!(out, "{policy:?}: {}", map.to_canonical_string(policy)).unwrap();
+        }
+        map.set("a", "1 2");
+        write!(out, "set a: {}", map.serialize()).unwrap();
+        out
+    });
+}
+
+// Values that parse but can not be written back, such as text with obs-text, render the error
+fn render_list<T>(
+    list: Result<Vec<T>, http::HeaderError>,
+    to_field_value: impl Fn(&T) -> Result<String, http::HeaderError>,
+) -> String {
+    match list {
+        Ok(list) => list
+            .iter()
+            .map(|item| to_field_value(item).unwrap_or_else(|err| error(err.code(), err.offset())))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Err(err) => error(err.code(), err.offset()),
+    }
+}
+
+#[test]
+fn golden_warning() {
+    check("warning.txt", |input| {
+        render_list(
+            http::Warning::parse_list(input),
+            http::Warning::to_field_value,
+        )
+    });
+}
+
+#[test]
+fn golden_cache_status() {
+    check("cache_status.txt", |input| {
+        render_list(
+            http::CacheStatus::parse_list(input),
+            http::CacheStatus::to_field_value,
+        )
+    });
+}
+
+#[test]
+fn golden_punycode() {
+    check("punycode.txt", |input| match punycode::encode(input) {
+        Ok(encoded) => {
+            assert_eq!(input, punycode::decode(&encoded).unwrap(), "{input}");
+            encoded
+        }
+        Err(err) => error(err.code(), err.offset()),
+    });
+}
+
+#[test]
+fn golden_percent_encode() {
+    let sets = [
+        ("c0", url::AsciiSet::C0_CONTROL),
+        ("fragment", url::AsciiSet::FRAGMENT),
+        ("query", url::AsciiSet::QUERY),
+        ("special-query", url::AsciiSet::SPECIAL_QUERY),
+        ("path", url::AsciiSet::PATH),
+        ("userinfo", url::AsciiSet::USERINFO),
+        ("component", url::AsciiSet::COMPONENT),
+        ("form", url::AsciiSet::FORM_URLENCODED),
+    ];
+
+    check("percent_encode.txt", |input| {
+        sets.iter()
+            .map(|(name, set)| format!("{name}: {}", url::percent_encode(input, set)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+}
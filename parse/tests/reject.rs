@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+
+use parse::{
+    email,
+    http::{AcceptCharset, CacheStatus, HeaderValue, StatusCode, Warning},
+    uri::iri_to_uri,
+    url::{Host, Scheme},
+    ErrorCode,
+};
+
+// The hook is process wide, so everything that installs one is in a single test
+#[test]
+fn test_rejection_hook() {
+    let rejections = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&rejections);
+    parse::set_rejection_hook(move |rejection| {
+        recorded
+            .lock()
+            .unwrap()
+            .push((rejection.code(), rejection.range()));
+    });
+
+    assert!(Host::parse("exa mple.com").is_err());
+    assert!(Scheme::parse("1http").is_err());
+    assert!(AcceptCharset::parse("utf-8;q=2").is_err());
+    assert!(HeaderValue::parse(b"a\r\nb").is_err());
+    assert!(HeaderValue::parse(b"\xff\n").is_err());
+    assert!("600".parse::<StatusCode>().is_err());
+    assert!("2xx".parse::<StatusCode>().is_err());
+    assert!(Warning::parse_list("").is_err());
+    assert!(CacheStatus::parse_list("a,").is_err());
+    assert!(email::validate("user.example.com").is_err());
+    // The local-part limit falls inside a 2 byte character
+    let long_local_part = format!("a{}@example.com", "\u{e9}".repeat(40));
+    assert!(email::validate(&long_local_part).is_err());
+    #[cfg(feature = "idna")]
+    assert!(iri_to_uri("http://\u{fc}\u{200d}.example/").is_err());
+
+    // Inputs that parse successfully are not reported
+    assert!(Host::parse("example.com").is_ok());
+    assert!(iri_to_uri("http://example.com/").is_ok());
+
+    let mut expected = vec![
+        (ErrorCode::InvalidHost, 3..4),
+        (ErrorCode::InvalidScheme, 0..1),
+        (ErrorCode::InvalidHeader, 5..6),
+        (ErrorCode::InvalidHeader, 1..2),
+        (ErrorCode::InvalidHeader, 1..2),
+        (ErrorCode::InvalidStatus, 0..1),
+        (ErrorCode::InvalidSyntax, 0..1),
+        (ErrorCode::InvalidHeader, 0..0),
+        (ErrorCode::InvalidHeader, 2..2),
+        (ErrorCode::InvalidSyntax, 16..16),
+        (ErrorCode::InvalidUserinfo, 63..65),
+    ];
+    if cfg!(feature = "idna") {
+        expected.push((ErrorCode::InvalidHost, 7..9));
+    }
+    assert_eq!(expected, *rejections.lock().unwrap());
+
+    // The hook is called without holding a lock, so it can replace itself and parse
+    parse::set_rejection_hook(|rejection| {
+        let hook = parse::take_rejection_hook();
+        assert!(hook.is_some());
+        assert!(Scheme::parse("").is_err());
+        parse::set_rejection_hook(|_| {});
+        assert_eq!(ErrorCode::InvalidScheme, rejection.code());
+    });
+    assert!(Scheme::parse("-").is_err());
+    assert!(Scheme::parse("-").is_err());
+
+    assert!(parse::take_rejection_hook().is_some());
+}